@@ -0,0 +1,252 @@
+//! Styled output for terminals, using ANSI escape sequences.
+
+use std::borrow::Cow;
+
+use crate::render::{RenderOptions, prepare};
+use crate::text::Style;
+use crate::visit::{
+    VisitorMut, walk_block_mut, walk_field_mut, walk_inline_mut, walk_option_item_mut, walk_py_object_mut,
+};
+use crate::{Block, Field, Inline, OptionItem, PyObject};
+
+/// Renders blocks for display in a terminal.
+///
+/// The layout is the one [`TextRenderer`](crate::TextRenderer) uses, with styling on top: headings
+/// are bold (and underlined at the top level), emphasis is italic, code is coloured or dimmed,
+/// admonitions sit in boxes coloured by their kind and tables are drawn with box-drawing
+/// characters. Links become OSC 8 hyperlinks, which terminals without support show as plain text.
+///
+/// Each feature can be switched off; with all of them off ([`AnsiRenderer::plain`]) the output is
+/// plain text. Control characters in the document are removed, so it can't inject escape sequences
+/// of its own.
+///
+/// ```
+/// use parserst::{AnsiRenderer, parse};
+///
+/// let blocks = parse("Some *emphasis*.").unwrap();
+/// assert_eq!(AnsiRenderer::new().render(&blocks), "Some \x1b[3memphasis\x1b[0m.\n");
+/// assert_eq!(AnsiRenderer::plain().render(&blocks), "Some emphasis.\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct AnsiRenderer {
+    width: usize,
+    style: Style,
+    options: RenderOptions,
+}
+
+impl Default for AnsiRenderer {
+    fn default() -> Self {
+        Self {
+            width: 79,
+            style: Style { color: true, hyperlinks: true, boxes: true },
+            options: RenderOptions::default(),
+        }
+    }
+}
+
+impl AnsiRenderer {
+    /// Defaults to colour, hyperlinks and box drawing, wrapping at 79 columns.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Plain text with no escape sequences or box drawing, for output that isn't a terminal.
+    pub fn plain() -> Self {
+        Self::new().color(false).hyperlinks(false).boxes(false)
+    }
+
+    /// Configure from the environment: colour is off when `NO_COLOR` is set to a non-empty value,
+    /// and the width comes from `COLUMNS` when it holds a number.
+    pub fn from_env() -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        let renderer = Self::new().color(!no_color);
+        match std::env::var("COLUMNS")
+            .ok()
+            .and_then(|columns| columns.trim().parse().ok())
+        {
+            Some(width) => renderer.width(width),
+            None => renderer,
+        }
+    }
+
+    /// Wrap paragraphs and list items to `width` columns; `usize::MAX` keeps each on one line.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Emit colours and text attributes (SGR sequences).
+    pub fn color(mut self, color: bool) -> Self {
+        self.style.color = color;
+        self
+    }
+
+    /// Emit links as OSC 8 hyperlinks; otherwise they read `text (url)`.
+    pub fn hyperlinks(mut self, hyperlinks: bool) -> Self {
+        self.style.hyperlinks = hyperlinks;
+        self
+    }
+
+    /// Draw tables and admonitions with box-drawing characters; otherwise tables use ASCII and
+    /// admonitions are a label with an indented body.
+    pub fn boxes(mut self, boxes: bool) -> Self {
+        self.style.boxes = boxes;
+        self
+    }
+
+    /// Apply `options` (raw content handling, conditional directives) when rendering a document.
+    pub fn options(mut self, options: RenderOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Render a document, separating blocks with blank lines.
+    pub fn render(&self, blocks: &[Block]) -> String {
+        let mut blocks = prepare(blocks, &self.options).into_owned();
+        StripControls.visit_blocks_mut(&mut blocks);
+        let mut out = self.style.blocks(&blocks, self.width);
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Render inline content as a single unwrapped line.
+    pub fn render_inlines(&self, inlines: &[Inline]) -> String {
+        let mut inlines = inlines.to_vec();
+        StripControls.visit_inlines_mut(&mut inlines);
+        self.style.words(&inlines, &[]).join(" ")
+    }
+}
+
+/// Removes control characters other than newlines and tabs from every string in the tree.
+struct StripControls;
+
+fn strip(s: &mut String) {
+    s.retain(|c| !c.is_control() || c == '\n' || c == '\t');
+}
+
+/// [`strip`] text that may be borrowed, copying it only if it has something to remove.
+fn strip_cow(s: &mut Cow<'_, str>) {
+    if s.contains(|c: char| c.is_control() && c != '\n' && c != '\t') {
+        strip(s.to_mut());
+    }
+}
+
+impl VisitorMut for StripControls {
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        match block {
+            Block::CodeBlock(text) | Block::LiteralBlock(text) => strip_cow(text),
+            Block::Raw { content: text, .. } => strip(text),
+            Block::Directive { argument, .. } => strip(argument),
+            Block::Target { name } | Block::Footnote { label: name, .. } => strip(name),
+            _ => {}
+        }
+        walk_block_mut(self, block);
+    }
+
+    fn visit_inline_mut(&mut self, inline: &mut Inline) {
+        match inline {
+            Inline::Text(text) | Inline::Code(text) | Inline::Link { url: text, .. } => strip_cow(text),
+            Inline::FootnoteRef(label) => strip(label),
+            Inline::Em(_) | Inline::Strong(_) => {}
+        }
+        walk_inline_mut(self, inline);
+    }
+
+    fn visit_field_mut(&mut self, field: &mut Field) {
+        strip(&mut field.name);
+        strip(&mut field.argument);
+        walk_field_mut(self, field);
+    }
+
+    fn visit_option_item_mut(&mut self, item: &mut OptionItem) {
+        item.options.iter_mut().for_each(strip);
+        walk_option_item_mut(self, item);
+    }
+
+    fn visit_py_object_mut(&mut self, obj: &mut PyObject) {
+        let signature = &mut obj.signature;
+        signature.decorators.iter_mut().for_each(strip);
+        strip(&mut signature.prefix);
+        strip(&mut signature.name);
+        for param in signature.params.iter_mut().flatten() {
+            strip(&mut param.name);
+            param.annotation.iter_mut().chain(&mut param.default).for_each(strip);
+        }
+        signature.return_annotation.iter_mut().for_each(strip);
+        walk_py_object_mut(self, obj);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TextRenderer, parse};
+
+    #[test]
+    fn styles_headings_emphasis_and_code() {
+        let blocks = parse("Title\n=====\n\nSub\n---\n\n**Bold** and ``code``.\n").unwrap();
+        assert_eq!(
+            AnsiRenderer::new().render(&blocks),
+            "\x1b[1;4mTitle\x1b[0m\n\n\x1b[1mSub\x1b[0m\n\n\x1b[1mBold\x1b[0m and \x1b[36mcode\x1b[0m.\n"
+        );
+    }
+
+    #[test]
+    fn links_are_osc8_hyperlinks() {
+        let blocks = parse("See `the docs <https://a.b>`_.").unwrap();
+        assert_eq!(
+            AnsiRenderer::new().color(false).render(&blocks),
+            "See \x1b]8;;https://a.b\x1b\\the\x1b]8;;\x1b\\ \x1b]8;;https://a.b\x1b\\docs\x1b]8;;\x1b\\.\n"
+        );
+    }
+
+    #[test]
+    fn wraps_by_visible_width() {
+        let blocks = parse("*one two three four*").unwrap();
+        let out = AnsiRenderer::new().width(9).render(&blocks);
+        assert_eq!(
+            out,
+            "\x1b[3mone\x1b[0m \x1b[3mtwo\x1b[0m\n\x1b[3mthree\x1b[0m\n\x1b[3mfour\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn admonitions_and_tables_are_boxed() {
+        let doc = ".. warning::\n\n   Mind the gap.\n\n===== =====\nA     B\n===== =====\nx     y\n===== =====\n";
+        let blocks = parse(doc).unwrap();
+        assert_eq!(
+            AnsiRenderer::new().color(false).render(&blocks),
+            "\
+╭─ Warning ─────╮
+│ Mind the gap. │
+╰───────────────╯
+
+┌───┬───┐
+│ A │ B │
+╞═══╪═══╡
+│ x │ y │
+└───┴───┘
+"
+        );
+        let colored = AnsiRenderer::new().render(&blocks);
+        assert!(colored.starts_with("\x1b[33m╭─ \x1b[0m\x1b[1;33mWarning\x1b[0m"));
+    }
+
+    #[test]
+    fn plain_matches_the_text_renderer() {
+        let doc = "Title\n=====\n\n.. note:: Careful.\n\n`link <https://a.b>`_ and *em*.\n";
+        let blocks = parse(doc).unwrap();
+        assert_eq!(
+            AnsiRenderer::plain().render(&blocks),
+            TextRenderer::new().render(&blocks)
+        );
+    }
+
+    #[test]
+    fn strips_escape_sequences_from_the_document() {
+        let blocks = vec![Block::Paragraph(vec![Inline::Text("a\x1b[31mred".into())])];
+        assert_eq!(AnsiRenderer::plain().render(&blocks), "a[31mred\n");
+    }
+}
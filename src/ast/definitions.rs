@@ -1,5 +1,5 @@
 use super::{Block, Field, Inline, list_kind};
-use crate::{Lines, ParseError, is_blank, leading_indent, parse, strip_indent_preserve};
+use crate::{Lines, ParseError, ParserOptions, is_blank, leading_indent, parse_nested, strip_indent_preserve};
 
 fn is_definition_entry(s: &str) -> bool {
     let indent = leading_indent(s);
@@ -21,21 +21,23 @@ fn is_definition_entry(s: &str) -> bool {
     }
 }
 
-fn build_definition_blocks(term: &str, classifier: Option<&str>, body_text: &str) -> Result<Vec<Block>, ParseError> {
-    let mut blocks = if body_text.trim().is_empty() { Vec::new() } else { parse(body_text)? };
+fn build_definition_blocks<'a>(
+    term: &'a str, classifier: Option<&'a str>, body_text: &str, opts: &ParserOptions,
+) -> Result<Vec<Block<'a>>, ParseError> {
+    let mut blocks = if body_text.trim().is_empty() { Vec::new() } else { parse_nested(body_text, opts)? };
 
     let mut label = Vec::new();
-    label.push(Inline::Strong(vec![Inline::Text(term.to_string())]));
-    if let Some(classifier) = classifier {
-        if !classifier.is_empty() {
-            label.push(Inline::Text(" (".into()));
-            label.push(Inline::Em(vec![Inline::Text(classifier.to_string())]));
-            label.push(Inline::Text(")".into()));
-        }
+    label.push(Inline::Strong(vec![Inline::Text(term.into())]));
+    if let Some(classifier) = classifier
+        && !classifier.is_empty()
+    {
+        label.push(Inline::Text(" (".into()));
+        label.push(Inline::Em(vec![Inline::Text(classifier.into())]));
+        label.push(Inline::Text(")".into()));
     }
 
     if blocks.is_empty() {
-        blocks.push(Block::Paragraph(label));
+        blocks.push(Block::Paragraph(merge_text(label)));
         return Ok(blocks);
     }
 
@@ -44,54 +46,65 @@ fn build_definition_blocks(term: &str, classifier: Option<&str>, body_text: &str
             if !inlines.is_empty() {
                 label.push(Inline::Text(": ".into()));
                 label.append(inlines);
-                *inlines = label;
-            } else {
-                *inlines = label;
             }
+            *inlines = merge_text(label);
         }
         _ => {
             let mut para = label;
             para.push(Inline::Text(":".into()));
-            blocks.insert(0, Block::Paragraph(para));
+            blocks.insert(0, Block::Paragraph(merge_text(para)));
         }
     }
 
     Ok(blocks)
 }
 
-fn split_definition_line(input: &str) -> (String, Option<String>, String) {
+/// Join neighbouring text nodes, so the label reads back the same way once written out as markup.
+fn merge_text(inlines: Vec<Inline<'_>>) -> Vec<Inline<'_>> {
+    let mut merged: Vec<Inline> = Vec::with_capacity(inlines.len());
+    for inline in inlines {
+        match (merged.last_mut(), inline) {
+            (Some(Inline::Text(prev)), Inline::Text(text)) => prev.to_mut().push_str(&text),
+            (_, inline) => merged.push(inline),
+        }
+    }
+    merged
+}
+
+fn split_definition_line(input: &str) -> (&str, Option<&str>, &str) {
     let idx = input.find(':').unwrap_or(input.len());
-    let term_part = input[..idx].trim();
-    let mut term = term_part.to_string();
+    let mut term = input[..idx].trim();
 
     let after = if idx < input.len() { &input[idx + 1..] } else { "" };
     let prev_is_space = idx > 0 && input.as_bytes()[idx - 1] == b' ';
     let next_is_space = after.starts_with(' ');
 
     let mut classifier = None;
-    if term.ends_with(')') {
-        if let Some(open_idx) = term.rfind('(') {
-            let inner = term[open_idx + 1..term.len() - 1].trim();
-            if !inner.is_empty() {
-                classifier = Some(inner.to_string());
-                term = term[..open_idx].trim().to_string();
-            }
+    if term.ends_with(')')
+        && let Some(open_idx) = term.rfind('(')
+    {
+        let inner = term[open_idx + 1..term.len() - 1].trim();
+        if !inner.is_empty() {
+            classifier = Some(inner);
+            term = term[..open_idx].trim();
         }
     }
 
     let after_trim = after.trim();
-    let mut body_initial = String::new();
+    let mut body_initial = "";
 
     if prev_is_space && next_is_space && !after_trim.is_empty() && classifier.is_none() {
-        classifier = Some(after_trim.to_string());
+        classifier = Some(after_trim);
     } else if !after_trim.is_empty() {
-        body_initial = after_trim.to_string();
+        body_initial = after_trim;
     }
 
     (term, classifier, body_initial)
 }
 
-pub fn parse_definition_entries(ls: &mut Lines<'_>) -> Result<Option<Vec<Block>>, ParseError> {
+pub fn parse_definition_entries<'a>(
+    ls: &mut Lines<'a>, opts: &ParserOptions,
+) -> Result<Option<Vec<Block<'a>>>, ParseError> {
     let Some(line) = ls.peek() else {
         return Ok(None);
     };
@@ -109,26 +122,25 @@ pub fn parse_definition_entries(ls: &mut Lines<'_>) -> Result<Option<Vec<Block>>
 
         let line = ls.next().unwrap();
         let trimmed = line.raw.trim_start();
-        let (term, classifier_opt, body_initial) = split_definition_line(trimmed);
-        let classifier_ref = classifier_opt.as_deref();
+        let (term, classifier, body_initial) = split_definition_line(trimmed);
         let indent_base = leading_indent(line.raw);
         let mut body_text = String::new();
 
         if !body_initial.is_empty() {
-            body_text.push_str(&body_initial);
+            body_text.push_str(body_initial);
         }
 
         while let Some(next) = ls.peek() {
             if is_blank(next.raw) {
-                if let Some(after_blank) = ls.peek_next() {
-                    if leading_indent(after_blank.raw) > indent_base {
-                        ls.next();
-                        if !body_text.is_empty() {
-                            body_text.push('\n');
-                            body_text.push('\n');
-                        }
-                        continue;
+                if let Some(after_blank) = ls.peek_next()
+                    && leading_indent(after_blank.raw) > indent_base
+                {
+                    ls.next();
+                    if !body_text.is_empty() {
+                        body_text.push('\n');
+                        body_text.push('\n');
                     }
+                    continue;
                 }
                 break;
             }
@@ -155,23 +167,29 @@ pub fn parse_definition_entries(ls: &mut Lines<'_>) -> Result<Option<Vec<Block>>
             body_text.push_str(stripped);
         }
 
-        let mut entry_blocks = build_definition_blocks(&term, classifier_ref, &body_text)?;
+        let mut entry_blocks = build_definition_blocks(term, classifier, &body_text, opts)?;
         blocks.append(&mut entry_blocks);
     }
 
     Ok(Some(blocks))
 }
 
+/// Whether `s` opens a field: a `:name:` marker followed by whitespace or the end of the line, so
+/// text starting with a role such as :literal:`x` is not one.
 fn is_field_line(s: &str) -> bool {
     let t = s.trim_start();
     if !t.starts_with(':') {
         return false;
     }
     let rest = &t[1..];
-    if let Some(end) = rest.find(':') { !rest[..end].trim().is_empty() } else { false }
+    if let Some(end) = rest.find(':') {
+        !rest[..end].trim().is_empty() && rest[end + 1..].chars().next().is_none_or(char::is_whitespace)
+    } else {
+        false
+    }
 }
 
-pub fn parse_field_entries(ls: &mut Lines<'_>) -> Result<Option<Block>, ParseError> {
+pub fn parse_field_entries<'a>(ls: &mut Lines<'a>, opts: &ParserOptions) -> Result<Option<Block<'a>>, ParseError> {
     let Some(line) = ls.peek() else {
         return Ok(None);
     };
@@ -200,6 +218,7 @@ pub fn parse_field_entries(ls: &mut Lines<'_>) -> Result<Option<Block>, ParseErr
             body_text.push_str(body_initial);
         }
         let indent_base = leading_indent(line.raw);
+        let mut body_indent = None;
 
         while let Some(next) = ls.peek() {
             if is_blank(next.raw) {
@@ -227,7 +246,7 @@ pub fn parse_field_entries(ls: &mut Lines<'_>) -> Result<Option<Block>, ParseErr
             }
 
             let cont = ls.next().unwrap();
-            let strip_amount = indent_base + 1;
+            let strip_amount = *body_indent.get_or_insert(indent);
             let stripped = strip_indent_preserve(cont.raw, strip_amount).trim_end();
             if !body_text.is_empty() {
                 body_text.push('\n');
@@ -235,7 +254,7 @@ pub fn parse_field_entries(ls: &mut Lines<'_>) -> Result<Option<Block>, ParseErr
             body_text.push_str(stripped);
         }
 
-        let body = if body_text.trim().is_empty() { Vec::new() } else { parse(&body_text)? };
+        let body = if body_text.trim().is_empty() { Vec::new() } else { parse_nested(&body_text, opts)? };
 
         fields.push(Field { name: kind.to_string(), argument: arg.unwrap_or("").to_string(), body });
     }
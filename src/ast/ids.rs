@@ -0,0 +1,254 @@
+//! Anchor ids for section headings and hyperlink targets.
+
+use std::collections::HashSet;
+
+use super::{Block, Inline};
+use crate::visit::{Visitor, VisitorMut, walk_block};
+
+/// Turn `name` into an HTML id the way docutils' `make_id` does.
+///
+/// The name is lowercased, accented Latin letters lose their accents, other non-ASCII characters are
+/// dropped, and every run of remaining non-alphanumeric characters becomes a single `-`. Leading digits
+/// and hyphens are stripped, so the result may be empty.
+///
+/// ```
+/// use parserst::make_id;
+///
+/// assert_eq!(make_id("2. Getting Started!"), "getting-started");
+/// assert_eq!(make_id("Überblick & Ausblick"), "uberblick-ausblick");
+/// ```
+pub fn make_id(name: &str) -> String {
+    let mut id = String::new();
+    let mut separated = false;
+    for c in name.chars().flat_map(char::to_lowercase) {
+        let mut buf = [0; 4];
+        let folded: &str = if c.is_ascii() {
+            c.encode_utf8(&mut buf)
+        } else if c.is_whitespace() {
+            " "
+        } else {
+            fold_to_ascii(c)
+        };
+        for c in folded.chars() {
+            if c.is_ascii_alphanumeric() {
+                if separated && !id.is_empty() {
+                    id.push('-');
+                }
+                separated = false;
+                id.push(c);
+            } else {
+                separated = true;
+            }
+        }
+    }
+    id.trim_start_matches(|c: char| c.is_ascii_digit() || c == '-')
+        .to_string()
+}
+
+/// ASCII spelling of a lowercase Latin letter, mirroring docutils' NFKD decomposition and the
+/// extra letters it transliterates; anything else has none.
+fn fold_to_ascii(c: char) -> &'static str {
+    match c {
+        'à'..='å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'ď' | 'đ' => "d",
+        'è'..='ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'ĥ' | 'ħ' => "h",
+        'ì'..='ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'ĵ' => "j",
+        'ķ' => "k",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'ò'..='ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'œ' => "oe",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'ß' => "sz",
+        'ś' | 'ŝ' | 'ş' | 'š' | 'ſ' => "s",
+        'ţ' | 'ť' | 'ŧ' => "t",
+        'ù'..='ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'ŵ' => "w",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => "",
+    }
+}
+
+/// The text of `inlines` with all markup removed.
+fn plain_text(inlines: &[Inline]) -> String {
+    let mut out = String::new();
+    for inline in inlines {
+        match inline {
+            Inline::Text(t) | Inline::Code(t) => out.push_str(t),
+            Inline::Em(children) | Inline::Strong(children) | Inline::Link { text: children, .. } => {
+                out.push_str(&plain_text(children))
+            }
+            Inline::FootnoteRef(_) => {}
+        }
+    }
+    out
+}
+
+/// Whether `block` is a target that supplies the id of the heading `next`, and so has no anchor of its own.
+pub(crate) fn names_heading(block: &Block, next: Option<&Block>) -> bool {
+    match (block, next) {
+        (Block::Target { name }, Some(Block::Heading { id, .. })) => make_id(name) == *id,
+        _ => false,
+    }
+}
+
+/// Give every heading a document-unique id.
+///
+/// A heading directly preceded by a `.. _label:` target takes the target's id. Others get
+/// [`make_id`] of their title, with `-1`, `-2`, ... appended on collisions (`id1`, `id2`, ... for
+/// titles that leave no id characters).
+pub fn assign_heading_ids(blocks: &mut [Block]) {
+    let mut targets = TargetIds(HashSet::new());
+    targets.visit_blocks(blocks);
+    HeadingIds { used: targets.0 }.visit_blocks_mut(blocks);
+}
+
+/// The ids of every target in a document, for [`HeadingIdStream`]; feed it the blocks in order.
+#[derive(Debug, Default)]
+pub(crate) struct TargetIds(HashSet<String>);
+
+impl Visitor for TargetIds {
+    fn visit_block(&mut self, block: &Block) {
+        if let Block::Target { name } = block {
+            self.0.insert(make_id(name));
+        }
+        walk_block(self, block);
+    }
+}
+
+/// [`assign_heading_ids`] for a document that arrives a few top-level blocks at a time.
+#[derive(Debug)]
+pub(crate) struct HeadingIdStream {
+    ids: HeadingIds,
+    /// The id supplied by a target at the end of the previous blocks.
+    label: Option<String>,
+}
+
+impl HeadingIdStream {
+    /// Heading titles must not collide with any target, including ones later in the document, so
+    /// every target has to be [added](Self::add_targets) before the first heading.
+    pub(crate) fn new() -> Self {
+        Self { ids: HeadingIds { used: HashSet::new() }, label: None }
+    }
+
+    /// Keep heading ids from colliding with `targets`.
+    pub(crate) fn add_targets(&mut self, targets: TargetIds) {
+        self.ids.used.extend(targets.0);
+    }
+
+    /// Assign ids in the next top-level `blocks` of the document.
+    pub(crate) fn assign(&mut self, blocks: &mut [Block]) {
+        self.label = self.ids.assign(blocks, self.label.take());
+    }
+}
+
+#[derive(Debug)]
+struct HeadingIds {
+    used: HashSet<String>,
+}
+
+impl HeadingIds {
+    fn unique(&mut self, base: &str) -> String {
+        let mut id = base.to_string();
+        let mut n = 0;
+        while id.is_empty() || self.used.contains(&id) {
+            n += 1;
+            id = if base.is_empty() { format!("id{n}") } else { format!("{base}-{n}") };
+        }
+        self.used.insert(id.clone());
+        id
+    }
+
+    /// Assign ids in `blocks`, where `label` is the id from a target just before them; returns the
+    /// one the last of them supplies.
+    fn assign(&mut self, blocks: &mut [Block], mut label: Option<String>) -> Option<String> {
+        for block in blocks {
+            if let Block::Heading { inlines, id, .. } = block {
+                *id = match label.filter(|label| !label.is_empty()) {
+                    Some(label) => label,
+                    None => self.unique(&make_id(&plain_text(inlines))),
+                };
+            }
+            label = match block {
+                Block::Target { name } => Some(make_id(name)),
+                _ => None,
+            };
+            self.visit_block_mut(block);
+        }
+        label
+    }
+}
+
+impl VisitorMut for HeadingIds {
+    fn visit_blocks_mut(&mut self, blocks: &mut [Block]) {
+        self.assign(blocks, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn heading_ids<'a>(blocks: &'a [Block]) -> Vec<&'a str> {
+        let mut ids = Vec::new();
+        for block in blocks {
+            match block {
+                Block::Heading { id, .. } => ids.push(id.as_str()),
+                Block::Directive { content, .. } => ids.extend(heading_ids(content)),
+                _ => {}
+            }
+        }
+        ids
+    }
+
+    #[test]
+    fn make_id_follows_docutils() {
+        assert_eq!(make_id("Hello, World"), "hello-world");
+        assert_eq!(make_id("  --Leading and trailing--  "), "leading-and-trailing");
+        assert_eq!(make_id("3.14 is pi"), "is-pi");
+        assert_eq!(make_id("snake_case and CamelCase"), "snake-case-and-camelcase");
+        assert_eq!(make_id("Straße, Œuvre, Łódź"), "strasze-oeuvre-lodz");
+        assert_eq!(make_id("Café\u{a0}crème"), "cafe-creme");
+        assert_eq!(make_id("a中文b"), "ab");
+        assert_eq!(make_id("表格"), "");
+    }
+
+    #[test]
+    fn colliding_titles_get_numeric_suffixes() {
+        let doc = "Usage\n=====\n\nUsage\n-----\n\n表格\n----\n\n.. note::\n\n   Usage\n   -----\n\n中文\n----\n";
+        assert_eq!(
+            heading_ids(&parse(doc).unwrap()),
+            ["usage", "usage-1", "id1", "usage-2", "id2"]
+        );
+    }
+
+    #[test]
+    fn targets_supply_heading_ids() {
+        let doc = ".. _Getting Started:\n\nIntro\n=====\n\nInstall\n-------\n\n.. _install:\n\nText.\n";
+        let blocks = parse(doc).unwrap();
+        assert_eq!(blocks[0], Block::Target { name: "Getting Started".into() });
+        // `install` is taken by the standalone target, so the title falls back to a suffix.
+        assert_eq!(heading_ids(&blocks), ["getting-started", "install-1"]);
+    }
+
+    #[test]
+    fn targets_with_urls_and_anonymous_targets_stay_comments() {
+        for doc in [
+            ".. _x: https://example.com\n",
+            ".. _x:\n   https://example.com\n",
+            ".. __:\n",
+        ] {
+            let blocks = parse(doc).unwrap();
+            assert!(matches!(blocks[..], [Block::Comment(_)]), "{doc}: {blocks:?}");
+        }
+        let blocks = parse(".. _`a: b`:\n").unwrap();
+        assert_eq!(blocks, [Block::Target { name: "a: b".into() }]);
+    }
+}
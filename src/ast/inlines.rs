@@ -1,9 +1,41 @@
-use crate::Inline;
+use std::borrow::Cow;
+use std::ops::Range;
+
+use crate::{Inline, ParserOptions};
+
+/// Characters that a backslash turns into literal text.
+const ESCAPABLE: &[u8] = b"\\*`[<";
+
+/// Whether `text[i..]` starts with a backslash escape.
+fn is_escape(bytes: &[u8], i: usize) -> bool {
+    bytes[i] == b'\\' && bytes.get(i + 1).is_some_and(|b| ESCAPABLE.contains(b))
+}
+
+/// Find the first `pat` in `text` that is not backslash-escaped.
+fn find_unescaped(text: &str, pat: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < text.len() {
+        if is_escape(bytes, i) {
+            i += 2;
+        } else if bytes[i..].starts_with(pat.as_bytes()) {
+            return Some(i);
+        } else {
+            i += 1;
+        }
+    }
+    None
+}
 
 /// Find closing single asterisk that is not part of a double asterisk
 fn find_single_asterisk_close(text: &str) -> Option<usize> {
     let bytes = text.as_bytes();
-    for i in 0..text.len() {
+    let mut i = 0;
+    while i < text.len() {
+        if is_escape(bytes, i) {
+            i += 2;
+            continue;
+        }
         if bytes[i] == b'*' {
             let before_is_asterisk = i > 0 && bytes[i - 1] == b'*';
             let after_is_asterisk = i + 1 < bytes.len() && bytes[i + 1] == b'*';
@@ -11,96 +43,181 @@ fn find_single_asterisk_close(text: &str) -> Option<usize> {
                 return Some(i);
             }
         }
+        i += 1;
     }
     None
 }
 
-/// Recursive descent parser for inline markup with nesting support.
-/// Handles **strong**, *em*, `code`, and `text <url>`_ references.
-pub fn parse_inlines(text: &str) -> Vec<Inline> {
+/// Replace backslash escapes with the characters they protect, borrowing `text` when it has none.
+fn unescape(text: &str) -> Cow<'_, str> {
+    let bytes = text.as_bytes();
+    if !(0..text.len()).any(|i| is_escape(bytes, i)) {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    let mut i = 0;
+    while i < text.len() {
+        if is_escape(bytes, i) {
+            out.push_str(&text[last..i]);
+            last = i + 1;
+            i += 2;
+        } else {
+            i += 1;
+        }
+    }
+    out.push_str(&text[last..]);
+    Cow::Owned(out)
+}
+
+/// Whether `label` names a footnote: a number, `#` or `#name` for auto-numbering, or `*` for a symbol.
+pub(crate) fn is_footnote_label(label: &str) -> bool {
+    let name = label.strip_prefix('#').unwrap_or(label);
+    label == "*"
+        || (label.starts_with('#') && name.chars().all(|c| c.is_alphanumeric() || "-_.".contains(c)))
+        || (!label.is_empty() && label.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Recursive descent parser for inline markup.
+/// Handles **strong**, *em*, `code`, :literal:`code`, `text <url>`_ references and `[label]_` footnote references, nesting markup
+/// inside them only when `opts` enables nested inline markup. A backslash before `\`, `*`, `` ` ``, `[` or `<` makes it literal.
+///
+/// Text, code and URLs borrow from `text` unless escapes had to be removed from them.
+pub fn parse_inlines<'a>(text: &'a str, opts: &ParserOptions) -> Vec<Inline<'a>> {
+    parse_inline_markup(text, opts.nested_inline_markup_enabled())
+}
+
+/// [`parse_inlines`] for text that may have been assembled rather than sliced from the input, in
+/// which case the inlines own their text.
+pub(crate) fn parse_inlines_cow<'a>(text: Cow<'a, str>, opts: &ParserOptions) -> Vec<Inline<'a>> {
+    match text {
+        Cow::Borrowed(text) => parse_inlines(text, opts),
+        Cow::Owned(text) => super::into_owned_inlines(parse_inlines(&text, opts)),
+    }
+}
+
+/// Children of a markup span: parsed recursively when nesting, otherwise kept as literal text.
+fn inner_inlines(inner: &str, nested: bool) -> Vec<Inline<'_>> {
+    if nested { parse_inline_markup(inner, true) } else { vec![Inline::Text(unescape(inner))] }
+}
+
+fn parse_inline_markup(text: &str, nested: bool) -> Vec<Inline<'_>> {
     let mut out = Vec::new();
-    let mut buf = String::new();
     let bytes = text.as_bytes();
+    // Start of the plain text that has not been pushed yet.
+    let mut start = 0;
     let mut i = 0;
 
-    let flush_text = |buf: &mut String, out: &mut Vec<Inline>| {
-        if !buf.is_empty() {
-            out.push(Inline::Text(std::mem::take(buf)));
+    let flush_text = |run: Range<usize>, out: &mut Vec<_>| {
+        if !run.is_empty() {
+            out.push(Inline::Text(unescape(&text[run])));
         }
     };
 
     while i < text.len() {
-        if bytes[i] == b'`' && i + 1 < text.len() && bytes[i + 1] == b'`' {
-            if let Some(end) = text[i + 2..].find("``") {
-                let inner = &text[i + 2..i + 2 + end];
-                flush_text(&mut buf, &mut out);
-                out.push(Inline::Code(inner.to_string()));
+        if is_escape(bytes, i) {
+            i += 2;
+            continue;
+        }
+
+        if bytes[i] == b'`'
+            && i + 1 < text.len()
+            && bytes[i + 1] == b'`'
+            && let Some(end) = text[i + 2..].find("``")
+        {
+            let inner = &text[i + 2..i + 2 + end];
+            flush_text(start..i, &mut out);
+            out.push(Inline::Code(inner.into()));
+            i += 2 + end + 2;
+            start = i;
+            continue;
+        }
+
+        // The `:literal:` role, unlike ``code``, can hold double backticks by escaping them.
+        if text[i..].starts_with(":literal:`")
+            && let Some(end) = find_unescaped(&text[i + 10..], "`")
+        {
+            flush_text(start..i, &mut out);
+            out.push(Inline::Code(unescape(&text[i + 10..i + 10 + end])));
+            i += 10 + end + 1;
+            start = i;
+            continue;
+        }
+
+        if bytes[i] == b'*'
+            && i + 1 < text.len()
+            && bytes[i + 1] == b'*'
+            && let Some(end) = find_unescaped(&text[i + 2..], "**")
+        {
+            let inner = &text[i + 2..i + 2 + end];
+            if !inner.is_empty() {
+                flush_text(start..i, &mut out);
+                out.push(Inline::Strong(inner_inlines(inner, nested)));
                 i += 2 + end + 2;
+                start = i;
                 continue;
             }
         }
 
-        if bytes[i] == b'*' && i + 1 < text.len() && bytes[i + 1] == b'*' {
-            if let Some(end) = text[i + 2..].find("**") {
-                let inner = &text[i + 2..i + 2 + end];
-                if !inner.is_empty() {
-                    flush_text(&mut buf, &mut out);
-                    let children = parse_inlines(inner);
-                    out.push(Inline::Strong(children));
-                    i += 2 + end + 2;
-                    continue;
-                }
+        if bytes[i] == b'*'
+            && let Some(end) = find_single_asterisk_close(&text[i + 1..])
+        {
+            let inner = &text[i + 1..i + 1 + end];
+            if !inner.is_empty() {
+                flush_text(start..i, &mut out);
+                out.push(Inline::Em(inner_inlines(inner, nested)));
+                i += 1 + end + 1;
+                start = i;
+                continue;
             }
         }
 
-        if bytes[i] == b'*' {
-            if let Some(end) = find_single_asterisk_close(&text[i + 1..]) {
-                let inner = &text[i + 1..i + 1 + end];
-                if !inner.is_empty() {
-                    flush_text(&mut buf, &mut out);
-                    let children = parse_inlines(inner);
-                    out.push(Inline::Em(children));
-                    i += 1 + end + 1;
-                    continue;
-                }
-            }
-        }
+        if bytes[i] == b'`'
+            && let Some(end) = text[i + 1..].find('`')
+        {
+            let closing_tick = i + 1 + end;
+            let after_tick = closing_tick + 1;
 
-        if bytes[i] == b'`' {
-            if let Some(end) = text[i + 1..].find('`') {
-                let closing_tick = i + 1 + end;
-                let after_tick = closing_tick + 1;
-
-                if after_tick < text.len() && bytes[after_tick] == b'_' {
-                    let inner = &text[i + 1..closing_tick];
-                    if let (Some(l), Some(r)) = (inner.find('<'), inner.rfind('>')) {
-                        if r > l {
-                            let label = inner[..l].trim();
-                            let url = inner[l + 1..r].trim();
-                            if !label.is_empty() && !url.is_empty() {
-                                flush_text(&mut buf, &mut out);
-                                let text_children = parse_inlines(label);
-                                out.push(Inline::Link { text: text_children, url: url.to_string() });
-                                i = after_tick + 1;
-                                continue;
-                            }
-                        }
+            if after_tick < text.len() && bytes[after_tick] == b'_' {
+                let inner = &text[i + 1..closing_tick];
+                if let (Some(l), Some(r)) = (find_unescaped(inner, "<"), inner.rfind('>'))
+                    && r > l
+                {
+                    let label = inner[..l].trim();
+                    let url = inner[l + 1..r].trim();
+                    if !label.is_empty() && !url.is_empty() {
+                        flush_text(start..i, &mut out);
+                        out.push(Inline::Link { text: inner_inlines(label, nested), url: url.into() });
+                        i = after_tick + 1;
+                        start = i;
+                        continue;
                     }
                 }
-
-                flush_text(&mut buf, &mut out);
-                let inner = &text[i + 1..closing_tick];
-                out.push(Inline::Code(inner.to_string()));
-                i = closing_tick + 1;
-                continue;
             }
+
+            flush_text(start..i, &mut out);
+            let inner = &text[i + 1..closing_tick];
+            out.push(Inline::Code(inner.into()));
+            i = closing_tick + 1;
+            start = i;
+            continue;
+        }
+
+        if bytes[i] == b'['
+            && let Some(end) = text[i + 1..].find("]_")
+            && is_footnote_label(&text[i + 1..i + 1 + end])
+            && !text[i + 1 + end + 2..].starts_with(|c: char| c.is_alphanumeric())
+        {
+            flush_text(start..i, &mut out);
+            out.push(Inline::FootnoteRef(text[i + 1..i + 1 + end].to_string()));
+            i += 1 + end + 2;
+            start = i;
+            continue;
         }
 
-        let ch = text[i..].chars().next().unwrap();
-        buf.push(ch);
-        i += ch.len_utf8();
+        i += text[i..].chars().next().unwrap().len_utf8();
     }
 
-    flush_text(&mut buf, &mut out);
+    flush_text(start..i, &mut out);
     out
 }
@@ -1,4 +1,6 @@
-use crate::{Block, Inline, Lines};
+use std::borrow::Cow;
+
+use crate::{Block, Inline, Lines, ParserOptions, is_blank, leading_indent};
 
 /// List flavor used by [`Block::List`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -9,7 +11,9 @@ pub enum ListKind {
 }
 
 /// Try to parse a list (ordered or unordered)
-pub fn try_parse_list(ls: &mut Lines<'_>) -> Option<Block> {
+///
+/// An item continues on following lines indented at least as far as its text.
+pub fn try_parse_list<'a>(ls: &mut Lines<'a>, opts: &ParserOptions) -> Option<Block<'a>> {
     let l = ls.peek()?;
     let kind = list_kind(l.raw)?;
 
@@ -18,8 +22,18 @@ pub fn try_parse_list(ls: &mut Lines<'_>) -> Option<Block> {
         match list_kind(it.raw) {
             Some(next_kind) if next_kind == kind => {
                 let line = ls.next().unwrap();
-                let content = strip_list_marker(line.raw, kind).unwrap().trim_end();
-                items.push(super::parse_inlines(content));
+                let content = strip_list_marker(line.raw, kind).unwrap();
+                let text_indent = line.raw.len() - content.len();
+                let mut text = Cow::Borrowed(content.trim_end());
+                while let Some(next) = ls.peek() {
+                    if is_blank(next.raw) || list_kind(next.raw).is_some() || leading_indent(next.raw) < text_indent {
+                        break;
+                    }
+                    let text = text.to_mut();
+                    text.push('\n');
+                    text.push_str(ls.next().unwrap().raw.trim());
+                }
+                items.push(super::parse_inlines_cow(text, opts));
             }
             _ => break,
         }
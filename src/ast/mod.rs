@@ -1,45 +1,98 @@
 mod definitions;
+mod ids;
 mod inlines;
 mod lists;
+mod option_list;
+mod python;
 mod ser;
 mod table;
 
 pub use definitions::{parse_definition_entries, parse_field_entries};
+pub(crate) use ids::{HeadingIdStream, TargetIds, names_heading};
+pub use ids::{assign_heading_ids, make_id};
 pub use inlines::parse_inlines;
+pub(crate) use inlines::{is_footnote_label, parse_inlines_cow};
 pub use lists::{ListKind, list_kind, try_parse_list};
-pub use table::{try_parse_grid_table, try_parse_simple_table};
+pub use option_list::{OptionItem, try_parse_option_list};
+pub use python::{
+    Inventory, InventoryEntry, ParamKind, PyObject, PyObjectKind, PyParam, PySignature, PySignatureError,
+    build_py_object, parse_py_signature, resolve_py_names,
+};
+pub(crate) use python::{NameResolver, anchor_id};
+pub use table::{TableCell, try_parse_grid_table, try_parse_simple_table};
+
+use std::borrow::Cow;
+
+use crate::HtmlRenderer;
 
 /// Inline-level nodes produced by the parser.
 ///
-/// These are rendered directly to HTML via [`std::fmt::Display`] and are reused by both the HTML and Markdown pipelines.
+/// These render to HTML via [`std::fmt::Display`], using the default [`HtmlRenderer`], and are reused by both the HTML and Markdown pipelines.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub enum Inline {
-    Text(String),
-    Em(Vec<Inline>),
-    Strong(Vec<Inline>),
-    Code(String),
-    Link { text: Vec<Inline>, url: String },
+pub enum Inline<'a> {
+    Text(Cow<'a, str>),
+    Em(Vec<Inline<'a>>),
+    Strong(Vec<Inline<'a>>),
+    Code(Cow<'a, str>),
+    Link {
+        text: Vec<Inline<'a>>,
+        url: Cow<'a, str>,
+    },
+    /// Reference to a footnote (`[1]_`, `[#]_`, `[#name]_` or `[*]_`) by its label
+    FootnoteRef(String),
 }
 
-impl std::fmt::Display for Inline {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Inline<'_> {
+    /// Copy any text borrowed from the input, giving an inline that outlives it.
+    pub fn into_owned(self) -> Inline<'static> {
         match self {
-            Inline::Text(t) => write!(f, "{t}"),
-            Inline::Em(children) => write!(f, "<em>{}</em>", join_inlines(children)),
-            Inline::Strong(children) => write!(f, "<strong>{}</strong>", join_inlines(children)),
-            Inline::Code(t) => write!(f, "<code>{}</code>", html_escape(t)),
-            Inline::Link { text, url } => write!(f, "<a href=\"{url}\">{}</a>", join_inlines(text)),
+            Inline::Text(text) => Inline::Text(Cow::Owned(text.into_owned())),
+            Inline::Em(children) => Inline::Em(into_owned_inlines(children)),
+            Inline::Strong(children) => Inline::Strong(into_owned_inlines(children)),
+            Inline::Code(code) => Inline::Code(Cow::Owned(code.into_owned())),
+            Inline::Link { text, url } => {
+                Inline::Link { text: into_owned_inlines(text), url: Cow::Owned(url.into_owned()) }
+            }
+            Inline::FootnoteRef(label) => Inline::FootnoteRef(label),
         }
     }
 }
 
+pub(crate) fn into_owned_inlines(inlines: Vec<Inline<'_>>) -> Vec<Inline<'static>> {
+    inlines.into_iter().map(Inline::into_owned).collect()
+}
+
+/// Copy any text the blocks borrow from their input; see [`Block::into_owned`].
+pub(crate) fn into_owned(blocks: Vec<Block<'_>>) -> Vec<Block<'static>> {
+    blocks.into_iter().map(Block::into_owned).collect()
+}
+
+impl std::fmt::Display for Inline<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&HtmlRenderer::new().render_inlines(std::slice::from_ref(self)))
+    }
+}
+
+#[cfg(test)]
 pub fn join_inlines(v: &[Inline]) -> String {
-    v.iter().map(|x| x.to_string()).collect::<Vec<_>>().join("")
+    HtmlRenderer::new().render_inlines(v)
 }
 
+/// Escape `s` for use in HTML text and double- or single-quoted attribute values.
 pub fn html_escape(s: &str) -> String {
-    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
 }
 
 /// A single field within a field list.
@@ -48,172 +101,130 @@ pub fn html_escape(s: &str) -> String {
 /// and a body containing nested block content.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Field {
+pub struct Field<'a> {
     pub name: String,
     pub argument: String,
-    pub body: Vec<Block>,
+    pub body: Vec<Block<'a>>,
 }
 
 /// Block-level nodes in the parsed document tree.
 ///
 /// Blocks embed [`Inline`] nodes where appropriate and carry the semantic shape
 /// required for downstream renderers.
+///
+/// Text, code and URLs borrow from the parsed input (`Block<'a>` for input `&'a str`) where it holds
+/// them unchanged; [`Block::into_owned`] copies them to keep the tree beyond the input.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub enum Block {
+pub enum Block<'a> {
     Heading {
         level: u8,
-        inlines: Vec<Inline>,
+        inlines: Vec<Inline<'a>>,
+        /// Anchor id, assigned by the parser from a preceding target or the title; see [`assign_heading_ids`]
+        #[cfg_attr(feature = "serde", serde(default))]
+        id: String,
     },
-    Paragraph(Vec<Inline>),
+    Paragraph(Vec<Inline<'a>>),
     List {
         kind: ListKind,
-        items: Vec<Vec<Inline>>,
+        items: Vec<Vec<Inline<'a>>>,
     },
-    CodeBlock(String),
-    Quote(Vec<Block>),
-    LiteralBlock(String),
+    CodeBlock(Cow<'a, str>),
+    Quote(Vec<Block<'a>>),
+    LiteralBlock(Cow<'a, str>),
     Directive {
         name: String,
         argument: String,
-        content: Vec<Block>,
+        content: Vec<Block<'a>>,
     },
+    /// Table with any number of header rows; each row lists the cells that start in it
     Table {
-        headers: Vec<Vec<Inline>>,
-        rows: Vec<Vec<Vec<Inline>>>,
+        headers: Vec<Vec<TableCell<'a>>>,
+        rows: Vec<Vec<TableCell<'a>>>,
+        /// Width of each grid column in the source, in characters; empty for tables built in code
+        #[cfg_attr(feature = "serde", serde(default))]
+        widths: Vec<usize>,
     },
     /// Comment blocks that are parsed but excluded from rendered output
-    Comment(Vec<Block>),
+    Comment(Vec<Block<'a>>),
     /// Field list containing structured field entries
     FieldList {
-        fields: Vec<Field>,
+        fields: Vec<Field<'a>>,
+    },
+    /// Content from a `raw` directive, emitted verbatim only by renderers for one of `formats`
+    Raw {
+        formats: Vec<String>,
+        content: String,
+    },
+    /// Internal hyperlink target (`.. _name:`) that gives the following element an id
+    Target {
+        name: String,
+    },
+    /// Python object description from a `py:*` domain directive
+    PyObject(Box<PyObject<'a>>),
+    /// Command-line options with their descriptions
+    OptionList {
+        items: Vec<OptionItem<'a>>,
+    },
+    /// Footnote (`.. [1] text`) whose label matches the [`Inline::FootnoteRef`]s pointing at it
+    Footnote {
+        label: String,
+        content: Vec<Block<'a>>,
     },
 }
 
-impl std::fmt::Display for Block {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Field<'_> {
+    /// Copy any text borrowed from the input, giving a field that outlives it.
+    pub fn into_owned(self) -> Field<'static> {
+        Field { name: self.name, argument: self.argument, body: into_owned(self.body) }
+    }
+}
+
+impl Block<'_> {
+    /// Copy any text borrowed from the input, giving a block that outlives it.
+    pub fn into_owned(self) -> Block<'static> {
         match self {
-            Block::Heading { level, inlines } => {
-                let tag = match level {
-                    1 => "h1",
-                    2 => "h2",
-                    _ => "h2",
-                };
-                write!(f, "<{}>{}</{}>", tag, join_inlines(inlines), tag)
-            }
-            Block::Paragraph(inl) => write!(f, "<p>{}</p>", join_inlines(inl)),
+            Block::Heading { level, inlines, id } => Block::Heading { level, inlines: into_owned_inlines(inlines), id },
+            Block::Paragraph(inlines) => Block::Paragraph(into_owned_inlines(inlines)),
             Block::List { kind, items } => {
-                let tag = match kind {
-                    ListKind::Unordered => "ul",
-                    ListKind::Ordered => "ol",
-                };
-                write!(f, "<{tag}>")?;
-                for it in items {
-                    write!(f, "<li>{}</li>", join_inlines(it))?;
-                }
-                write!(f, "</{tag}>")
+                Block::List { kind, items: items.into_iter().map(into_owned_inlines).collect() }
             }
-            Block::CodeBlock(code) => write!(f, "<pre><code>{}</code></pre>", html_escape(code)),
-            Block::Quote(children) => {
-                write!(f, "<blockquote>")?;
-                for b in children {
-                    write!(f, "{b}")?;
-                }
-                write!(f, "</blockquote>")
+            Block::CodeBlock(code) => Block::CodeBlock(Cow::Owned(code.into_owned())),
+            Block::Quote(children) => Block::Quote(into_owned(children)),
+            Block::LiteralBlock(text) => Block::LiteralBlock(Cow::Owned(text.into_owned())),
+            Block::Directive { name, argument, content } => {
+                Block::Directive { name, argument, content: into_owned(content) }
             }
-            Block::LiteralBlock(code) => {
-                write!(f, "<pre><code>{}</code></pre>", html_escape(code))
-            }
-            Block::Directive { name, argument, content } => render_directive(f, name, argument, content),
-            Block::Table { headers, rows } => {
-                write!(f, "<table>")?;
-                if !headers.is_empty() {
-                    write!(f, "<thead><tr>")?;
-                    for header_cell in headers {
-                        write!(f, "<th>{}</th>", join_inlines(header_cell))?;
-                    }
-                    write!(f, "</tr></thead>")?;
-                }
-                if !rows.is_empty() {
-                    write!(f, "<tbody>")?;
-                    for row in rows {
-                        write!(f, "<tr>")?;
-                        for cell in row {
-                            write!(f, "<td>{}</td>", join_inlines(cell))?;
-                        }
-                        write!(f, "</tr>")?;
-                    }
-                    write!(f, "</tbody>")?;
-                }
-                write!(f, "</table>")
+            Block::Table { headers, rows, widths } => {
+                let cells = |rows: Vec<Vec<TableCell<'_>>>| {
+                    rows.into_iter()
+                        .map(|row| row.into_iter().map(TableCell::into_owned).collect())
+                        .collect()
+                };
+                Block::Table { headers: cells(headers), rows: cells(rows), widths }
             }
-            Block::Comment(_) => Ok(()),
+            Block::Comment(children) => Block::Comment(into_owned(children)),
             Block::FieldList { fields } => {
-                write!(f, "<dl>")?;
-                for field in fields {
-                    let term = if field.argument.is_empty() {
-                        field.name.clone()
-                    } else {
-                        format!("{} {}", field.name, field.argument)
-                    };
-                    write!(f, "<dt>{}</dt>", html_escape(&term))?;
-                    write!(f, "<dd>")?;
-                    for block in &field.body {
-                        write!(f, "{block}")?;
-                    }
-                    write!(f, "</dd>")?;
-                }
-                write!(f, "</dl>")
+                Block::FieldList { fields: fields.into_iter().map(Field::into_owned).collect() }
             }
+            Block::Raw { formats, content } => Block::Raw { formats, content },
+            Block::Target { name } => Block::Target { name },
+            Block::PyObject(obj) => Block::PyObject(Box::new(obj.into_owned())),
+            Block::OptionList { items } => {
+                Block::OptionList { items: items.into_iter().map(OptionItem::into_owned).collect() }
+            }
+            Block::Footnote { label, content } => Block::Footnote { label, content: into_owned(content) },
         }
     }
 }
 
-/// Render directive to HTML based on directive type
-fn render_directive(
-    f: &mut std::fmt::Formatter<'_>, name: &str, argument: &str, content: &[Block],
-) -> std::fmt::Result {
-    match name {
-        "note" | "warning" | "tip" | "caution" | "danger" | "attention" | "important" => {
-            let class = name;
-            write!(f, "<div class=\"admonition {class}\">")?;
-            write!(f, "<p class=\"admonition-title\">{}</p>", capitalize(name))?;
-            for block in content {
-                write!(f, "{block}")?;
-            }
-            write!(f, "</div>")
-        }
-        "code-block" | "code" => {
-            let lang = if argument.is_empty() { "" } else { argument };
-            let lang_attr = if lang.is_empty() { String::new() } else { format!(" class=\"language-{lang}\"") };
-            write!(f, "<pre><code{lang_attr}>")?;
-            for block in content {
-                if let Block::LiteralBlock(code) = block {
-                    write!(f, "{}", html_escape(code))?;
-                } else if let Block::Paragraph(inlines) = block {
-                    write!(f, "{}", join_inlines(inlines))?;
-                }
-            }
-            write!(f, "</code></pre>")
-        }
-        "image" => {
-            let alt = if content.is_empty() { String::new() } else { "image".to_string() };
-            write!(f, "<img src=\"{argument}\" alt=\"{alt}\" />")
-        }
-        _ => {
-            write!(f, "<div class=\"directive directive-{name}\">")?;
-            if !argument.is_empty() {
-                write!(f, "<p><code>{}</code></p>", html_escape(argument))?;
-            }
-            for block in content {
-                write!(f, "{block}")?;
-            }
-            write!(f, "</div>")
-        }
+impl std::fmt::Display for Block<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&HtmlRenderer::new().render_block(self))
     }
 }
 
-fn capitalize(s: &str) -> String {
+pub(crate) fn capitalize(s: &str) -> String {
     let mut chars = s.chars();
     match chars.next() {
         Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
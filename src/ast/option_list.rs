@@ -0,0 +1,158 @@
+use super::Block;
+use crate::{Lines, ParseError, ParserOptions, is_blank, leading_indent, parse_nested, strip_indent_preserve};
+
+/// Entry of an option list (`-v, --verbose  Be loud.`): the options it documents and their description.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OptionItem<'a> {
+    /// Options as written, each with its argument (`-o FILE`, `--output=FILE`)
+    pub options: Vec<String>,
+    pub body: Vec<Block<'a>>,
+}
+
+impl OptionItem<'_> {
+    /// Copy any text borrowed from the input, giving an item that outlives it.
+    pub fn into_owned(self) -> OptionItem<'static> {
+        OptionItem { options: self.options, body: super::into_owned(self.body) }
+    }
+}
+
+/// Whether `option` is a short (`-v`, `-o FILE`) or long (`--verbose`, `--output=FILE`) option.
+fn is_option(option: &str) -> bool {
+    let (name, argument) = match option.find([' ', '=']) {
+        Some(i) => (&option[..i], Some(&option[i + 1..])),
+        None => (option, None),
+    };
+    let valid_name = match name.strip_prefix("--") {
+        Some(long) => {
+            long.starts_with(|c: char| c.is_ascii_alphanumeric())
+                && long.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        }
+        None => name
+            .strip_prefix('-')
+            .is_some_and(|short| short.len() == 1 && short.bytes().all(|b| b.is_ascii_alphanumeric())),
+    };
+    valid_name && argument.is_none_or(|argument| !argument.is_empty() && !argument.contains(char::is_whitespace))
+}
+
+/// Split an option list line into its options and the description that follows them after two spaces.
+fn split_options(line: &str) -> Option<(Vec<String>, &str)> {
+    let t = line.trim();
+    if !t.starts_with('-') {
+        return None;
+    }
+    let (head, description) = match t.find("  ") {
+        Some(i) => (&t[..i], t[i..].trim()),
+        None => (t, ""),
+    };
+    let options: Vec<String> = head.split(", ").map(|option| option.trim().to_string()).collect();
+    options
+        .iter()
+        .all(|option| is_option(option))
+        .then_some((options, description))
+}
+
+/// Parse an option list, whose descriptions start on the option line or on indented lines below it.
+pub fn try_parse_option_list<'a>(ls: &mut Lines<'a>, opts: &ParserOptions) -> Result<Option<Block<'a>>, ParseError> {
+    let Some(line) = ls.peek() else {
+        return Ok(None);
+    };
+    let base = leading_indent(line.raw);
+    let indented = |line: Option<&crate::Line<'_>>| {
+        line.is_some_and(|line| !is_blank(line.raw) && leading_indent(line.raw) > base)
+    };
+
+    let mut items = Vec::new();
+    while let Some(line) = ls.peek() {
+        if leading_indent(line.raw) != base {
+            break;
+        }
+        let Some((options, first)) = split_options(line.raw) else {
+            break;
+        };
+        if first.is_empty() && !indented(ls.peek_next()) {
+            break;
+        }
+        let mut body = first.to_string();
+        ls.next();
+
+        let mut body_indent = None;
+        while let Some(next) = ls.peek() {
+            if is_blank(next.raw) {
+                if !indented(ls.peek_next()) {
+                    break;
+                }
+                ls.next();
+                body.push('\n');
+                continue;
+            }
+            if leading_indent(next.raw) <= base {
+                break;
+            }
+            let indent = *body_indent.get_or_insert_with(|| leading_indent(next.raw));
+            let next = ls.next().unwrap();
+            if !body.is_empty() {
+                body.push('\n');
+            }
+            body.push_str(strip_indent_preserve(next.raw, indent));
+        }
+        items.push(OptionItem { options, body: parse_nested(&body, opts)? });
+
+        if ls.peek().is_some_and(|l| is_blank(l.raw))
+            && ls
+                .peek_next()
+                .is_some_and(|l| leading_indent(l.raw) == base && split_options(l.raw).is_some())
+        {
+            ls.next();
+        }
+    }
+
+    Ok((!items.is_empty()).then_some(Block::OptionList { items }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Inline, parse};
+
+    fn para(text: &str) -> Block<'_> {
+        Block::Paragraph(vec![Inline::Text(text.into())])
+    }
+
+    #[test]
+    fn parses_options_and_descriptions() {
+        let doc = "\
+-v, --verbose    Be loud.
+--output=FILE    Write to FILE
+                 instead of stdout.
+-o FILE
+    Short form.
+
+-h  Help.
+";
+        assert_eq!(
+            parse(doc).unwrap(),
+            vec![Block::OptionList {
+                items: vec![
+                    OptionItem { options: vec!["-v".into(), "--verbose".into()], body: vec![para("Be loud.")] },
+                    OptionItem {
+                        options: vec!["--output=FILE".into()],
+                        body: vec![para("Write to FILE\ninstead of stdout.")]
+                    },
+                    OptionItem { options: vec!["-o FILE".into()], body: vec![para("Short form.")] },
+                    OptionItem { options: vec!["-h".into()], body: vec![para("Help.")] },
+                ]
+            }]
+        );
+    }
+
+    #[test]
+    fn leaves_other_dashes_alone() {
+        for doc in ["-1 is negative", "--verbose\n", "- bullet", "-x y z  text"] {
+            assert!(
+                !matches!(parse(doc).unwrap().first(), Some(Block::OptionList { .. })),
+                "{doc:?}"
+            );
+        }
+    }
+}
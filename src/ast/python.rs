@@ -0,0 +1,558 @@
+//! Python domain directives (`py:function`, `py:class`, ...) and their signature parser.
+
+use super::Block;
+use crate::visit::{Visitor, VisitorMut, walk_py_object, walk_py_object_mut};
+
+/// Which Python domain directive produced a [`PyObject`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PyObjectKind {
+    Module,
+    CurrentModule,
+    Function,
+    Class,
+    Exception,
+    Method,
+    ClassMethod,
+    StaticMethod,
+    Attribute,
+    Property,
+    Data,
+}
+
+impl PyObjectKind {
+    /// Map a directive name such as `py:method` to its kind.
+    pub fn from_directive(name: &str) -> Option<Self> {
+        let kind = match name.strip_prefix("py:")? {
+            "module" => Self::Module,
+            "currentmodule" => Self::CurrentModule,
+            "function" => Self::Function,
+            "class" => Self::Class,
+            "exception" => Self::Exception,
+            "method" => Self::Method,
+            "classmethod" => Self::ClassMethod,
+            "staticmethod" => Self::StaticMethod,
+            "attribute" => Self::Attribute,
+            "property" => Self::Property,
+            "data" => Self::Data,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    /// The directive name without the `py:` prefix.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Module => "module",
+            Self::CurrentModule => "currentmodule",
+            Self::Function => "function",
+            Self::Class => "class",
+            Self::Exception => "exception",
+            Self::Method => "method",
+            Self::ClassMethod => "classmethod",
+            Self::StaticMethod => "staticmethod",
+            Self::Attribute => "attribute",
+            Self::Property => "property",
+            Self::Data => "data",
+        }
+    }
+
+    /// Whether nested objects are qualified with this object's name (e.g. methods of a class).
+    fn is_container(self) -> bool {
+        matches!(self, Self::Class | Self::Exception)
+    }
+
+    pub(crate) fn takes_arguments(self) -> bool {
+        matches!(
+            self,
+            Self::Function | Self::Class | Self::Exception | Self::Method | Self::ClassMethod | Self::StaticMethod
+        )
+    }
+}
+
+/// How a parameter binds its argument, mirroring `inspect.Parameter.kind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParamKind {
+    PositionalOnly,
+    PositionalOrKeyword,
+    VarPositional,
+    KeywordOnly,
+    VarKeyword,
+}
+
+/// A single parameter in a [`PySignature`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PyParam {
+    pub name: String,
+    pub annotation: Option<String>,
+    pub default: Option<String>,
+    pub kind: ParamKind,
+}
+
+/// A parsed Python signature such as `@cache pkg.mod.name(a: int, b=1) -> str`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PySignature {
+    pub decorators: Vec<String>,
+    /// Dotted prefix written before the name, including the trailing dot (e.g. `pkg.mod.`).
+    pub prefix: String,
+    pub name: String,
+    /// `None` when the signature has no parameter list at all (e.g. `py:class:: Foo`).
+    pub params: Option<Vec<PyParam>>,
+    pub return_annotation: Option<String>,
+}
+
+/// A Python object description produced by a `py:*` directive.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PyObject<'a> {
+    pub kind: PyObjectKind,
+    pub signature: PySignature,
+    /// Fully qualified name including the current module and enclosing classes; also used as the anchor id.
+    pub fullname: String,
+    pub options: Vec<(String, String)>,
+    pub content: Vec<Block<'a>>,
+}
+
+impl PyObject<'_> {
+    /// Copy any text borrowed from the input, giving an object that outlives it.
+    pub fn into_owned(self) -> PyObject<'static> {
+        PyObject {
+            kind: self.kind,
+            signature: self.signature,
+            fullname: self.fullname,
+            options: self.options,
+            content: super::into_owned(self.content),
+        }
+    }
+
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl std::fmt::Display for PySignature {
+    /// Write the signature back in the form [`parse_py_signature`] accepts.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for decorator in &self.decorators {
+            write!(f, "@{decorator} ")?;
+        }
+        write!(f, "{}{}", self.prefix, self.name)?;
+        if let Some(params) = &self.params {
+            let mut parts = Vec::new();
+            let mut previous: Option<ParamKind> = None;
+            for (i, param) in params.iter().enumerate() {
+                if param.kind == ParamKind::KeywordOnly
+                    && !matches!(previous, Some(ParamKind::KeywordOnly | ParamKind::VarPositional))
+                {
+                    parts.push("*".to_string());
+                }
+                let stars = match param.kind {
+                    ParamKind::VarPositional => "*",
+                    ParamKind::VarKeyword => "**",
+                    _ => "",
+                };
+                let mut part = format!("{stars}{}", param.name);
+                if let Some(annotation) = &param.annotation {
+                    part.push_str(&format!(": {annotation}"));
+                }
+                if let Some(default) = &param.default {
+                    let op = if param.annotation.is_some() { " = " } else { "=" };
+                    part.push_str(&format!("{op}{default}"));
+                }
+                parts.push(part);
+                if param.kind == ParamKind::PositionalOnly
+                    && params
+                        .get(i + 1)
+                        .is_none_or(|next| next.kind != ParamKind::PositionalOnly)
+                {
+                    parts.push("/".to_string());
+                }
+                previous = Some(param.kind);
+            }
+            write!(f, "({})", parts.join(", "))?;
+        }
+        if let Some(ret) = &self.return_annotation {
+            write!(f, " -> {ret}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Error returned when a Python signature cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PySignatureError(String);
+
+impl std::fmt::Display for PySignatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid Python signature: {}", self.0)
+    }
+}
+
+impl std::error::Error for PySignatureError {}
+
+/// Index just past the bracket that closes the one opening at `open`, skipping nested brackets and strings.
+fn matching_close(s: &str, open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (i, c) in s[open..].char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '\'' | '"' => quote = Some(c),
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(open + i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Split `s` on `sep` where it appears outside brackets and string literals.
+fn split_top_level(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while let Some(c) = s[i..].chars().next() {
+        match c {
+            '(' | '[' | '{' | '\'' | '"' => {
+                let end = if matches!(c, '\'' | '"') {
+                    s[i + 1..].find(c).map_or(s.len(), |j| i + 1 + j + 1)
+                } else {
+                    matching_close(s, i).unwrap_or(s.len())
+                };
+                i = end;
+                continue;
+            }
+            c if c == sep => {
+                parts.push(&s[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+        i += c.len_utf8();
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+/// Find the first `sep` outside brackets and strings.
+fn find_top_level(s: &str, sep: char) -> Option<usize> {
+    let first = split_top_level(s, sep).into_iter().next()?;
+    (first.len() < s.len()).then_some(first.len())
+}
+
+fn parse_params(list: &str) -> Result<Vec<PyParam>, PySignatureError> {
+    let mut params: Vec<PyParam> = Vec::new();
+    let mut keyword_only = false;
+
+    for raw in split_top_level(list, ',') {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            continue;
+        }
+        match raw {
+            "/" => {
+                for param in &mut params {
+                    param.kind = ParamKind::PositionalOnly;
+                }
+                continue;
+            }
+            "*" => {
+                keyword_only = true;
+                continue;
+            }
+            _ => {}
+        }
+
+        let (head, default) = match find_top_level(raw, '=') {
+            Some(idx) => (raw[..idx].trim(), Some(raw[idx + 1..].trim().to_string())),
+            None => (raw, None),
+        };
+        let (name, annotation) = match find_top_level(head, ':') {
+            Some(idx) => (head[..idx].trim(), Some(head[idx + 1..].trim().to_string())),
+            None => (head, None),
+        };
+
+        let (name, kind) = if let Some(rest) = name.strip_prefix("**") {
+            (rest, ParamKind::VarKeyword)
+        } else if let Some(rest) = name.strip_prefix('*') {
+            keyword_only = true;
+            (rest, ParamKind::VarPositional)
+        } else if keyword_only {
+            (name, ParamKind::KeywordOnly)
+        } else {
+            (name, ParamKind::PositionalOrKeyword)
+        };
+
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(PySignatureError(format!("invalid parameter `{raw}`")));
+        }
+
+        params.push(PyParam {
+            name: name.to_string(),
+            annotation: annotation.filter(|a| !a.is_empty()),
+            default: default.filter(|d| !d.is_empty()),
+            kind,
+        });
+    }
+
+    Ok(params)
+}
+
+/// Parse a Python signature as written in a `py:*` directive argument.
+///
+/// Accepts leading `@decorator` tokens, a dotted name, an optional parameter list and an optional
+/// `-> annotation`. Parameter kinds honour `/`, `*`, `*args` and `**kwargs`.
+pub fn parse_py_signature(input: &str) -> Result<PySignature, PySignatureError> {
+    let mut rest = input.trim();
+    let mut decorators = Vec::new();
+
+    while rest.starts_with('@') {
+        let name_end = rest.find(|c: char| c.is_whitespace() || c == '(').unwrap_or(rest.len());
+        let end = if rest[name_end..].starts_with('(') {
+            matching_close(rest, name_end).ok_or_else(|| PySignatureError("unclosed decorator arguments".into()))?
+        } else {
+            name_end
+        };
+        decorators.push(rest[1..end].to_string());
+        rest = rest[end..].trim_start();
+    }
+
+    let (path, params, tail) = match rest.find('(') {
+        Some(open) => {
+            let close = matching_close(rest, open).ok_or_else(|| PySignatureError("unclosed `(`".into()))?;
+            let params = parse_params(&rest[open + 1..close - 1])?;
+            (rest[..open].trim(), Some(params), rest[close..].trim())
+        }
+        None => match rest.find("->") {
+            Some(idx) => (rest[..idx].trim(), None, rest[idx..].trim()),
+            None => (rest, None, ""),
+        },
+    };
+
+    let return_annotation = if tail.is_empty() {
+        None
+    } else {
+        let ann = tail
+            .strip_prefix("->")
+            .ok_or_else(|| PySignatureError(format!("unexpected `{tail}` after parameters")))?
+            .trim();
+        if ann.is_empty() {
+            return Err(PySignatureError("missing return annotation after `->`".into()));
+        }
+        Some(ann.to_string())
+    };
+
+    if path.is_empty() || !path.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '.') {
+        return Err(PySignatureError(format!("invalid object name `{path}`")));
+    }
+    let (prefix, name) = match path.rfind('.') {
+        Some(idx) => (&path[..idx + 1], &path[idx + 1..]),
+        None => ("", path),
+    };
+
+    Ok(PySignature { decorators, prefix: prefix.to_string(), name: name.to_string(), params, return_annotation })
+}
+
+/// Build the [`PyObject`] for a `py:*` directive; the fullname is provisional until [`resolve_py_names`].
+pub fn build_py_object<'a>(
+    kind: PyObjectKind, argument: &str, options: Vec<(String, String)>, content: Vec<Block<'a>>,
+) -> Result<PyObject<'a>, PySignatureError> {
+    let signature = match kind {
+        PyObjectKind::Module | PyObjectKind::CurrentModule => PySignature {
+            decorators: Vec::new(),
+            prefix: String::new(),
+            name: argument.trim().to_string(),
+            params: None,
+            return_annotation: None,
+        },
+        _ => parse_py_signature(argument)?,
+    };
+    let fullname = format!("{}{}", signature.prefix, signature.name);
+    Ok(PyObject { kind, signature, fullname, options, content })
+}
+
+/// Qualify every [`PyObject`] with its module (from `py:module`/`py:currentmodule`) and enclosing classes.
+pub fn resolve_py_names(blocks: &mut [Block]) {
+    NameResolver::default().visit_blocks_mut(blocks);
+}
+
+/// The module and enclosing class path in effect at the current point of the walk; the module
+/// carries over when the same resolver visits the next blocks of a document.
+#[derive(Debug, Default)]
+pub(crate) struct NameResolver {
+    module: Option<String>,
+    class_path: String,
+}
+
+impl VisitorMut for NameResolver {
+    fn visit_py_object_mut(&mut self, obj: &mut PyObject) {
+        match obj.kind {
+            PyObjectKind::Module | PyObjectKind::CurrentModule => {
+                let name = obj.signature.name.clone();
+                self.module = (!name.is_empty() && name != "None").then_some(name);
+                obj.fullname = obj.signature.name.clone();
+            }
+            kind => {
+                let mut fullname = String::new();
+                if let Some(module) = self.module.as_deref()
+                    && obj.signature.prefix.is_empty()
+                    && self.class_path.is_empty()
+                {
+                    fullname.push_str(module);
+                    fullname.push('.');
+                }
+                fullname.push_str(&self.class_path);
+                fullname.push_str(&obj.signature.prefix);
+                fullname.push_str(&obj.signature.name);
+                obj.fullname = fullname;
+
+                let nested = if kind.is_container() { format!("{}.", obj.fullname) } else { self.class_path.clone() };
+                let module = self.module.clone();
+                let class_path = std::mem::replace(&mut self.class_path, nested);
+                walk_py_object_mut(self, obj);
+                self.module = module;
+                self.class_path = class_path;
+            }
+        }
+    }
+}
+
+/// A cross-reference target registered by a Python object description.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InventoryEntry {
+    pub name: String,
+    pub kind: PyObjectKind,
+    pub anchor: String,
+}
+
+/// Every Python object described in a document, keyed by fully qualified name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inventory {
+    entries: Vec<InventoryEntry>,
+}
+
+impl Inventory {
+    /// Collect the objects described anywhere in `blocks`, in document order.
+    pub fn from_blocks(blocks: &[Block]) -> Self {
+        let mut inventory = Self::default();
+        Collector(&mut inventory.entries).visit_blocks(blocks);
+        inventory
+    }
+
+    pub fn get(&self, name: &str) -> Option<&InventoryEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    /// Resolve a cross-reference target: an exact name first, then a unique `.target` suffix match.
+    pub fn find(&self, target: &str) -> Option<&InventoryEntry> {
+        if let Some(entry) = self.get(target) {
+            return Some(entry);
+        }
+        let suffix = format!(".{target}");
+        let mut matches = self.entries.iter().filter(|entry| entry.name.ends_with(&suffix));
+        let first = matches.next()?;
+        matches.next().is_none().then_some(first)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &InventoryEntry> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+struct Collector<'a>(&'a mut Vec<InventoryEntry>);
+
+impl Visitor for Collector<'_> {
+    fn visit_py_object(&mut self, obj: &PyObject) {
+        if obj.kind != PyObjectKind::CurrentModule && obj.option("noindex").is_none() {
+            self.0
+                .push(InventoryEntry { name: obj.fullname.clone(), kind: obj.kind, anchor: anchor_id(obj) });
+        }
+        walk_py_object(self, obj);
+    }
+}
+
+pub(crate) fn anchor_id(obj: &PyObject) -> String {
+    match obj.kind {
+        PyObjectKind::Module => format!("module-{}", obj.fullname),
+        _ => obj.fullname.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_annotations_defaults_and_return() {
+        let sig = parse_py_signature("name(a: int, b=1, c: dict[str, int] = {'x': 1}) -> str").unwrap();
+        assert_eq!(sig.name, "name");
+        assert_eq!(sig.return_annotation.as_deref(), Some("str"));
+        let params = sig.params.unwrap();
+        assert_eq!(params.len(), 3);
+        assert_eq!(params[0].annotation.as_deref(), Some("int"));
+        assert_eq!(params[1].default.as_deref(), Some("1"));
+        assert_eq!(params[2].annotation.as_deref(), Some("dict[str, int]"));
+        assert_eq!(params[2].default.as_deref(), Some("{'x': 1}"));
+    }
+
+    #[test]
+    fn parses_parameter_kinds_and_decorators() {
+        let sig =
+            parse_py_signature("@lru_cache(maxsize=None) @staticmethod pkg.mod.f(a, /, b, *args, c, **kw)").unwrap();
+        assert_eq!(sig.decorators, vec!["lru_cache(maxsize=None)", "staticmethod"]);
+        assert_eq!(sig.prefix, "pkg.mod.");
+        let kinds: Vec<ParamKind> = sig.params.unwrap().iter().map(|p| p.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ParamKind::PositionalOnly,
+                ParamKind::PositionalOrKeyword,
+                ParamKind::VarPositional,
+                ParamKind::KeywordOnly,
+                ParamKind::VarKeyword,
+            ]
+        );
+
+        let bare_star = parse_py_signature("g(a, *, b=2)").unwrap().params.unwrap();
+        assert_eq!(bare_star[1].kind, ParamKind::KeywordOnly);
+    }
+
+    #[test]
+    fn rejects_malformed_signatures() {
+        for input in ["f(a, b", "f(a) str", "f(a) ->", "(a)", "f(1a b)"] {
+            assert!(parse_py_signature(input).is_err(), "{input:?} should not parse");
+        }
+        assert_eq!(parse_py_signature("Point").unwrap().params, None);
+    }
+}
@@ -1,6 +1,6 @@
 //! Serialization support for the AST types.
 //!
-//! When the `serde` feature is enabled, all AST types ([`Inline`], [`Block`], [`Field`], and [`ListKind`])
+//! When the `serde` feature is enabled, all AST types ([`Inline`], [`Block`], [`Field`], [`ListKind`], [`TableCell`], ...)
 //! implement [`serde::Serialize`] and [`serde::Deserialize`] via derive macros.
 //!
 //! This allows you to serialize parsed documents to JSON, YAML, or any other format supported by serde.
@@ -22,16 +22,13 @@
 //! assert_eq!(ast, parsed);
 //! ```
 
-#[cfg(feature = "serde")]
-pub use serde::{Deserialize, Serialize};
-
 #[cfg(all(test, feature = "serde"))]
 mod tests {
-    use crate::{Block, Field, Inline, ListKind, parse};
+    use crate::{Block, Field, Inline, ListKind, TableCell, parse};
 
     #[test]
     fn roundtrip_inline_text_json() {
-        let inline = Inline::Text("Hello, world!".to_string());
+        let inline = Inline::Text("Hello, world!".into());
         let json = serde_json::to_string(&inline).unwrap();
         let deserialized: Inline = serde_json::from_str(&json).unwrap();
         assert_eq!(inline, deserialized);
@@ -40,9 +37,9 @@ mod tests {
     #[test]
     fn roundtrip_inline_nested_json() {
         let inline = Inline::Strong(vec![
-            Inline::Text("bold ".to_string()),
-            Inline::Em(vec![Inline::Text("italic".to_string())]),
-            Inline::Text(" text".to_string()),
+            Inline::Text("bold ".into()),
+            Inline::Em(vec![Inline::Text("italic".into())]),
+            Inline::Text(" text".into()),
         ]);
         let json = serde_json::to_string(&inline).unwrap();
         let deserialized: Inline = serde_json::from_str(&json).unwrap();
@@ -51,8 +48,7 @@ mod tests {
 
     #[test]
     fn roundtrip_inline_link_json() {
-        let inline =
-            Inline::Link { text: vec![Inline::Text("example".to_string())], url: "https://example.com".to_string() };
+        let inline = Inline::Link { text: vec![Inline::Text("example".into())], url: "https://example.com".into() };
         let json = serde_json::to_string(&inline).unwrap();
         let deserialized: Inline = serde_json::from_str(&json).unwrap();
         assert_eq!(inline, deserialized);
@@ -60,7 +56,7 @@ mod tests {
 
     #[test]
     fn roundtrip_block_heading_json() {
-        let block = Block::Heading { level: 1, inlines: vec![Inline::Text("Title".to_string())] };
+        let block = Block::Heading { level: 1, inlines: vec![Inline::Text("Title".into())], id: "title".to_string() };
         let json = serde_json::to_string(&block).unwrap();
         let deserialized: Block = serde_json::from_str(&json).unwrap();
         assert_eq!(block, deserialized);
@@ -69,8 +65,8 @@ mod tests {
     #[test]
     fn roundtrip_block_paragraph_json() {
         let block = Block::Paragraph(vec![
-            Inline::Text("Some ".to_string()),
-            Inline::Em(vec![Inline::Text("text".to_string())]),
+            Inline::Text("Some ".into()),
+            Inline::Em(vec![Inline::Text("text".into())]),
         ]);
         let json = serde_json::to_string(&block).unwrap();
         let deserialized: Block = serde_json::from_str(&json).unwrap();
@@ -82,9 +78,9 @@ mod tests {
         let block = Block::List {
             kind: ListKind::Unordered,
             items: vec![
-                vec![Inline::Text("Item 1".to_string())],
-                vec![Inline::Text("Item 2".to_string())],
-                vec![Inline::Text("Item 3".to_string())],
+                vec![Inline::Text("Item 1".into())],
+                vec![Inline::Text("Item 2".into())],
+                vec![Inline::Text("Item 3".into())],
             ],
         };
         let json = serde_json::to_string(&block).unwrap();
@@ -95,20 +91,22 @@ mod tests {
     #[test]
     fn roundtrip_block_table_json() {
         let block = Block::Table {
-            headers: vec![
-                vec![Inline::Text("Col1".to_string())],
-                vec![Inline::Text("Col2".to_string())],
-            ],
+            headers: vec![vec![
+                TableCell::new(vec![Block::Paragraph(vec![Inline::Text("Col1".into())])]),
+                TableCell::new(vec![Block::Paragraph(vec![Inline::Text("Col2".into())])]),
+            ]],
             rows: vec![
+                vec![TableCell {
+                    content: vec![Block::Paragraph(vec![Inline::Text("wide".into())])],
+                    colspan: 2,
+                    rowspan: 1,
+                }],
                 vec![
-                    vec![Inline::Text("val1".to_string())],
-                    vec![Inline::Text("val2".to_string())],
-                ],
-                vec![
-                    vec![Inline::Text("val3".to_string())],
-                    vec![Inline::Text("val4".to_string())],
+                    TableCell::new(vec![Block::Paragraph(vec![Inline::Text("val3".into())])]),
+                    TableCell::new(vec![Block::Paragraph(vec![Inline::Text("val4".into())])]),
                 ],
             ],
+            widths: vec![4, 4],
         };
         let json = serde_json::to_string(&block).unwrap();
         let deserialized: Block = serde_json::from_str(&json).unwrap();
@@ -120,7 +118,7 @@ mod tests {
         let block = Block::Directive {
             name: "note".to_string(),
             argument: "".to_string(),
-            content: vec![Block::Paragraph(vec![Inline::Text("Note content".to_string())])],
+            content: vec![Block::Paragraph(vec![Inline::Text("Note content".into())])],
         };
         let json = serde_json::to_string(&block).unwrap();
         let deserialized: Block = serde_json::from_str(&json).unwrap();
@@ -132,7 +130,7 @@ mod tests {
         let field = Field {
             name: "param".to_string(),
             argument: "x".to_string(),
-            body: vec![Block::Paragraph(vec![Inline::Text("Description".to_string())])],
+            body: vec![Block::Paragraph(vec![Inline::Text("Description".into())])],
         };
         let json = serde_json::to_string(&field).unwrap();
         let deserialized: Field = serde_json::from_str(&json).unwrap();
@@ -146,12 +144,12 @@ mod tests {
                 Field {
                     name: "param".to_string(),
                     argument: "x".to_string(),
-                    body: vec![Block::Paragraph(vec![Inline::Text("X value".to_string())])],
+                    body: vec![Block::Paragraph(vec![Inline::Text("X value".into())])],
                 },
                 Field {
                     name: "returns".to_string(),
                     argument: "".to_string(),
-                    body: vec![Block::Paragraph(vec![Inline::Text("Result".to_string())])],
+                    body: vec![Block::Paragraph(vec![Inline::Text("Result".into())])],
                 },
             ],
         };
@@ -223,7 +221,7 @@ val1  val2
 
     #[test]
     fn roundtrip_inline_text_yaml() {
-        let inline = Inline::Text("Hello, world!".to_string());
+        let inline = Inline::Text("Hello, world!".into());
         let yaml = serde_yml::to_string(&inline).unwrap();
         let deserialized: Inline = serde_yml::from_str(&yaml).unwrap();
         assert_eq!(inline, deserialized);
@@ -231,7 +229,7 @@ val1  val2
 
     #[test]
     fn roundtrip_block_heading_yaml() {
-        let block = Block::Heading { level: 1, inlines: vec![Inline::Text("Title".to_string())] };
+        let block = Block::Heading { level: 1, inlines: vec![Inline::Text("Title".into())], id: "title".to_string() };
         let yaml = serde_yml::to_string(&block).unwrap();
         let deserialized: Block = serde_yml::from_str(&yaml).unwrap();
         assert_eq!(block, deserialized);
@@ -258,22 +256,19 @@ A paragraph with *emphasis*.
     fn roundtrip_deeply_nested_structure() {
         let block = Block::Quote(vec![
             Block::Paragraph(vec![Inline::Strong(vec![
-                Inline::Text("Bold with ".to_string()),
-                Inline::Em(vec![Inline::Text("nested italic".to_string())]),
-                Inline::Text(" and ".to_string()),
-                Inline::Link {
-                    text: vec![Inline::Code("code link".to_string())],
-                    url: "https://example.com".to_string(),
-                },
+                Inline::Text("Bold with ".into()),
+                Inline::Em(vec![Inline::Text("nested italic".into())]),
+                Inline::Text(" and ".into()),
+                Inline::Link { text: vec![Inline::Code("code link".into())], url: "https://example.com".into() },
             ])]),
             Block::List {
                 kind: ListKind::Ordered,
                 items: vec![
                     vec![
-                        Inline::Text("Item with ".to_string()),
-                        Inline::Em(vec![Inline::Text("emphasis".to_string())]),
+                        Inline::Text("Item with ".into()),
+                        Inline::Em(vec![Inline::Text("emphasis".into())]),
                     ],
-                    vec![Inline::Code("code item".to_string())],
+                    vec![Inline::Code("code item".into())],
                 ],
             },
         ]);
@@ -294,7 +289,7 @@ A paragraph with *emphasis*.
         let deserialized: Block = serde_json::from_str(&json).unwrap();
         assert_eq!(empty_list, deserialized);
 
-        let empty_table = Block::Table { headers: vec![], rows: vec![] };
+        let empty_table = Block::Table { headers: vec![], rows: vec![], widths: vec![] };
         let json = serde_json::to_string(&empty_table).unwrap();
         let deserialized: Block = serde_json::from_str(&json).unwrap();
         assert_eq!(empty_table, deserialized);
@@ -303,8 +298,8 @@ A paragraph with *emphasis*.
     #[test]
     fn json_format_is_readable() {
         let block = Block::Paragraph(vec![
-            Inline::Text("Hello ".to_string()),
-            Inline::Em(vec![Inline::Text("world".to_string())]),
+            Inline::Text("Hello ".into()),
+            Inline::Em(vec![Inline::Text("world".into())]),
         ]);
         let json = serde_json::to_string_pretty(&block).unwrap();
 
@@ -318,8 +313,8 @@ A paragraph with *emphasis*.
     #[test]
     fn yaml_format_is_readable() {
         let block = Block::Paragraph(vec![
-            Inline::Text("Hello ".to_string()),
-            Inline::Em(vec![Inline::Text("world".to_string())]),
+            Inline::Text("Hello ".into()),
+            Inline::Em(vec![Inline::Text("world".into())]),
         ]);
         let yaml = serde_yml::to_string(&block).unwrap();
 
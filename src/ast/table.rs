@@ -1,7 +1,54 @@
-use super::parse_inlines;
-use crate::{Block, Inline, Lines};
+use std::collections::{BTreeMap, BTreeSet};
 
-/// Check if a line is a simple table separator (all = and spaces)
+use unicode_width::UnicodeWidthChar;
+
+use crate::{Block, Inline, Line, Lines, ParseError, ParserOptions, is_blank, leading_indent, parse_nested, trim_end};
+
+/// A table cell together with the number of grid columns and rows it covers.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableCell<'a> {
+    pub content: Vec<Block<'a>>,
+    pub colspan: usize,
+    pub rowspan: usize,
+}
+
+impl<'a> TableCell<'a> {
+    /// A cell covering exactly one row and one column.
+    pub fn new(content: Vec<Block<'a>>) -> Self {
+        Self { content, colspan: 1, rowspan: 1 }
+    }
+
+    /// Copy any text borrowed from the input, giving a cell that outlives it.
+    pub fn into_owned(self) -> TableCell<'static> {
+        TableCell { content: super::into_owned(self.content), colspan: self.colspan, rowspan: self.rowspan }
+    }
+
+    /// The cell's inlines when it holds nothing but a single paragraph.
+    pub fn as_inlines(&self) -> Option<&[Inline<'a>]> {
+        match self.content.as_slice() {
+            [Block::Paragraph(inlines)] => Some(inlines),
+            _ => None,
+        }
+    }
+}
+
+/// Check if a line is a grid table border (+--+--+)
+fn is_grid_border(s: &str) -> bool {
+    let trimmed = s.trim();
+    if trimmed.len() < 2 || !trimmed.starts_with('+') || !trimmed.ends_with('+') {
+        return false;
+    }
+    trimmed.chars().all(|c| c == '+' || c == '-' || c == '=')
+}
+
+/// Check if a line can belong to a grid table indented by `indent`
+fn is_grid_line(s: &str, indent: usize) -> bool {
+    let t = s.trim_start();
+    leading_indent(s) == indent && (t.starts_with('+') || t.starts_with('|'))
+}
+
+/// Check if a line is a simple table border (all = and spaces)
 fn is_table_separator(s: &str) -> bool {
     let trimmed = s.trim();
     if trimmed.is_empty() {
@@ -10,231 +57,584 @@ fn is_table_separator(s: &str) -> bool {
     trimmed.chars().all(|c| c == '=' || c == ' ')
 }
 
-/// Check if a line is a grid table border (+--+--+)
-fn is_grid_border(s: &str) -> bool {
-    let trimmed = s.trim();
-    if trimmed.is_empty() || !trimmed.starts_with('+') {
-        return false;
+/// Placeholder for the second display column taken up by an East Asian wide character.
+const WIDE_PAD: &str = "\0";
+
+/// Split `s` into display columns, measured the way docutils measures table text.
+///
+/// East Asian wide and fullwidth characters take two columns, the second holding [`WIDE_PAD`].
+/// Combining marks and other zero-width characters stay attached to the character before them.
+fn display_columns(s: &str) -> Vec<&str> {
+    let mut columns: Vec<&str> = Vec::with_capacity(s.len());
+    let mut base: Option<(usize, usize)> = None;
+    for (i, c) in s.char_indices() {
+        let end = i + c.len_utf8();
+        match (c.width().unwrap_or(1), base) {
+            (0, Some((idx, start))) => columns[idx] = &s[start..end],
+            (width, _) => {
+                base = Some((columns.len(), i));
+                columns.push(&s[i..end]);
+                if width == 2 {
+                    columns.push(WIDE_PAD);
+                }
+            }
+        }
     }
-    trimmed.chars().all(|c| c == '+' || c == '-' || c == '=' || c == ' ')
+    columns
 }
 
-/// Parse column positions from a grid table border line
-fn parse_grid_columns(border: &str) -> Vec<usize> {
-    border
-        .char_indices()
-        .filter_map(|(i, c)| if c == '+' { Some(i) } else { None })
-        .collect()
+/// Reassemble the text of a run of display columns.
+fn columns_text(columns: &[&str]) -> String {
+    columns.iter().filter(|&&col| col != WIDE_PAD).copied().collect()
+}
+
+fn is_blank_column(col: &str) -> bool {
+    col.chars().all(char::is_whitespace)
 }
 
-/// Check if a grid border line is a header separator (contains =)
-fn is_grid_header_separator(s: &str) -> bool {
-    s.contains('=')
+/// Check if a line opens a simple table: at least two runs of `=` separated by spaces.
+///
+/// A single run is left alone so it can still act as a section title adornment.
+fn is_simple_table_top(s: &str) -> bool {
+    is_table_separator(s) && s.split_whitespace().count() >= 2
 }
 
-/// Parse column boundaries from a separator line
-fn parse_column_boundaries(sep: &str) -> Vec<(usize, usize)> {
+/// Check if a (converted) line is a column-span underline or a table border.
+fn is_span_line(line: &[&str]) -> bool {
+    line.first() == Some(&"-") && line.iter().all(|&c| c == "-" || c == " ")
+}
+
+/// Column ranges `(start, end)` of the runs of `-` in `line`.
+fn dash_runs(line: &[&str]) -> Vec<(usize, usize)> {
     let mut columns = Vec::new();
-    let mut in_col = false;
-    let mut start = 0;
-
-    for (i, ch) in sep.char_indices() {
-        if ch == '=' {
-            if !in_col {
-                in_col = true;
-                start = i;
+    let mut start = None;
+    for (i, &c) in line.iter().enumerate() {
+        match (c == "-", start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                columns.push((s, i));
+                start = None;
             }
-        } else if in_col {
-            columns.push((start, i));
-            in_col = false;
+            _ => {}
         }
     }
-    if in_col {
-        columns.push((start, sep.len()));
+    if let Some(s) = start {
+        columns.push((s, line.len()));
     }
     columns
 }
 
-/// Extract cell content from a line based on column boundaries
-fn extract_cells(line: &str, columns: &[(usize, usize)]) -> Vec<String> {
-    columns
-        .iter()
-        .map(|(start, end)| {
-            let cell_text = if *start < line.len() {
-                let end_bounded = (*end).min(line.len());
-                &line[*start..end_bounded]
-            } else {
-                ""
-            };
-            cell_text.trim().to_string()
-        })
-        .collect()
+fn has_text(line: &[&str], start: usize, end: usize) -> bool {
+    let end = end.min(line.len());
+    start < end && line[start..end].iter().any(|col| !is_blank_column(col))
+}
+
+/// A simple table under construction, following docutils' `SimpleTableParser`.
+///
+/// `block` holds the table lines with the top and bottom borders rewritten to `-`, so that the bottom
+/// border closes the last row exactly like a column-span underline does.
+struct SimpleTable<'a> {
+    lines: &'a [Line<'a>],
+    block: Vec<Vec<&'a str>>,
+    columns: Vec<(usize, usize)>,
+    border_end: usize,
+    /// Parsed rows: starting line index plus `(colspan, text)` for each cell.
+    rows: Vec<(usize, Vec<(usize, String)>)>,
 }
 
-/// Try to parse a simple table (=== separators)
-pub fn try_parse_simple_table(ls: &mut Lines<'_>) -> Option<Block> {
-    let first_line = ls.peek()?;
-    if !is_table_separator(first_line.raw) {
-        return None;
+impl SimpleTable<'_> {
+    fn error(&self, idx: usize, msg: &str) -> ParseError {
+        ParseError::Invalid { line: self.lines[idx].num, msg: msg.into() }
     }
 
-    let separator = first_line.raw;
-    let columns = parse_column_boundaries(separator);
-    if columns.is_empty() {
-        return None;
+    /// Columns of a span underline, checked against the table's full width.
+    fn span_columns(&self, idx: usize) -> Result<Vec<(usize, usize)>, ParseError> {
+        let mut columns = dash_runs(&self.block[idx]);
+        let last = columns.len() - 1;
+        if columns[last].1 != self.border_end {
+            return Err(self.error(idx, "column span underline does not reach the table's right edge"));
+        }
+        // The rightmost column is unbounded: let it inherit any widening from earlier rows.
+        columns[last].1 = self.columns[self.columns.len() - 1].1;
+        Ok(columns)
     }
 
-    ls.next();
+    /// Reject text in the margins between columns, widening the last column for text that runs past it.
+    fn check_columns(&mut self, first: usize, last: usize, columns: &mut [(usize, usize)]) -> Result<(), ParseError> {
+        let lastcol = columns.len() - 1;
+        for i in 0..columns.len() {
+            let (start, end) = columns[i];
+            let next_start = columns.get(i + 1).map_or(usize::MAX, |c| c.0);
+            for idx in first..last {
+                let line = &self.block[idx];
+                if i == lastcol && has_text(line, end, line.len()) {
+                    let new_end = start + line[start..].iter().rposition(|col| !is_blank_column(col)).unwrap_or(0) + 1;
+                    columns[i].1 = columns[i].1.max(new_end);
+                    let main = self.columns.len() - 1;
+                    self.columns[main].1 = self.columns[main].1.max(new_end);
+                } else if has_text(line, end, next_start) {
+                    return Err(self.error(idx, "simple table has text outside its column boundaries"));
+                }
+            }
+        }
+        Ok(())
+    }
 
-    let header_line = ls.peek()?;
-    if is_table_separator(header_line.raw) {
-        ls.backtrack();
-        return None;
+    /// Record the row made of lines `first..last`, split by `span_line` if it closes the row.
+    fn parse_row(&mut self, first: usize, last: usize, span_line: Option<usize>) -> Result<(), ParseError> {
+        if first >= last {
+            return Ok(());
+        }
+        let mut columns = match span_line {
+            Some(idx) => self.span_columns(idx)?,
+            None => self.columns.clone(),
+        };
+        self.check_columns(first, last, &mut columns)?;
+
+        let mut cells = Vec::with_capacity(columns.len());
+        let mut base = 0;
+        for &(start, end) in &columns {
+            let misaligned = || {
+                self.error(
+                    span_line.unwrap_or(first),
+                    "column span underline is not aligned with the table columns",
+                )
+            };
+            if self.columns.get(base).is_none_or(|c| c.0 != start) {
+                return Err(misaligned());
+            }
+            let mut colspan = 1;
+            while self.columns[base].1 != end {
+                base += 1;
+                colspan += 1;
+                if base == self.columns.len() {
+                    return Err(misaligned());
+                }
+            }
+            base += 1;
+            cells.push((colspan, self.cell_text(first, last, start, end)));
+        }
+        self.rows.push((first, cells));
+        Ok(())
     }
-    let header_cells = extract_cells(header_line.raw, &columns);
-    ls.next();
 
-    if !ls.peek().map(|l| is_table_separator(l.raw)).unwrap_or(false) {
-        ls.backtrack();
-        ls.backtrack();
-        return None;
+    /// Text of one cell, right-trimmed and dedented to its own left margin.
+    fn cell_text(&self, first: usize, last: usize, start: usize, end: usize) -> String {
+        let lines: Vec<String> = self.block[first..last]
+            .iter()
+            .map(|line| {
+                let end = end.min(line.len());
+                if start < end { columns_text(&line[start..end]).trim_end().to_string() } else { String::new() }
+            })
+            .collect();
+        let margin = lines
+            .iter()
+            .filter(|l| !l.is_empty())
+            .map(|l| leading_indent(l))
+            .min()
+            .unwrap_or(0);
+        let lines: Vec<String> = lines.into_iter().map(|l| l.chars().skip(margin).collect()).collect();
+        lines.join("\n").trim_matches('\n').to_string()
     }
-    ls.next();
+}
+
+/// Text of a table cell with its `(colspan, rowspan)`, before the text is parsed as blocks.
+type CellText = (usize, usize, String);
+
+/// A table's cell text laid out in rows, before the text is parsed as blocks.
+struct Layout {
+    rows: Vec<Vec<CellText>>,
+    header_rows: usize,
+    /// Width of each grid column in the source, in display columns.
+    widths: Vec<usize>,
+}
 
-    let mut body_rows = Vec::new();
-    while let Some(line) = ls.peek() {
-        if is_table_separator(line.raw) {
-            ls.next();
+impl Layout {
+    /// Parse the text of every cell and split off the header rows.
+    fn into_table(self, opts: &ParserOptions) -> Result<Block<'static>, ParseError> {
+        let mut headers = parse_rows(self.rows, opts)?;
+        let rows = headers.split_off(self.header_rows);
+        Ok(Block::Table { headers, rows, widths: self.widths })
+    }
+}
+
+/// Try to parse a simple table (=== borders), with multi-line rows, optional header rows and `---`
+/// column-span underlines.
+///
+/// The table ends at the border that is followed by a blank line or the end of input, or at the
+/// third border. A malformed table is kept as a literal block holding its source, and parsing
+/// carries on after it; without [lenient tables](ParserOptions::lenient_tables) it is reported as
+/// an error instead.
+pub fn try_parse_simple_table<'a>(ls: &mut Lines<'a>, opts: &ParserOptions) -> Result<Option<Block<'a>>, ParseError> {
+    let Some(top) = ls.peek() else {
+        return Ok(None);
+    };
+    if !is_simple_table_top(top.raw) {
+        return Ok(None);
+    }
+    let start = ls.i;
+    match simple_table_layout(ls) {
+        Ok(layout) => layout.into_table(opts).map(Some),
+        Err(err) => malformed_table(ls, start, err, opts).map(Some),
+    }
+}
+
+/// Lay out the simple table opening at the current line, with each column as wide as its run of `=`
+/// in the borders (the last one widened to the longest text running past it).
+///
+/// `ls` is left after the table; for a malformed table, after the lines to keep as its source.
+fn simple_table_layout(ls: &mut Lines<'_>) -> Result<Layout, ParseError> {
+    let top = ls.all[ls.i];
+    let indent = leading_indent(top.raw);
+    let top_len = top.raw.trim().len();
+
+    let mut found = 0;
+    let mut end = None;
+    for i in ls.i + 1..ls.all.len() {
+        let raw = ls.all[i].raw;
+        if !is_table_separator(raw) {
+            continue;
+        }
+        if raw.trim().len() != top_len || leading_indent(raw) != indent {
+            ls.i = i + 1;
+            return Err(ParseError::Invalid {
+                line: ls.all[i].num,
+                msg: "simple table border does not match the top border".into(),
+            });
+        }
+        found += 1;
+        if found == 2 || ls.all.get(i + 1).is_none_or(|next| is_blank(next.raw)) {
+            end = Some(i);
             break;
         }
-        let cells = extract_cells(line.raw, &columns);
-        body_rows.push(cells);
-        ls.next();
+    }
+    let Some(end) = end else {
+        ls.i = (ls.i + 1..ls.all.len())
+            .find(|&i| is_blank(ls.all[i].raw))
+            .unwrap_or(ls.all.len());
+        return Err(ParseError::Invalid { line: top.num, msg: "simple table is missing its bottom border".into() });
+    };
+    let lines = &ls.all[ls.i..=end];
+    ls.i = end + 1;
+
+    if let Some(line) = lines
+        .iter()
+        .find(|l| !is_blank(l.raw) && leading_indent(l.raw) < indent)
+    {
+        return Err(ParseError::Invalid {
+            line: line.num,
+            msg: "simple table has text outside its column boundaries".into(),
+        });
+    }
+    let mut block: Vec<Vec<&str>> = lines
+        .iter()
+        .map(|l| display_columns(l.raw.trim_end()).into_iter().skip(indent).collect())
+        .collect();
+    let last = block.len() - 1;
+    for idx in [0, last] {
+        block[idx] = block[idx].iter().map(|&c| if c == "=" { "-" } else { c }).collect();
+    }
+    let mut head_body_sep = None;
+    for idx in 1..last {
+        if is_table_separator(lines[idx].raw) {
+            head_body_sep = Some(idx);
+            block[idx].clear();
+        }
     }
 
-    let headers: Vec<Vec<Inline>> = header_cells.into_iter().map(|cell| parse_inlines(&cell)).collect();
+    let columns = dash_runs(&block[0]);
+    let border_end = columns[columns.len() - 1].1;
+    let (first_start, first_end) = columns[0];
+    let mut table = SimpleTable { lines, block, columns, border_end, rows: Vec::new() };
+
+    let mut start = 1;
+    let mut text_found = false;
+    for offset in 1..=last {
+        let line = &table.block[offset];
+        if is_span_line(line) {
+            table.parse_row(start, offset, Some(offset))?;
+            start = offset + 1;
+            text_found = false;
+        } else if has_text(line, first_start, first_end) {
+            if text_found && offset != start {
+                table.parse_row(start, offset, None)?;
+            }
+            start = offset;
+            text_found = true;
+        } else if !text_found {
+            start = offset + 1;
+        }
+    }
 
-    let rows: Vec<Vec<Vec<Inline>>> = body_rows
+    let header_rows = table
+        .rows
+        .iter()
+        .filter(|(first, _)| head_body_sep.is_some_and(|sep| *first < sep))
+        .count();
+    let widths = table.columns.iter().map(|(start, end)| end - start).collect();
+    let rows = table
+        .rows
         .into_iter()
-        .map(|row| row.into_iter().map(|cell| parse_inlines(&cell)).collect())
+        .map(|(_, cells)| cells.into_iter().map(|(colspan, text)| (colspan, 1, text)).collect())
         .collect();
-
-    Some(Block::Table { headers, rows })
+    Ok(Layout { rows, header_rows, widths })
 }
 
-/// Extract grid table cell from a row based on column positions
-fn extract_grid_cell(row: &str, start_col: usize, end_col: usize) -> String {
-    if start_col >= row.len() {
-        return String::new();
+/// Keep the source of a malformed table, from line `start` up to the current line, as a literal block,
+/// or return `err` when tables are not parsed leniently.
+fn malformed_table<'a>(
+    ls: &Lines<'a>, start: usize, err: ParseError, opts: &ParserOptions,
+) -> Result<Block<'a>, ParseError> {
+    if !opts.lenient_tables_enabled() {
+        return Err(err);
     }
-    let end = end_col.min(row.len());
-    let cell_text = &row[start_col..end];
+    Ok(Block::LiteralBlock(trim_end(ls.text_since(start))))
+}
 
-    cell_text
-        .trim_matches(|c: char| c == '|' || c.is_whitespace())
-        .to_string()
+/// Parse the text of every cell as nested block content.
+fn parse_rows(rows: Vec<Vec<CellText>>, opts: &ParserOptions) -> Result<Vec<Vec<TableCell<'static>>>, ParseError> {
+    rows.into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|(colspan, rowspan, text)| Ok(TableCell { content: parse_cell(&text, opts)?, colspan, rowspan }))
+                .collect()
+        })
+        .collect()
 }
 
-/// Try to parse a grid table (+---+---+)
-pub fn try_parse_grid_table(ls: &mut Lines<'_>) -> Option<Block> {
-    let first_border = ls.peek()?;
-    if !is_grid_border(first_border.raw) {
-        return None;
-    }
+/// Parse a cell's dedented text as nested block content.
+fn parse_cell(text: &str, opts: &ParserOptions) -> Result<Vec<Block<'static>>, ParseError> {
+    if text.trim().is_empty() { Ok(Vec::new()) } else { parse_nested(text, opts) }
+}
 
-    let col_positions = parse_grid_columns(first_border.raw);
-    if col_positions.len() < 2 {
-        return None;
-    }
+/// Character grid of a grid table, scanned cell by cell the way docutils' `GridTableParser` does.
+///
+/// Each cell is the rectangle between four `+` corners; the corners found along its right and bottom
+/// edges become the row and column separators that determine spans.
+struct Grid<'a> {
+    rows: Vec<Vec<&'a str>>,
+    bottom: usize,
+    right: usize,
+    /// For every column, the last grid row already covered by a parsed cell.
+    done: Vec<Option<usize>>,
+}
 
-    ls.next();
+/// Corner positions crossed while tracing one cell's border.
+type CellScan = (usize, usize, Vec<usize>, Vec<usize>);
 
-    let mut all_rows: Vec<Vec<String>> = Vec::new();
-    let mut current_row_lines: Vec<String> = Vec::new();
-    let mut header_row_count = 0;
-    let mut found_header_sep = false;
+impl Grid<'_> {
+    fn at(&self, row: usize, col: usize) -> char {
+        self.rows[row][col].chars().next().unwrap_or(' ')
+    }
 
-    while let Some(line) = ls.peek() {
-        if is_grid_border(line.raw) {
-            if !current_row_lines.is_empty() {
-                let merged_row = merge_multi_line_row(&current_row_lines, &col_positions);
-                all_rows.push(merged_row);
-                current_row_lines.clear();
+    fn scan_cell(&self, top: usize, left: usize) -> Option<CellScan> {
+        self.scan_right(top, left)
+    }
 
-                if !found_header_sep {
-                    header_row_count = all_rows.len();
+    fn scan_right(&self, top: usize, left: usize) -> Option<CellScan> {
+        let mut colseps = Vec::new();
+        for i in left + 1..=self.right {
+            match self.at(top, i) {
+                '+' => {
+                    colseps.push(i);
+                    if let Some((bottom, rowseps, more_colseps)) = self.scan_down(top, left, i) {
+                        colseps.extend(more_colseps);
+                        return Some((bottom, i, rowseps, colseps));
+                    }
                 }
+                '-' => {}
+                _ => return None,
             }
+        }
+        None
+    }
 
-            if is_grid_header_separator(line.raw) && !found_header_sep {
-                found_header_sep = true;
+    fn scan_down(&self, top: usize, left: usize, right: usize) -> Option<(usize, Vec<usize>, Vec<usize>)> {
+        let mut rowseps = Vec::new();
+        for i in top + 1..=self.bottom {
+            match self.at(i, right) {
+                '+' => {
+                    rowseps.push(i);
+                    if let Some((more_rowseps, colseps)) = self.scan_left(top, left, i, right) {
+                        rowseps.extend(more_rowseps);
+                        return Some((i, rowseps, colseps));
+                    }
+                }
+                '|' => {}
+                _ => return None,
             }
+        }
+        None
+    }
 
-            ls.next();
-
-            if let Some(next) = ls.peek() {
-                if !is_grid_border(next.raw) && !next.raw.trim_start().starts_with('|') {
-                    break;
-                }
-            } else {
-                break;
+    fn scan_left(&self, top: usize, left: usize, bottom: usize, right: usize) -> Option<(Vec<usize>, Vec<usize>)> {
+        let mut colseps = Vec::new();
+        for i in (left + 1..right).rev() {
+            match self.at(bottom, i) {
+                '+' => colseps.push(i),
+                '-' => {}
+                _ => return None,
             }
-        } else if line.raw.trim_start().starts_with('|') {
-            current_row_lines.push(line.raw.to_string());
-            ls.next();
-        } else {
-            break;
         }
+        if self.at(bottom, left) != '+' {
+            return None;
+        }
+        let rowseps = self.scan_up(top, left, bottom)?;
+        Some((rowseps, colseps))
     }
 
-    if !current_row_lines.is_empty() {
-        let merged_row = merge_multi_line_row(&current_row_lines, &col_positions);
-        all_rows.push(merged_row);
+    fn scan_up(&self, top: usize, left: usize, bottom: usize) -> Option<Vec<usize>> {
+        let mut rowseps = Vec::new();
+        for i in (top + 1..bottom).rev() {
+            match self.at(i, left) {
+                '+' => rowseps.push(i),
+                '|' => {}
+                _ => return None,
+            }
+        }
+        Some(rowseps)
     }
 
-    if all_rows.is_empty() {
-        return None;
+    /// Text inside the cell, right-trimmed and dedented to its own left margin.
+    fn cell_lines(&self, top: usize, left: usize, bottom: usize, right: usize) -> Vec<String> {
+        let lines: Vec<String> = (top + 1..bottom)
+            .map(|row| columns_text(&self.rows[row][left + 1..right]).trim_end().to_string())
+            .collect();
+        let margin = lines
+            .iter()
+            .filter(|l| !l.is_empty())
+            .map(|l| leading_indent(l))
+            .min()
+            .unwrap_or(0);
+        lines.into_iter().map(|l| l.chars().skip(margin).collect()).collect()
     }
+}
 
-    let (header_rows, body_rows) = if header_row_count > 0 {
-        all_rows.split_at(header_row_count)
-    } else {
-        (&all_rows[..0], all_rows.as_slice())
+/// Try to parse a grid table (+---+---+), including cells that span rows or columns.
+///
+/// Once a border line is followed by table rows, the lines belong to the table; if they do not form a
+/// valid grid they are kept as a literal block holding their source instead of falling back to
+/// paragraphs, or reported as an error without [lenient tables](ParserOptions::lenient_tables).
+pub fn try_parse_grid_table<'a>(ls: &mut Lines<'a>, opts: &ParserOptions) -> Result<Option<Block<'a>>, ParseError> {
+    let Some(first) = ls.peek() else {
+        return Ok(None);
     };
+    if !is_grid_border(first.raw) {
+        return Ok(None);
+    }
+    let indent = leading_indent(first.raw);
 
-    let headers: Vec<Vec<Inline>> = if !header_rows.is_empty() {
-        header_rows[0].iter().map(|cell| parse_inlines(cell)).collect()
-    } else {
-        Vec::new()
-    };
+    let start = ls.i;
+    let mut end = start;
+    while end < ls.all.len() && is_grid_line(ls.all[end].raw, indent) {
+        end += 1;
+    }
+    if end - start < 3 {
+        return Ok(None);
+    }
+    ls.i = end;
+    match grid_table_layout(&ls.all[start..end], indent) {
+        Ok(layout) => layout.into_table(opts).map(Some),
+        Err(err) => malformed_table(ls, start, err, opts).map(Some),
+    }
+}
 
-    let rows: Vec<Vec<Vec<Inline>>> = body_rows
+/// Lay out the lines of a grid table, with each column as wide as the space between the column
+/// separators around it.
+fn grid_table_layout(lines: &[Line<'_>], indent: usize) -> Result<Layout, ParseError> {
+    let mut rows: Vec<Vec<&str>> = lines
         .iter()
-        .map(|row| row.iter().map(|cell| parse_inlines(cell)).collect())
+        .map(|l| display_columns(l.raw.trim_end()).into_iter().skip(indent).collect())
         .collect();
+    let width = rows[0].len();
+
+    for (line, row) in lines.iter().zip(&rows) {
+        if row.len() != width || !matches!(row[width - 1], "+" | "|") {
+            return Err(ParseError::Invalid {
+                line: line.num,
+                msg: "grid table right border is not aligned with the top border".into(),
+            });
+        }
+    }
 
-    Some(Block::Table { headers, rows })
-}
+    let last = lines.len() - 1;
+    if !is_grid_border(lines[last].raw) {
+        return Err(ParseError::Invalid {
+            line: lines[last].num,
+            msg: "grid table is missing its bottom border".into(),
+        });
+    }
 
-/// Merge multiple lines of a grid table row into single cells
-fn merge_multi_line_row(lines: &[String], col_positions: &[usize]) -> Vec<String> {
-    let num_cols = col_positions.len().saturating_sub(1);
-    let mut cells: Vec<String> = vec![String::new(); num_cols];
+    let mut head_body_sep = None;
+    for (idx, line) in lines.iter().enumerate().take(last).skip(1) {
+        if is_grid_border(line.raw) && line.raw.contains('=') {
+            if head_body_sep.is_some() {
+                return Err(ParseError::Invalid {
+                    line: line.num,
+                    msg: "grid table has more than one header separator".into(),
+                });
+            }
+            head_body_sep = Some(idx);
+            rows[idx] = rows[idx].iter().map(|&c| if c == "=" { "-" } else { c }).collect();
+        }
+    }
 
-    for line in lines {
-        for col_idx in 0..num_cols {
-            let start = col_positions[col_idx];
-            let end = col_positions[col_idx + 1];
-            let cell_content = extract_grid_cell(line, start, end);
+    let mut grid = Grid { rows, bottom: last, right: width - 1, done: vec![None; width] };
+    let mut rowseps = BTreeSet::from([0]);
+    let mut colseps = BTreeSet::from([0]);
+    let mut cells = Vec::new();
+    let mut corners = vec![(0, 0)];
 
-            if !cell_content.is_empty() {
-                if !cells[col_idx].is_empty() {
-                    cells[col_idx].push(' ');
-                }
-                cells[col_idx].push_str(&cell_content);
-            }
+    while !corners.is_empty() {
+        let (top, left) = corners.remove(0);
+        if top == grid.bottom || left == grid.right || grid.done[left].is_some_and(|d| top <= d) {
+            continue;
         }
+        let Some((bottom, right, cell_rowseps, cell_colseps)) = grid.scan_cell(top, left) else {
+            continue;
+        };
+        for col in left..right {
+            grid.done[col] = Some(bottom - 1);
+        }
+        rowseps.extend(cell_rowseps);
+        colseps.extend(cell_colseps);
+        corners.push((top, right));
+        corners.push((bottom, left));
+        corners.sort_unstable();
+        cells.push((top, left, bottom, right));
     }
 
-    cells
+    if let Some(col) = (0..grid.right).find(|&col| grid.done[col] != Some(grid.bottom - 1)) {
+        let row = grid.done[col].map_or(0, |d| d + 1);
+        return Err(ParseError::Invalid {
+            line: lines[row].num,
+            msg: "malformed grid table: a cell starting on this border never closes".into(),
+        });
+    }
+
+    let row_index: BTreeMap<usize, usize> = rowseps.iter().enumerate().map(|(i, &r)| (r, i)).collect();
+    let col_index: BTreeMap<usize, usize> = colseps.iter().enumerate().map(|(i, &c)| (c, i)).collect();
+
+    let header_rows = match head_body_sep {
+        Some(sep) => *row_index.get(&sep).ok_or_else(|| ParseError::Invalid {
+            line: lines[sep].num,
+            msg: "grid table header separator is crossed by a spanning cell".into(),
+        })?,
+        None => 0,
+    };
+
+    cells.sort_unstable();
+    let mut table_rows: Vec<Vec<CellText>> = vec![Vec::new(); rowseps.len() - 1];
+    for (top, left, bottom, right) in cells {
+        let row = row_index[&top];
+        let col = col_index[&left];
+        let text = grid.cell_lines(top, left, bottom, right).join("\n");
+        table_rows[row].push((col_index[&right] - col, row_index[&bottom] - row, text));
+    }
+    let widths = colseps
+        .iter()
+        .zip(colseps.iter().skip(1))
+        .map(|(left, right)| right - left - 1)
+        .collect();
+    Ok(Layout { rows: table_rows, header_rows, widths })
 }
@@ -0,0 +1,162 @@
+//! Google style: `Args:` headers followed by indented `name (type): description` entries.
+
+use super::{
+    DocRaise, DocReturn, Docstring, SectionKind, build_param, dedent, parse_body, push_prose_section, push_see_also,
+    split_entries,
+};
+use crate::leading_indent;
+
+/// The section title on `lines[i]`, if it is a known `Title:` header followed by an indented body.
+fn header_at<'a>(lines: &[&'a str], i: usize) -> Option<(&'a str, SectionKind)> {
+    let line = lines[i];
+    let title = line.trim().strip_suffix(':').filter(|t| !t.ends_with(':'))?;
+    let kind = SectionKind::from_title(title)?;
+    let indent = leading_indent(line);
+    let next = lines[i + 1..].iter().find(|l| !l.trim().is_empty())?;
+    (leading_indent(next) > indent).then_some((title, kind))
+}
+
+/// Index of the first section header, or `lines.len()` if there is none.
+pub(super) fn first_section(lines: &[&str]) -> usize {
+    (0..lines.len())
+        .find(|&i| header_at(lines, i).is_some())
+        .unwrap_or(lines.len())
+}
+
+pub(super) fn parse_sections(lines: &[&str], doc: &mut Docstring) {
+    let mut i = 0;
+    while i < lines.len() {
+        let Some((title, kind)) = header_at(lines, i) else {
+            let end = (i..lines.len())
+                .find(|&j| header_at(lines, j).is_some())
+                .unwrap_or(lines.len());
+            doc.description.extend(parse_body(&lines[i..end].join("\n")));
+            i = end;
+            continue;
+        };
+        let indent = leading_indent(lines[i]);
+        let end = (i + 1..lines.len())
+            .find(|&j| !lines[j].trim().is_empty() && leading_indent(lines[j]) <= indent)
+            .unwrap_or(lines.len());
+        parse_section(doc, kind, title, &lines[i + 1..end]);
+        i = end;
+    }
+}
+
+fn parse_section(doc: &mut Docstring, kind: SectionKind, title: &str, body: &[&str]) {
+    match kind {
+        SectionKind::Params | SectionKind::Attributes => {
+            let params = split_entries(body).into_iter().map(|(head, rest)| {
+                let (name, desc) = split_at_colon(head);
+                let (name, spec) = match name.strip_suffix(')').and_then(|n| n.split_once('(')) {
+                    Some((name, spec)) => (name, Some(spec)),
+                    None => (name, None),
+                };
+                build_param(name, spec, &join_description(desc, &rest))
+            });
+            if kind == SectionKind::Params { doc.params.extend(params) } else { doc.attributes.extend(params) }
+        }
+        SectionKind::Returns | SectionKind::Yields => {
+            let entries = split_entries(body);
+            let values = match entries.len() {
+                0 | 1 => None,
+                _ => entries.iter().map(|(head, rest)| labelled_value(head, rest)).collect(),
+            };
+            let values = values.unwrap_or_else(|| vec![single_value(body)]);
+            if kind == SectionKind::Returns { doc.returns.extend(values) } else { doc.yields.extend(values) }
+        }
+        SectionKind::Raises | SectionKind::Warns => {
+            for (head, rest) in split_entries(body) {
+                let (type_name, desc) = split_at_colon(head);
+                let entry = DocRaise {
+                    type_name: type_name.to_string(),
+                    description: parse_body(&join_description(desc, &rest)),
+                };
+                if kind == SectionKind::Raises { doc.raises.push(entry) } else { doc.warns.push(entry) }
+            }
+        }
+        SectionKind::SeeAlso => {
+            for (head, rest) in split_entries(body) {
+                push_see_also(doc, head, &rest);
+            }
+        }
+        _ => push_prose_section(doc, kind, title, &dedent(body)),
+    }
+}
+
+/// A `Returns` entry written as `type: description` or `name (type): description`, as the writer
+/// emits one entry per value when there are several.
+fn labelled_value(head: &str, rest: &str) -> Option<DocReturn> {
+    let (label, desc) = split_at_colon(head);
+    if desc.is_empty() && !head.ends_with(':') {
+        return None;
+    }
+    let (name, type_name) = value_label(label)?;
+    Some(DocReturn {
+        name: name.map(str::to_string),
+        type_name: Some(type_name.to_string()),
+        description: parse_body(&join_description(desc, rest)),
+    })
+}
+
+/// A `Returns` body describing one value, typed when its first line starts with a `type:` label.
+fn single_value(body: &[&str]) -> DocReturn {
+    let text = dedent(body);
+    let (first, rest) = text.split_once('\n').unwrap_or((&text, ""));
+    let (label, desc) = split_at_colon(first);
+    match value_label(label).filter(|_| !desc.is_empty()) {
+        Some((name, type_name)) => DocReturn {
+            name: name.map(str::to_string),
+            type_name: Some(type_name.to_string()),
+            description: parse_body(&join_description(desc, rest)),
+        },
+        None => DocReturn { description: parse_body(&text), ..DocReturn::default() },
+    }
+}
+
+/// Split a value label into its optional name and its type: `int` or `total (int)`.
+fn value_label(label: &str) -> Option<(Option<&str>, &str)> {
+    if let Some((name, type_name)) = label.strip_suffix(')').and_then(|l| l.split_once(" ("))
+        && !name.is_empty()
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+    {
+        return Some((Some(name), type_name));
+    }
+    is_type_expr(label).then_some((None, label))
+}
+
+/// Split `head` at its first colon outside brackets into a trimmed label and description.
+fn split_at_colon(head: &str) -> (&str, &str) {
+    let mut depth = 0usize;
+    for (i, c) in head.char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            ':' if depth == 0 => return (head[..i].trim(), head[i + 1..].trim()),
+            _ => {}
+        }
+    }
+    (head.trim(), "")
+}
+
+/// Whether `s` reads as a type annotation rather than prose: no spaces outside brackets.
+fn is_type_expr(s: &str) -> bool {
+    let mut depth = 0usize;
+    !s.is_empty()
+        && s.chars().all(|c| {
+            match c {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            depth > 0 || !c.is_whitespace()
+        })
+}
+
+fn join_description(first: &str, rest: &str) -> String {
+    match (first.is_empty(), rest.is_empty()) {
+        (_, true) => first.to_string(),
+        (true, false) => rest.to_string(),
+        (false, false) => format!("{first}\n{rest}"),
+    }
+}
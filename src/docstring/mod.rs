@@ -0,0 +1,556 @@
+//! Structured model of Python docstrings written in Google, NumPy or Sphinx style.
+//!
+//! [`parse_docstring`] detects the convention a docstring follows and splits it into a summary,
+//! a free-form description and typed sections (parameters, returns, raises, ...). Free-form text is
+//! parsed into [`Block`]s with the docstring parser profile.
+
+mod google;
+mod numpy;
+mod sphinx;
+mod write;
+
+use crate::ast::into_owned;
+use crate::{Block, ParserOptions, leading_indent, parse_with};
+
+pub use write::{WriteOptions, write_docstring};
+
+/// The docstring convention detected by [`parse_docstring`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DocstringStyle {
+    /// `Args:` style sections with indented entries.
+    Google,
+    /// Sections underlined with dashes and `name : type` entries.
+    Numpy,
+    /// `:param x:` / `:type x:` / `:returns:` field lists.
+    Sphinx,
+    /// No recognizable sections, only a summary and description.
+    #[default]
+    Plain,
+}
+
+/// A documented parameter or attribute.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DocParam {
+    pub name: String,
+    pub type_name: Option<String>,
+    pub default: Option<String>,
+    /// Marked `optional` in its type specification.
+    pub optional: bool,
+    pub description: Vec<Block<'static>>,
+}
+
+/// A documented return or yield value.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DocReturn {
+    pub name: Option<String>,
+    pub type_name: Option<String>,
+    pub description: Vec<Block<'static>>,
+}
+
+/// A documented exception or warning.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DocRaise {
+    pub type_name: String,
+    pub description: Vec<Block<'static>>,
+}
+
+/// An entry of a "See Also" section.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SeeAlso {
+    pub name: String,
+    pub description: Vec<Block<'static>>,
+}
+
+/// A deprecation notice, from a `.. deprecated::` directive or a `Deprecated` section.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Deprecation {
+    pub version: Option<String>,
+    pub description: Vec<Block<'static>>,
+}
+
+/// A section without a dedicated field, such as `References` or `Todo`.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DocSection {
+    pub title: String,
+    pub content: Vec<Block<'static>>,
+}
+
+/// A docstring split into its summary, description and sections.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Docstring {
+    pub style: DocstringStyle,
+    /// The first paragraph, with its lines joined by spaces.
+    pub summary: String,
+    pub description: Vec<Block<'static>>,
+    pub params: Vec<DocParam>,
+    pub returns: Vec<DocReturn>,
+    pub yields: Vec<DocReturn>,
+    pub raises: Vec<DocRaise>,
+    pub warns: Vec<DocRaise>,
+    pub attributes: Vec<DocParam>,
+    pub examples: Vec<Block<'static>>,
+    pub notes: Vec<Block<'static>>,
+    pub see_also: Vec<SeeAlso>,
+    pub deprecation: Option<Deprecation>,
+    /// Remaining sections, in source order.
+    pub sections: Vec<DocSection>,
+}
+
+/// Which [`Docstring`] field a section title maps to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SectionKind {
+    Params,
+    Attributes,
+    Returns,
+    Yields,
+    Raises,
+    Warns,
+    Examples,
+    Notes,
+    SeeAlso,
+    Deprecated,
+    Other,
+}
+
+impl SectionKind {
+    /// Classify a section title, or `None` if it is not a title any style recognizes.
+    fn from_title(title: &str) -> Option<Self> {
+        let kind = match title.trim().to_ascii_lowercase().as_str() {
+            "args" | "arguments" | "parameters" | "params" | "keyword args" | "keyword arguments" | "kwargs"
+            | "other parameters" | "other params" => Self::Params,
+            "attributes" | "attrs" => Self::Attributes,
+            "returns" | "return" => Self::Returns,
+            "yields" | "yield" => Self::Yields,
+            "raises" | "raise" | "exceptions" | "except" => Self::Raises,
+            "warns" | "warn" => Self::Warns,
+            "example" | "examples" => Self::Examples,
+            "note" | "notes" => Self::Notes,
+            "see also" => Self::SeeAlso,
+            "deprecated" => Self::Deprecated,
+            "warning" | "warnings" | "todo" | "references" | "methods" => Self::Other,
+            _ => return None,
+        };
+        Some(kind)
+    }
+}
+
+/// Parse a Python docstring into a [`Docstring`], detecting its style.
+///
+/// Indentation is normalized the way `inspect.cleandoc` does, so the raw `__doc__` string can be
+/// passed in directly. Free-form text that fails to parse as markup is kept as a literal block.
+///
+/// ```
+/// use parserst::{DocstringStyle, parse_docstring};
+///
+/// let doc = parse_docstring("Add two numbers.\n\nArgs:\n    a (int): First.\n    b (int, optional): Second.\n");
+/// assert_eq!(doc.style, DocstringStyle::Google);
+/// assert_eq!(doc.summary, "Add two numbers.");
+/// assert!(doc.params[1].optional);
+/// ```
+pub fn parse_docstring(input: &str) -> Docstring {
+    let text = cleandoc(input);
+    let lines: Vec<&str> = text.lines().collect();
+    let style = detect_style(&lines);
+    let start = match style {
+        DocstringStyle::Google => google::first_section(&lines),
+        DocstringStyle::Numpy => numpy::first_section(&lines),
+        DocstringStyle::Sphinx => sphinx::first_field(&lines),
+        DocstringStyle::Plain => lines.len(),
+    };
+
+    let mut doc = Docstring { style, ..Docstring::default() };
+    let preamble = &lines[..start];
+    let summary_end = preamble
+        .iter()
+        .position(|l| l.trim().is_empty())
+        .unwrap_or(preamble.len());
+    doc.summary = preamble[..summary_end]
+        .iter()
+        .map(|l| l.trim())
+        .collect::<Vec<_>>()
+        .join(" ");
+    doc.description = parse_body(&preamble[summary_end..].join("\n"));
+
+    let rest = &lines[start..];
+    match style {
+        DocstringStyle::Google => google::parse_sections(rest, &mut doc),
+        DocstringStyle::Numpy => numpy::parse_sections(rest, &mut doc),
+        DocstringStyle::Sphinx => {
+            sphinx::take_directive_sections(&mut doc);
+            sphinx::parse_fields(rest, &mut doc);
+        }
+        DocstringStyle::Plain => {}
+    }
+    take_deprecation(&mut doc);
+    doc
+}
+
+/// Pick the style whose section syntax appears in `lines`, preferring NumPy, then Google, then Sphinx.
+fn detect_style(lines: &[&str]) -> DocstringStyle {
+    if numpy::first_section(lines) < lines.len() {
+        DocstringStyle::Numpy
+    } else if google::first_section(lines) < lines.len() {
+        DocstringStyle::Google
+    } else if sphinx::first_field(lines) < lines.len() || sphinx::has_directive_sections(lines) {
+        DocstringStyle::Sphinx
+    } else {
+        DocstringStyle::Plain
+    }
+}
+
+/// Strip surrounding blank lines and the common indentation of every line after the first.
+fn cleandoc(input: &str) -> String {
+    let lines: Vec<&str> = input.lines().collect();
+    let margin = lines
+        .iter()
+        .skip(1)
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| leading_indent(l))
+        .min()
+        .unwrap_or(0);
+    let mut out: Vec<String> = lines
+        .iter()
+        .enumerate()
+        .map(|(i, l)| {
+            if i == 0 {
+                l.trim().to_string()
+            } else {
+                l.chars().skip(margin).collect::<String>().trim_end().to_string()
+            }
+        })
+        .collect();
+    while out.first().is_some_and(|l| l.is_empty()) {
+        out.remove(0);
+    }
+    while out.last().is_some_and(|l| l.is_empty()) {
+        out.pop();
+    }
+    out.join("\n")
+}
+
+/// Remove the common indentation of `lines` and trim surrounding blank lines.
+fn dedent(lines: &[&str]) -> String {
+    let margin = lines
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| leading_indent(l))
+        .min()
+        .unwrap_or(0);
+    let text: Vec<String> = lines.iter().map(|l| l.chars().skip(margin).collect()).collect();
+    text.join("\n").trim_matches('\n').trim_end().to_string()
+}
+
+/// Split a section body into entries: each starts on a line at the body's base indentation and
+/// owns the more deeply indented (or blank) lines below it. Returns each head line with its
+/// dedented continuation, which starts with a blank line when one separates it from the head.
+fn split_entries<'a>(lines: &[&'a str]) -> Vec<(&'a str, String)> {
+    let Some(base) = lines.iter().find(|l| !l.trim().is_empty()).map(|l| leading_indent(l)) else {
+        return Vec::new();
+    };
+    let mut entries: Vec<(&str, Vec<&str>)> = Vec::new();
+    for &line in lines {
+        match entries.last_mut() {
+            Some((_, body)) if line.trim().is_empty() || leading_indent(line) > base => body.push(line),
+            _ if line.trim().is_empty() => {}
+            _ => entries.push((line.trim(), Vec::new())),
+        }
+    }
+    entries
+        .into_iter()
+        .map(|(head, body)| {
+            let rest = dedent(&body);
+            match body.first() {
+                Some(line) if line.trim().is_empty() && !rest.is_empty() => (head, format!("\n{rest}")),
+                _ => (head, rest),
+            }
+        })
+        .collect()
+}
+
+/// Parse free-form docstring text into blocks.
+///
+/// Doctest chunks (`>>>` lines up to the next blank line) become literal blocks so they are not
+/// mistaken for `>` quotes.
+fn parse_body(text: &str) -> Vec<Block<'static>> {
+    let mut blocks = Vec::new();
+    let mut pending: Vec<&str> = Vec::new();
+    let mut lines = text.lines().peekable();
+    while let Some(line) = lines.next() {
+        if !line.starts_with(">>>") {
+            pending.push(line);
+            continue;
+        }
+        blocks.extend(parse_markup(&pending.join("\n")));
+        pending.clear();
+        let mut doctest = vec![line];
+        while let Some(next) = lines.next_if(|l| !l.trim().is_empty()) {
+            doctest.push(next);
+        }
+        blocks.push(Block::LiteralBlock(doctest.join("\n").into()));
+    }
+    blocks.extend(parse_markup(&pending.join("\n")));
+    blocks
+}
+
+fn parse_markup(text: &str) -> Vec<Block<'static>> {
+    if text.trim().is_empty() {
+        return Vec::new();
+    }
+    parse_with(text, &ParserOptions::docstring())
+        .map(into_owned)
+        .unwrap_or_else(|_| vec![Block::LiteralBlock(text.to_string().into())])
+}
+
+/// Split `s` at commas that are not nested in brackets, braces or quotes.
+fn split_top_level(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '\'' | '"' if quote == Some(c) => quote = None,
+            '\'' | '"' if quote.is_none() => quote = Some(c),
+            '(' | '[' | '{' if quote.is_none() => depth += 1,
+            ')' | ']' | '}' if quote.is_none() => depth = depth.saturating_sub(1),
+            ',' if quote.is_none() && depth == 0 => {
+                parts.push(s[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(s[start..].trim());
+    parts
+}
+
+/// Build a parameter from its name, a type specification such as `int, optional, default 3`, and its
+/// description text.
+fn build_param(name: &str, spec: Option<&str>, description: &str) -> DocParam {
+    let mut param = DocParam { name: name.trim().to_string(), ..DocParam::default() };
+    if let Some(spec) = spec {
+        apply_type_spec(&mut param, spec);
+    }
+    if param.default.is_none() {
+        param.default = default_from_description(description);
+    }
+    param.description = parse_body(description);
+    param
+}
+
+/// Fill in the type, `optional` flag and default of `param` from a type specification.
+fn apply_type_spec(param: &mut DocParam, spec: &str) {
+    let mut types = Vec::new();
+    for part in split_top_level(spec) {
+        let lower = part.to_ascii_lowercase();
+        if lower == "optional" {
+            param.optional = true;
+        } else if let Some(value) = ["defaults to", "default:", "default=", "default"]
+            .iter()
+            .find_map(|prefix| lower.starts_with(prefix).then(|| part[prefix.len()..].trim()))
+        {
+            param.default = Some(value.to_string());
+        } else if !part.is_empty() {
+            types.push(part);
+        }
+    }
+    if !types.is_empty() {
+        param.type_name = Some(types.join(", "));
+    }
+}
+
+/// The value of a trailing `Defaults to X.` sentence.
+fn default_from_description(text: &str) -> Option<String> {
+    let idx = text.rfind("Defaults to ")?;
+    let value = text[idx + "Defaults to ".len()..].trim();
+    let value = value.strip_suffix('.').unwrap_or(value).trim_matches('`');
+    (!value.is_empty() && !value.contains('\n')).then(|| value.to_string())
+}
+
+/// Move a `.. deprecated::` directive out of the description into [`Docstring::deprecation`].
+fn take_deprecation(doc: &mut Docstring) {
+    let Some(idx) = doc
+        .description
+        .iter()
+        .position(|b| matches!(b, Block::Directive { name, .. } if name == "deprecated"))
+    else {
+        return;
+    };
+    if let Block::Directive { argument, content, .. } = doc.description.remove(idx) {
+        let version = argument.trim();
+        doc.deprecation =
+            Some(Deprecation { version: (!version.is_empty()).then(|| version.to_string()), description: content });
+    }
+}
+
+/// Store a section body whose entries need no style-specific parsing.
+fn push_prose_section(doc: &mut Docstring, kind: SectionKind, title: &str, body: &str) {
+    let content = parse_body(body);
+    match kind {
+        SectionKind::Examples => doc.examples.extend(content),
+        SectionKind::Notes => doc.notes.extend(content),
+        SectionKind::Deprecated => doc.deprecation = Some(Deprecation { version: None, description: content }),
+        _ => doc
+            .sections
+            .push(DocSection { title: title.trim().to_string(), content }),
+    }
+}
+
+/// Parse "See Also" entries of the form `name, other : description`.
+fn push_see_also(doc: &mut Docstring, head: &str, body: &str) {
+    let (names, inline) = match head.split_once(':') {
+        Some((names, desc)) => (names, desc.trim()),
+        None => (head, ""),
+    };
+    let text = [inline, body]
+        .iter()
+        .filter(|s| !s.is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join("\n");
+    let description = parse_body(&text);
+    for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        doc.see_also
+            .push(SeeAlso { name: name.to_string(), description: description.clone() });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Inline;
+
+    fn text(blocks: &[Block]) -> String {
+        blocks
+            .iter()
+            .map(|b| match b {
+                Block::Paragraph(inlines) => inlines
+                    .iter()
+                    .map(|i| match i {
+                        Inline::Text(t) => t.to_string(),
+                        other => format!("{other:?}"),
+                    })
+                    .collect(),
+                Block::LiteralBlock(t) => t.to_string(),
+                other => format!("{other:?}"),
+            })
+            .collect::<Vec<_>>()
+            .join("|")
+    }
+
+    #[test]
+    fn parses_google_style() {
+        let doc = parse_docstring(
+            r#"Fetch rows from a table.
+
+            Longer explanation of the lookup.
+
+            Args:
+                table (str): Name of the table.
+                keys (list[str], optional): Keys to fetch.
+                    Defaults to None.
+                *args: Extra positional values.
+
+            Returns:
+                dict[str, int]: Mapping of keys to rows.
+
+            Raises:
+                IOError: If the table is unreachable.
+
+            Example:
+                >>> fetch("users")
+                {}
+            "#,
+        );
+        assert_eq!(doc.style, DocstringStyle::Google);
+        assert_eq!(doc.summary, "Fetch rows from a table.");
+        assert_eq!(text(&doc.description), "Longer explanation of the lookup.");
+        assert_eq!(doc.params.len(), 3);
+        assert_eq!(doc.params[0].type_name.as_deref(), Some("str"));
+        assert_eq!(doc.params[1].type_name.as_deref(), Some("list[str]"));
+        assert!(doc.params[1].optional);
+        assert_eq!(doc.params[1].default.as_deref(), Some("None"));
+        assert_eq!(doc.params[2].name, "*args");
+        assert_eq!(doc.returns[0].type_name.as_deref(), Some("dict[str, int]"));
+        assert_eq!(text(&doc.returns[0].description), "Mapping of keys to rows.");
+        assert_eq!(doc.raises[0].type_name, "IOError");
+        assert_eq!(
+            doc.examples,
+            vec![Block::LiteralBlock(">>> fetch(\"users\")\n{}".into())]
+        );
+    }
+
+    #[test]
+    fn google_entries_keep_their_paragraphs() {
+        let doc = parse_docstring(
+            "Do it.\n\nArgs:\n    x (int): The value.\n\n        Negative values count from the end.\n    y: Other.\n",
+        );
+        assert_eq!(
+            doc.params[0].description,
+            vec![
+                Block::Paragraph(vec![Inline::Text("The value.".into())]),
+                Block::Paragraph(vec![Inline::Text("Negative values count from the end.".into())]),
+            ]
+        );
+        assert_eq!(text(&doc.params[1].description), "Other.");
+    }
+
+    #[test]
+    fn parses_numpy_style() {
+        let doc = parse_docstring(
+            "Compute the mean.\n\n.. deprecated:: 1.6.0\n    Use `average` instead.\n\nParameters\n----------\nx : array_like\n    Input values.\naxis : int, default 0\n    Axis to reduce.\n\nReturns\n-------\nmean : float\n    The mean.\n\nSee Also\n--------\naverage : Weighted mean.\nmedian, mode\n\nNotes\n-----\nUses pairwise summation.\n",
+        );
+        assert_eq!(doc.style, DocstringStyle::Numpy);
+        assert!(doc.description.is_empty());
+        let deprecation = doc.deprecation.unwrap();
+        assert_eq!(deprecation.version.as_deref(), Some("1.6.0"));
+        assert_eq!(doc.params[0].name, "x");
+        assert_eq!(doc.params[1].type_name.as_deref(), Some("int"));
+        assert_eq!(doc.params[1].default.as_deref(), Some("0"));
+        assert_eq!(doc.returns[0].name.as_deref(), Some("mean"));
+        assert_eq!(doc.returns[0].type_name.as_deref(), Some("float"));
+        let names: Vec<_> = doc.see_also.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["average", "median", "mode"]);
+        assert_eq!(text(&doc.notes), "Uses pairwise summation.");
+    }
+
+    #[test]
+    fn parses_sphinx_style() {
+        let doc = parse_docstring(
+            "Open a file.\n\n:param str path: Where to look.\n:param mode: Access mode.\n:type mode: str, optional\n:returns: The handle.\n:rtype: io.TextIOWrapper\n:raises OSError: When the file is missing.\n:ivar closed: Whether it is closed.\n",
+        );
+        assert_eq!(doc.style, DocstringStyle::Sphinx);
+        assert_eq!(doc.params.len(), 2);
+        assert_eq!(doc.params[0].type_name.as_deref(), Some("str"));
+        assert_eq!(doc.params[1].type_name.as_deref(), Some("str"));
+        assert!(doc.params[1].optional);
+        assert_eq!(text(&doc.params[1].description), "Access mode.");
+        assert_eq!(doc.returns[0].type_name.as_deref(), Some("io.TextIOWrapper"));
+        assert_eq!(text(&doc.returns[0].description), "The handle.");
+        assert_eq!(doc.raises[0].type_name, "OSError");
+        assert_eq!(doc.attributes[0].name, "closed");
+    }
+
+    #[test]
+    fn plain_docstrings_have_only_summary_and_description() {
+        let doc = parse_docstring("Summary that\nwraps.\n\nFor example: this is prose.");
+        assert_eq!(doc.style, DocstringStyle::Plain);
+        assert_eq!(doc.summary, "Summary that wraps.");
+        assert_eq!(text(&doc.description), "For example: this is prose.");
+    }
+
+    #[test]
+    fn unknown_google_sections_are_kept() {
+        let doc = parse_docstring("Do it.\n\nArgs:\n    x: A value.\n\nTodo:\n    * Finish this.\n");
+        assert_eq!(doc.sections.len(), 1);
+        assert_eq!(doc.sections[0].title, "Todo");
+    }
+}
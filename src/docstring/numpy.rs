@@ -0,0 +1,81 @@
+//! NumPy style: dash-underlined section titles and `name : type` entries with indented descriptions.
+
+use super::{
+    DocRaise, DocReturn, Docstring, SectionKind, build_param, dedent, parse_body, push_prose_section, push_see_also,
+    split_entries,
+};
+
+/// The section title on `lines[i]`, if it is a known title underlined with dashes.
+fn header_at<'a>(lines: &[&'a str], i: usize) -> Option<(&'a str, SectionKind)> {
+    let title = lines[i].trim();
+    let underline = lines.get(i + 1)?.trim();
+    if underline.len() < 3 || !underline.chars().all(|c| c == '-') {
+        return None;
+    }
+    Some((title, SectionKind::from_title(title)?))
+}
+
+/// Index of the first section header, or `lines.len()` if there is none.
+pub(super) fn first_section(lines: &[&str]) -> usize {
+    (0..lines.len())
+        .find(|&i| header_at(lines, i).is_some())
+        .unwrap_or(lines.len())
+}
+
+pub(super) fn parse_sections(lines: &[&str], doc: &mut Docstring) {
+    let mut i = 0;
+    while i < lines.len() {
+        let Some((title, kind)) = header_at(lines, i) else {
+            i += 1;
+            continue;
+        };
+        let end = (i + 2..lines.len())
+            .find(|&j| header_at(lines, j).is_some())
+            .unwrap_or(lines.len());
+        parse_section(doc, kind, title, &lines[i + 2..end]);
+        i = end;
+    }
+}
+
+fn parse_section(doc: &mut Docstring, kind: SectionKind, title: &str, body: &[&str]) {
+    match kind {
+        SectionKind::Params | SectionKind::Attributes => {
+            let params = split_entries(body).into_iter().map(|(head, rest)| {
+                let (name, spec) = split_head(head);
+                build_param(name, spec, &rest)
+            });
+            if kind == SectionKind::Params { doc.params.extend(params) } else { doc.attributes.extend(params) }
+        }
+        SectionKind::Returns | SectionKind::Yields => {
+            for (head, rest) in split_entries(body) {
+                let (name, type_name) = match split_head(head) {
+                    (name, Some(ty)) => (Some(name.to_string()), Some(ty.to_string())),
+                    (ty, None) => (None, Some(ty.to_string())),
+                };
+                let value = DocReturn { name, type_name, description: parse_body(&rest) };
+                if kind == SectionKind::Returns { doc.returns.push(value) } else { doc.yields.push(value) }
+            }
+        }
+        SectionKind::Raises | SectionKind::Warns => {
+            for (head, rest) in split_entries(body) {
+                let entry = DocRaise { type_name: head.to_string(), description: parse_body(&rest) };
+                if kind == SectionKind::Raises { doc.raises.push(entry) } else { doc.warns.push(entry) }
+            }
+        }
+        SectionKind::SeeAlso => {
+            for (head, rest) in split_entries(body) {
+                push_see_also(doc, head, &rest);
+            }
+        }
+        _ => push_prose_section(doc, kind, title, &dedent(body)),
+    }
+}
+
+/// Split `name : type` into its name and optional type specification.
+fn split_head(head: &str) -> (&str, Option<&str>) {
+    match head.split_once(':') {
+        Some((name, spec)) if !spec.trim().is_empty() => (name.trim(), Some(spec.trim())),
+        Some((name, _)) => (name.trim(), None),
+        None => (head.trim(), None),
+    }
+}
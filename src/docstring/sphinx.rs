@@ -0,0 +1,261 @@
+//! Sphinx style: `:param x:`, `:type x:`, `:returns:`, `:rtype:` and `:raises E:` fields.
+
+use super::{
+    DocParam, DocRaise, DocReturn, DocSection, Docstring, SectionKind, SeeAlso, apply_type_spec, build_param, dedent,
+    default_from_description, parse_body,
+};
+use crate::{Block, Inline, leading_indent};
+
+/// Field names that mark a docstring as Sphinx style.
+const FIELD_NAMES: [&str; 24] = [
+    "param",
+    "parameter",
+    "arg",
+    "argument",
+    "key",
+    "keyword",
+    "type",
+    "returns",
+    "return",
+    "rtype",
+    "raises",
+    "raise",
+    "except",
+    "exception",
+    "yields",
+    "yield",
+    "ytype",
+    "var",
+    "ivar",
+    "cvar",
+    "vartype",
+    "warns",
+    "warn",
+    "meta",
+];
+
+/// Split a `:name args: text` line into the field spec (`name args`) and its text.
+fn field_at(line: &str) -> Option<(&str, &str)> {
+    if leading_indent(line) > 0 {
+        return None;
+    }
+    let rest = line.strip_prefix(':')?;
+    let close = rest.find(':')?;
+    let spec = rest[..close].trim();
+    Some((spec, rest[close + 1..].trim()))
+}
+
+fn is_known_field(line: &str) -> bool {
+    field_at(line).is_some_and(|(spec, _)| spec.split_whitespace().next().is_some_and(|n| FIELD_NAMES.contains(&n)))
+}
+
+/// Index of the first docstring field, or `lines.len()` if there is none.
+pub(super) fn first_field(lines: &[&str]) -> usize {
+    lines.iter().position(|l| is_known_field(l)).unwrap_or(lines.len())
+}
+
+/// Whether `lines` hold a `.. seealso::` or `.. rubric::` directive, which Sphinx style uses for the
+/// sections that have no field.
+pub(super) fn has_directive_sections(lines: &[&str]) -> bool {
+    lines
+        .iter()
+        .any(|l| l.starts_with(".. seealso::") || l.starts_with(".. rubric::"))
+}
+
+/// Move `.. seealso::` entries and the blocks under each `.. rubric::` out of the description into
+/// the matching [`Docstring`] fields.
+pub(super) fn take_directive_sections(doc: &mut Docstring) {
+    let mut rubric: Option<Option<SectionKind>> = None;
+    for block in std::mem::take(&mut doc.description) {
+        match block {
+            Block::Directive { name, content, .. } if name == "seealso" => {
+                rubric = None;
+                push_see_also_blocks(doc, content);
+            }
+            Block::Directive { name, argument, .. } if name == "rubric" => {
+                let kind = SectionKind::from_title(&argument)
+                    .filter(|k| matches!(k, SectionKind::Notes | SectionKind::Examples));
+                if kind.is_none() {
+                    doc.sections
+                        .push(DocSection { title: argument.trim().to_string(), content: Vec::new() });
+                }
+                rubric = Some(kind);
+            }
+            block => match rubric {
+                None => doc.description.push(block),
+                Some(Some(SectionKind::Notes)) => doc.notes.push(block),
+                Some(Some(_)) => doc.examples.push(block),
+                Some(None) => doc.sections.last_mut().unwrap().content.push(block),
+            },
+        }
+    }
+}
+
+/// Read `.. seealso::` content: each paragraph opening with an inline literal name starts an entry,
+/// optionally followed by `: description`; other blocks continue the entry before them.
+fn push_see_also_blocks(doc: &mut Docstring, content: Vec<Block<'static>>) {
+    for block in content {
+        match block {
+            Block::Paragraph(mut inlines) if matches!(inlines.first(), Some(Inline::Code(_))) => {
+                let Inline::Code(name) = inlines.remove(0) else { unreachable!() };
+                if let Some(Inline::Text(text)) = inlines.first_mut() {
+                    let rest = text.strip_prefix(':').unwrap_or(text).trim_start().to_string();
+                    *text = rest.into();
+                    if text.is_empty() {
+                        inlines.remove(0);
+                    }
+                }
+                let description = if inlines.is_empty() { Vec::new() } else { vec![Block::Paragraph(inlines)] };
+                doc.see_also.push(SeeAlso { name: name.into_owned(), description });
+            }
+            block => match doc.see_also.last_mut() {
+                Some(entry) => entry.description.push(block),
+                None => doc.description.push(block),
+            },
+        }
+    }
+}
+
+pub(super) fn parse_fields(lines: &[&str], doc: &mut Docstring) {
+    let mut i = 0;
+    while i < lines.len() {
+        let Some((spec, text)) = field_at(lines[i]) else {
+            let end = (i + 1..lines.len())
+                .find(|&j| field_at(lines[j]).is_some())
+                .unwrap_or(lines.len());
+            doc.description.extend(parse_body(&lines[i..end].join("\n")));
+            i = end;
+            continue;
+        };
+        let end = (i + 1..lines.len())
+            .find(|&j| !lines[j].trim().is_empty() && leading_indent(lines[j]) == 0)
+            .unwrap_or(lines.len());
+        let rest = dedent(&lines[i + 1..end]);
+        let gap = lines.get(i + 1).is_some_and(|l| l.trim().is_empty());
+        let body = match (text.is_empty(), rest.is_empty()) {
+            (_, true) => text.to_string(),
+            (true, false) => rest,
+            (false, false) if gap => format!("{text}\n\n{rest}"),
+            (false, false) => format!("{text}\n{rest}"),
+        };
+        apply_field(doc, spec, &body);
+        i = end;
+    }
+}
+
+fn apply_field(doc: &mut Docstring, spec: &str, body: &str) {
+    let mut words = spec.split_whitespace();
+    let name = words.next().unwrap_or_default();
+    let args: Vec<&str> = words.collect();
+    match name {
+        "param" | "parameter" | "arg" | "argument" | "key" | "keyword" => {
+            describe_param(&mut doc.params, &args, body);
+        }
+        "var" | "ivar" | "cvar" => describe_param(&mut doc.attributes, &args, body),
+        "type" => apply_type_spec(entry(&mut doc.params, &args.join(" ")), body),
+        "vartype" => apply_type_spec(entry(&mut doc.attributes, &args.join(" ")), body),
+        "returns" | "return" => describe_values(&mut doc.returns, body),
+        "rtype" => return_value(&mut doc.returns).type_name = Some(body.trim().to_string()),
+        "yields" | "yield" => describe_values(&mut doc.yields, body),
+        "ytype" => return_value(&mut doc.yields).type_name = Some(body.trim().to_string()),
+        "raises" | "raise" | "except" | "exception" => {
+            doc.raises
+                .push(DocRaise { type_name: args.join(" "), description: parse_body(body) });
+        }
+        "warns" | "warn" => doc
+            .warns
+            .push(DocRaise { type_name: args.join(" "), description: parse_body(body) }),
+        "meta" => {}
+        _ => doc
+            .sections
+            .push(DocSection { title: spec.to_string(), content: parse_body(body) }),
+    }
+}
+
+/// Record `:param [type] name: description`, merging with an earlier `:type name:` field.
+fn describe_param(params: &mut Vec<DocParam>, args: &[&str], body: &str) {
+    let Some((name, type_words)) = args.split_last() else {
+        return;
+    };
+    let built = build_param(name, None, body);
+    let param = entry(params, name);
+    if !type_words.is_empty() {
+        apply_type_spec(param, &type_words.join(" "));
+    }
+    if param.default.is_none() {
+        param.default = default_from_description(body);
+    }
+    param.description = built.description;
+}
+
+/// The parameter called `name`, added to `params` if it is not there yet.
+fn entry<'a>(params: &'a mut Vec<DocParam>, name: &str) -> &'a mut DocParam {
+    match params.iter().position(|p| p.name == name) {
+        Some(idx) => &mut params[idx],
+        None => {
+            params.push(DocParam { name: name.to_string(), ..DocParam::default() });
+            params.last_mut().unwrap()
+        }
+    }
+}
+
+/// Record a `:returns:` or `:yields:` body, which lists several values as Napoleon's
+/// `* **name** (*type*) -- description` items and otherwise describes a single value.
+fn describe_values(values: &mut Vec<DocReturn>, body: &str) {
+    match value_items(body) {
+        Some(items) => values.extend(items),
+        None => return_value(values).description = parse_body(body),
+    }
+}
+
+/// The values of a body made only of `* **name** (*type*) -- description` items.
+fn value_items(body: &str) -> Option<Vec<DocReturn>> {
+    let mut items: Vec<Vec<&str>> = Vec::new();
+    for line in body.lines() {
+        match line.strip_prefix("* ") {
+            Some(head) => items.push(vec![head]),
+            None => items.last_mut()?.push(line),
+        }
+    }
+    if items.is_empty() {
+        return None;
+    }
+    items.iter().map(|lines| value_item(lines[0], &lines[1..])).collect()
+}
+
+fn value_item(head: &str, rest: &[&str]) -> Option<DocReturn> {
+    let (name, after) = match head.strip_prefix("**").and_then(|h| h.split_once("**")) {
+        Some((name, after)) => (Some(name.to_string()), after.trim_start()),
+        None => (None, head),
+    };
+    let typed = match name {
+        Some(_) => after.strip_prefix("(*").and_then(|a| a.split_once("*)")),
+        None => after.strip_prefix('*').and_then(|a| a.split_once('*')),
+    };
+    let (type_name, after) = match typed {
+        Some((ty, after)) => (Some(ty.to_string()), after.trim_start()),
+        None => (None, after),
+    };
+    if name.is_none() && type_name.is_none() {
+        return None;
+    }
+    let desc = match after.strip_prefix("--") {
+        Some(desc) => desc.trim(),
+        None if after.is_empty() => "",
+        None => return None,
+    };
+    let more = dedent(rest);
+    let text = match (more.is_empty(), rest.first().is_some_and(|l| l.trim().is_empty())) {
+        (true, _) => desc.to_string(),
+        (false, true) => format!("{desc}\n\n{more}"),
+        (false, false) => format!("{desc}\n{more}"),
+    };
+    Some(DocReturn { name, type_name, description: parse_body(&text) })
+}
+
+fn return_value(values: &mut Vec<DocReturn>) -> &mut DocReturn {
+    if values.is_empty() {
+        values.push(DocReturn::default());
+    }
+    &mut values[0]
+}
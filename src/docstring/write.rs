@@ -0,0 +1,607 @@
+//! Writers that emit a [`Docstring`] in Google, NumPy or Sphinx style.
+
+use super::{DocParam, DocRaise, DocReturn, Docstring, DocstringStyle};
+use crate::rst::{fill, indent, inline_words, write_blocks};
+use crate::{Block, Inline};
+
+/// Layout options for [`write_docstring`].
+#[derive(Debug, Clone)]
+pub struct WriteOptions {
+    width: usize,
+    indent: usize,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self { width: 72, indent: 4 }
+    }
+}
+
+impl WriteOptions {
+    /// Defaults to a line width of 72 and an indentation step of 4 spaces.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum line width that prose is refilled to.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Number of spaces section bodies and entry descriptions are indented by.
+    pub fn indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+}
+
+/// Write `doc` as docstring text in `style`.
+///
+/// Sections are emitted in a fixed order: parameters, attributes, returns, yields, raises, warns,
+/// other sections, see also, notes and examples. [`DocstringStyle::Plain`] writes Google style
+/// sections for whatever the docstring contains beyond its summary and description.
+///
+/// ```
+/// use parserst::{DocstringStyle, parse_docstring, write_docstring};
+/// use parserst::docstring::WriteOptions;
+///
+/// let doc = parse_docstring(":param x: The value.\n:type x: int\n");
+/// let numpy = write_docstring(&doc, DocstringStyle::Numpy, &WriteOptions::new());
+/// assert_eq!(numpy, "Parameters\n----------\nx : int\n    The value.\n");
+/// ```
+pub fn write_docstring(doc: &Docstring, style: DocstringStyle, opts: &WriteOptions) -> String {
+    let mut w = Writer { opts, parts: Vec::new() };
+    if !doc.summary.is_empty() {
+        w.prose(&doc.summary, 0);
+    }
+    match style {
+        DocstringStyle::Numpy => {
+            w.deprecation(doc);
+            w.blocks(&doc.description, 0);
+            w.numpy(doc);
+        }
+        DocstringStyle::Sphinx => {
+            w.deprecation(doc);
+            w.blocks(&doc.description, 0);
+            w.sphinx(doc);
+        }
+        DocstringStyle::Google | DocstringStyle::Plain => {
+            w.deprecation(doc);
+            w.blocks(&doc.description, 0);
+            w.google(doc);
+        }
+    }
+    let mut out = w.parts.join("\n\n");
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+struct Writer<'a> {
+    opts: &'a WriteOptions,
+    /// Paragraph-level chunks, joined with blank lines.
+    parts: Vec<String>,
+}
+
+impl Writer<'_> {
+    /// Write blocks as reST, keeping doctest examples as bare `>>>` lines.
+    fn write(&self, blocks: &[Block], width: Option<usize>) -> String {
+        blocks
+            .iter()
+            .map(|block| match block {
+                Block::LiteralBlock(text) if text.starts_with(">>>") => text.to_string(),
+                other => write_blocks(std::slice::from_ref(other), width),
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    fn width_at(&self, indent: usize) -> Option<usize> {
+        Some(self.opts.width.saturating_sub(indent).max(20))
+    }
+
+    fn prose(&mut self, text: &str, at: usize) {
+        let words: Vec<String> = text.split_whitespace().map(str::to_string).collect();
+        let lines = fill(&words, self.width_at(at), "", "").join("\n");
+        self.parts.push(indent(&lines, at));
+    }
+
+    fn blocks(&mut self, blocks: &[Block], at: usize) {
+        if !blocks.is_empty() {
+            self.parts.push(indent(&self.write(blocks, self.width_at(at)), at));
+        }
+    }
+
+    fn deprecation(&mut self, doc: &Docstring) {
+        let Some(dep) = &doc.deprecation else {
+            return;
+        };
+        let directive = Block::Directive {
+            name: "deprecated".into(),
+            argument: dep.version.clone().unwrap_or_default(),
+            content: dep.description.clone(),
+        };
+        self.blocks(&[directive], 0);
+    }
+
+    /// `head` followed by `description`, whose first paragraph continues on the head line when
+    /// `inline` is set, with the rest indented under the head.
+    fn entry(&self, head: &str, description: &[Block], at: usize, inline: bool) -> String {
+        if inline {
+            return self.hanging_entry(head, description, at, self.opts.indent);
+        }
+        let mut out = head.to_string();
+        if !description.is_empty() {
+            let step = self.opts.indent;
+            out.push('\n');
+            out.push_str(&indent(&self.write(description, self.width_at(at + step)), step));
+        }
+        indent(&out, at)
+    }
+
+    /// `head` with the first paragraph of `description` continuing on its line, and everything
+    /// after it indented by `step` under the head.
+    fn hanging_entry(&self, head: &str, description: &[Block], at: usize, step: usize) -> String {
+        let mut out = match description.first() {
+            Some(Block::Paragraph(inlines)) => {
+                let mut words = vec![head.to_string()];
+                words.extend(inline_words(inlines).into_iter().filter(|w| w != "\n"));
+                fill(&words, self.width_at(at), "", &" ".repeat(step)).join("\n")
+            }
+            _ => head.to_string(),
+        };
+        let rest = if matches!(description.first(), Some(Block::Paragraph(_))) {
+            &description[1..]
+        } else {
+            description
+        };
+        if !rest.is_empty() {
+            let body = indent(&self.write(rest, self.width_at(at + step)), step);
+            out.push_str("\n\n");
+            out.push_str(&body);
+        }
+        indent(&out, at)
+    }
+
+    fn google(&mut self, doc: &Docstring) {
+        let step = self.opts.indent;
+        let section = |title: &str, entries: Vec<String>| format!("{title}:\n{}", entries.join("\n"));
+
+        if !doc.params.is_empty() {
+            let entries = doc.params.iter().map(|p| self.google_param(p)).collect();
+            self.parts.push(section("Args", entries));
+        }
+        if !doc.attributes.is_empty() {
+            let entries = doc.attributes.iter().map(|p| self.google_param(p)).collect();
+            self.parts.push(section("Attributes", entries));
+        }
+        for (title, values) in [("Returns", &doc.returns), ("Yields", &doc.yields)] {
+            if !values.is_empty() {
+                // A lone untyped value is plain prose; several values each need a label to stay apart.
+                let entries = values
+                    .iter()
+                    .map(|v| match (&v.name, &v.type_name) {
+                        (None, None) if values.len() == 1 => {
+                            indent(&self.write(&v.description, self.width_at(step)), step)
+                        }
+                        (None, ty) => {
+                            let ty = ty.as_deref().unwrap_or("object");
+                            self.entry(&format!("{ty}:"), &v.description, step, true)
+                        }
+                        (Some(name), ty) => {
+                            let ty = ty.as_deref().unwrap_or("object");
+                            self.entry(&format!("{name} ({ty}):"), &v.description, step, true)
+                        }
+                    })
+                    .collect();
+                self.parts.push(section(title, entries));
+            }
+        }
+        for (title, values) in [("Raises", &doc.raises), ("Warns", &doc.warns)] {
+            if !values.is_empty() {
+                let entries = values
+                    .iter()
+                    .map(|r| self.entry(&format!("{}:", r.type_name), &r.description, step, true))
+                    .collect();
+                self.parts.push(section(title, entries));
+            }
+        }
+        for extra in &doc.sections {
+            let body = indent(&self.write(&extra.content, self.width_at(step)), step);
+            self.parts.push(format!("{}:\n{body}", extra.title));
+        }
+        if !doc.see_also.is_empty() {
+            let entries = doc
+                .see_also
+                .iter()
+                .map(|s| {
+                    let head = if s.description.is_empty() { s.name.clone() } else { format!("{}:", s.name) };
+                    self.entry(&head, &s.description, step, true)
+                })
+                .collect();
+            self.parts.push(section("See Also", entries));
+        }
+        for (title, blocks) in [("Notes", &doc.notes), ("Examples", &doc.examples)] {
+            if !blocks.is_empty() {
+                let body = indent(&self.write(blocks, self.width_at(step)), step);
+                self.parts.push(format!("{title}:\n{body}"));
+            }
+        }
+    }
+
+    fn google_param(&self, param: &DocParam) -> String {
+        let mut spec = param.type_name.clone().into_iter().collect::<Vec<_>>();
+        if param.optional {
+            spec.push("optional".into());
+        }
+        let head = if spec.is_empty() {
+            format!("{}:", param.name)
+        } else {
+            format!("{} ({}):", param.name, spec.join(", "))
+        };
+        self.entry(&head, &with_default_sentence(param), self.opts.indent, true)
+    }
+
+    fn numpy(&mut self, doc: &Docstring) {
+        let section = |title: &str, body: String| format!("{title}\n{}\n{body}", "-".repeat(title.len()));
+
+        for (title, params) in [("Parameters", &doc.params), ("Attributes", &doc.attributes)] {
+            if !params.is_empty() {
+                let entries: Vec<String> = params.iter().map(|p| self.numpy_param(p)).collect();
+                self.parts.push(section(title, entries.join("\n")));
+            }
+        }
+        for (title, values) in [("Returns", &doc.returns), ("Yields", &doc.yields)] {
+            if !values.is_empty() {
+                let entries: Vec<String> = values.iter().map(|v| self.numpy_return(v)).collect();
+                self.parts.push(section(title, entries.join("\n")));
+            }
+        }
+        for (title, values) in [("Raises", &doc.raises), ("Warns", &doc.warns)] {
+            if !values.is_empty() {
+                let entries: Vec<String> = values.iter().map(|r| self.numpy_raise(r)).collect();
+                self.parts.push(section(title, entries.join("\n")));
+            }
+        }
+        for extra in &doc.sections {
+            self.parts
+                .push(section(&extra.title, self.write(&extra.content, self.width_at(0))));
+        }
+        if !doc.see_also.is_empty() {
+            let entries: Vec<String> = doc
+                .see_also
+                .iter()
+                .map(|s| {
+                    let head = if s.description.is_empty() { s.name.clone() } else { format!("{} :", s.name) };
+                    self.entry(&head, &s.description, 0, true)
+                })
+                .collect();
+            self.parts.push(section("See Also", entries.join("\n")));
+        }
+        for (title, blocks) in [("Notes", &doc.notes), ("Examples", &doc.examples)] {
+            if !blocks.is_empty() {
+                self.parts.push(section(title, self.write(blocks, self.width_at(0))));
+            }
+        }
+    }
+
+    fn numpy_param(&self, param: &DocParam) -> String {
+        let mut spec = param.type_name.clone().into_iter().collect::<Vec<_>>();
+        if param.optional {
+            spec.push("optional".into());
+        }
+        if let Some(default) = &param.default
+            && !mentions_default(&param.description)
+        {
+            spec.push(format!("default {default}"));
+        }
+        let head = if spec.is_empty() { param.name.clone() } else { format!("{} : {}", param.name, spec.join(", ")) };
+        self.entry(&head, &param.description, 0, false)
+    }
+
+    fn numpy_return(&self, value: &DocReturn) -> String {
+        let ty = value.type_name.as_deref().unwrap_or("object");
+        let head = match &value.name {
+            Some(name) => format!("{name} : {ty}"),
+            None => ty.to_string(),
+        };
+        self.entry(&head, &value.description, 0, false)
+    }
+
+    fn numpy_raise(&self, raise: &DocRaise) -> String {
+        self.entry(&raise.type_name, &raise.description, 0, false)
+    }
+
+    fn sphinx(&mut self, doc: &Docstring) {
+        for extra in &doc.sections {
+            self.rubric(&extra.title, &extra.content);
+        }
+        if !doc.see_also.is_empty() {
+            let directive = Block::Directive {
+                name: "seealso".into(),
+                argument: String::new(),
+                content: doc
+                    .see_also
+                    .iter()
+                    .flat_map(|s| {
+                        let mut inlines = vec![Inline::Code(s.name.clone().into())];
+                        let rest = match s.description.split_first() {
+                            Some((Block::Paragraph(desc), rest)) => {
+                                inlines.push(Inline::Text(": ".into()));
+                                inlines.extend(desc.iter().cloned());
+                                rest
+                            }
+                            _ => &s.description[..],
+                        };
+                        std::iter::once(Block::Paragraph(inlines)).chain(rest.iter().cloned())
+                    })
+                    .collect(),
+            };
+            self.blocks(&[directive], 0);
+        }
+        self.rubric("Notes", &doc.notes);
+        self.rubric("Examples", &doc.examples);
+
+        let mut fields = Vec::new();
+        for (field, type_field, params) in [("param", "type", &doc.params), ("ivar", "vartype", &doc.attributes)] {
+            for p in params {
+                fields.push(self.entry(&format!(":{field} {}:", p.name), &with_default_sentence(p), 0, true));
+                let mut spec = p.type_name.clone().into_iter().collect::<Vec<_>>();
+                if p.optional {
+                    spec.push("optional".into());
+                }
+                if !spec.is_empty() {
+                    fields.push(format!(":{type_field} {}: {}", p.name, spec.join(", ")));
+                }
+            }
+        }
+        for (field, type_field, values) in [("returns", "rtype", &doc.returns), ("yields", "ytype", &doc.yields)] {
+            match values.as_slice() {
+                [] => {}
+                [value] if value.name.is_none() => {
+                    if !value.description.is_empty() {
+                        fields.push(self.entry(&format!(":{field}:"), &value.description, 0, true));
+                    }
+                    if let Some(ty) = &value.type_name {
+                        fields.push(format!(":{type_field}: {ty}"));
+                    }
+                }
+                _ => {
+                    let items: Vec<String> = values.iter().map(|v| self.sphinx_value(v)).collect();
+                    fields.push(format!(":{field}:\n{}", items.join("\n")));
+                }
+            }
+        }
+        for (field, values) in [("raises", &doc.raises), ("warns", &doc.warns)] {
+            for r in values {
+                fields.push(self.entry(&format!(":{field} {}:", r.type_name), &r.description, 0, true));
+            }
+        }
+        if !fields.is_empty() {
+            self.parts.push(fields.join("\n"));
+        }
+    }
+
+    /// One value of a `:returns:` or `:yields:` field listing several, as an indented
+    /// `* **name** (*type*) -- description` item in the form Napoleon writes.
+    fn sphinx_value(&self, value: &DocReturn) -> String {
+        let ty = value.type_name.as_deref().unwrap_or("object");
+        let mut head = match &value.name {
+            Some(name) => format!("* **{name}** (*{ty}*)"),
+            None => format!("* *{ty}*"),
+        };
+        if !value.description.is_empty() {
+            head.push_str(" --");
+        }
+        self.hanging_entry(&head, &value.description, self.opts.indent, 2)
+    }
+
+    fn rubric(&mut self, title: &str, content: &[Block]) {
+        if content.is_empty() {
+            return;
+        }
+        let mut blocks = vec![Block::Directive { name: "rubric".into(), argument: title.into(), content: Vec::new() }];
+        blocks.extend(content.iter().cloned());
+        self.blocks(&blocks, 0);
+    }
+}
+
+/// Whether a description already states its default with a `Defaults to` sentence.
+fn mentions_default(description: &[Block]) -> bool {
+    write_blocks(description, None).contains("Defaults to ")
+}
+
+/// The parameter's description, with a `Defaults to X.` sentence appended when the default is not
+/// already mentioned.
+fn with_default_sentence(param: &DocParam) -> Vec<Block<'static>> {
+    let mut description = param.description.clone();
+    let Some(default) = &param.default else {
+        return description;
+    };
+    if mentions_default(&description) {
+        return description;
+    }
+    let sentence = Inline::Text(format!("Defaults to {default}.").into());
+    match description.last_mut() {
+        Some(Block::Paragraph(inlines)) => {
+            inlines.push(Inline::Text(" ".into()));
+            inlines.push(sentence);
+        }
+        _ => description.push(Block::Paragraph(vec![sentence])),
+    }
+    description
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_docstring;
+
+    const GOOGLE: &str = "Fetch rows from a table.
+
+Longer explanation of the lookup.
+
+Args:
+    table (str): Name of the table.
+    keys (list[str], optional): Keys to fetch. Defaults to None.
+
+Returns:
+    dict[str, int]: Mapping of keys to rows.
+
+Raises:
+    IOError: If the table is unreachable.
+
+Examples:
+    >>> fetch(\"users\")
+    {}
+";
+
+    const NUMPY: &str = "Compute the mean.
+
+.. deprecated:: 1.6.0
+
+   Use ``average`` instead.
+
+Parameters
+----------
+x : array_like
+    Input values.
+axis : int, optional, default 0
+    Axis to reduce.
+
+Returns
+-------
+mean : float
+    The mean.
+
+See Also
+--------
+average :
+    Weighted mean.
+
+Notes
+-----
+Uses pairwise summation.
+";
+
+    const SPHINX: &str = "Open a file.
+
+:param path: Where to look.
+:type path: str
+:param mode: Access mode. Defaults to 'r'.
+:type mode: str, optional
+:returns: The handle.
+:rtype: io.TextIOWrapper
+:raises OSError: When the file is missing.
+";
+
+    #[test]
+    fn round_trips_keep_each_style_unchanged() {
+        for (input, style) in [(GOOGLE, DocstringStyle::Google), (SPHINX, DocstringStyle::Sphinx)] {
+            let doc = parse_docstring(input);
+            assert_eq!(doc.style, style);
+            assert_eq!(write_docstring(&doc, style, &WriteOptions::new()), input);
+        }
+        let doc = parse_docstring(NUMPY);
+        let written = write_docstring(&doc, DocstringStyle::Numpy, &WriteOptions::new());
+        assert_eq!(parse_docstring(&written), doc);
+    }
+
+    const RICH: &str = "Summarize rows.
+
+Longer explanation.
+
+Args:
+    rows (list[Row]): Rows to scan.
+
+        Empty lists are fine.
+    limit (int, optional): Maximum rows. Defaults to 10.
+
+Attributes:
+    cache (dict): Seen rows.
+
+Returns:
+    total (int): Sum of the values.
+    count (int): Number of rows.
+
+Yields:
+    int: Running totals.
+
+Raises:
+    ValueError: If a row is malformed.
+
+Warns:
+    UserWarning: When rows repeat.
+
+Todo:
+    Handle streaming input.
+
+See Also:
+    average: Weighted mean.
+    median: Middle value.
+
+Notes:
+    Runs in linear time.
+
+Examples:
+    >>> summarize([])
+    (0, 0)
+";
+
+    #[test]
+    fn every_style_reads_back_what_it_writes() {
+        let original = parse_docstring(RICH);
+        assert_eq!(original.returns.len(), 2);
+        for style in [DocstringStyle::Google, DocstringStyle::Numpy, DocstringStyle::Sphinx] {
+            let written = write_docstring(&original, style, &WriteOptions::new());
+            let reparsed = parse_docstring(&written);
+            assert_eq!(
+                reparsed,
+                Docstring { style, ..original.clone() },
+                "{style:?}:\n{written}"
+            );
+        }
+    }
+
+    #[test]
+    fn sphinx_lists_every_return_value() {
+        let doc = parse_docstring(RICH);
+        let written = write_docstring(&doc, DocstringStyle::Sphinx, &WriteOptions::new());
+        assert!(written.contains(
+            ":returns:\n    * **total** (*int*) -- Sum of the values.\n    * **count** (*int*) -- Number of rows.\n"
+        ));
+        assert!(written.contains(".. seealso::\n\n   ``average``: Weighted mean.\n\n   ``median``: Middle value."));
+        assert!(written.contains(".. rubric:: Notes\n\nRuns in linear time."));
+    }
+
+    #[test]
+    fn converts_between_styles_without_losing_meaning() {
+        let original = parse_docstring(GOOGLE);
+        for style in [DocstringStyle::Numpy, DocstringStyle::Sphinx] {
+            let converted = parse_docstring(&write_docstring(&original, style, &WriteOptions::new()));
+            assert_eq!(converted.style, style);
+            assert_eq!(converted.summary, original.summary);
+            assert_eq!(converted.params, original.params);
+            assert_eq!(converted.returns[0].type_name, original.returns[0].type_name);
+            assert_eq!(converted.raises, original.raises);
+        }
+    }
+
+    #[test]
+    fn refills_prose_to_the_configured_width() {
+        let doc = parse_docstring(
+            "Summary.\n\nArgs:\n    value (int): A fairly long description that will not fit on a single narrow line.\n",
+        );
+        let out = write_docstring(&doc, DocstringStyle::Google, &WriteOptions::new().width(40).indent(2));
+        assert_eq!(
+            out,
+            "Summary.\n\nArgs:\n  value (int): A fairly long description\n    that will not fit on a single narrow\n    line.\n"
+        );
+        let reparsed = parse_docstring(&out);
+        let unwrapped = |p: &DocParam| write_blocks(&p.description, Some(usize::MAX));
+        assert_eq!(reparsed.params[0].type_name, doc.params[0].type_name);
+        assert_eq!(unwrapped(&reparsed.params[0]), unwrapped(&doc.params[0]));
+    }
+}
@@ -0,0 +1,343 @@
+//! Incremental re-parsing for editors.
+
+use std::ops::Range;
+
+use crate::ast::{assign_heading_ids, into_owned, resolve_py_names};
+use crate::{Block, Lines, ParseError, ParserOptions, apply_conditionals, parse_next};
+
+/// A parsed document that can be edited in place, re-parsing only the top-level blocks an edit
+/// affects.
+///
+/// The top-level parse is a series of steps, each starting where the last one stopped and
+/// producing the next block (occasionally several, like the entries of a definition list). A step
+/// reads only its own lines and the ones after them, so after an edit the steps before it are
+/// kept. Parsing resumes one step before the edited line, since that step may have looked ahead
+/// into it, and goes on until it stops at a line where an old step past the edit started. From
+/// there on the old steps are kept too, with their spans shifted. Python names, heading ids and
+/// conditional directives are then resolved over the whole document again, which is a walk over
+/// the tree rather than a parse, so [`blocks`](Self::blocks) is always what
+/// [`parse_with`](crate::parse_with) returns for the current text.
+///
+/// Files pulled in through a [`FileResolver`](crate::FileResolver) are read again only when the
+/// blocks that include them are re-parsed.
+///
+/// ```
+/// use parserst::{Document, parse};
+///
+/// let mut doc = Document::parse("Title\n=====\n\nFirst *one*.\n\nSecond.\n").unwrap();
+/// let reparsed = doc.edit(20..23, "two").unwrap();
+/// assert_eq!(doc.text(), "Title\n=====\n\nFirst *two*.\n\nSecond.\n");
+/// assert_eq!(doc.blocks(), parse(doc.text()).unwrap());
+/// assert_eq!(reparsed, 0..26);
+/// assert_eq!(doc.spans().map(|(span, _)| span).collect::<Vec<_>>(), [0..11, 13..25, 27..34]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Document {
+    text: String,
+    opts: ParserOptions,
+    steps: Vec<Step>,
+    /// The blocks of every step in order, with Python names and heading ids resolved.
+    parsed: Vec<Block<'static>>,
+    /// `parsed` with conditional directives applied, when the options set tags.
+    filtered: Option<Vec<Block<'static>>>,
+}
+
+/// One step of the top-level parse.
+#[derive(Debug, Clone)]
+struct Step {
+    /// The lines the step consumed, including blank lines before its block.
+    lines: Range<usize>,
+    /// Byte range of the step's text, from its first to its last non-blank line.
+    span: Range<usize>,
+    /// How many blocks it produced.
+    blocks: usize,
+}
+
+impl Document {
+    /// Parse `text` with the default [`ParserOptions`], like [`parse`](crate::parse).
+    pub fn parse(text: impl Into<String>) -> Result<Self, ParseError> {
+        Self::parse_with(text, &ParserOptions::default())
+    }
+
+    /// Parse `text` with `opts`, like [`parse_with`](crate::parse_with).
+    pub fn parse_with(text: impl Into<String>, opts: &ParserOptions) -> Result<Self, ParseError> {
+        let text = text.into();
+        let starts = line_starts(&text);
+        let mut lines = Lines::new(&text);
+        let mut steps = Vec::new();
+        let mut parsed = Vec::new();
+        while !lines.is_eof() {
+            let start = lines.position();
+            let blocks = into_owned(parse_next(&mut lines, opts)?);
+            steps.push(Step::new(&text, &starts, start..lines.position(), blocks.len()));
+            parsed.extend(blocks);
+        }
+        let mut doc = Self { text, opts: opts.clone(), steps, parsed, filtered: None };
+        doc.resolve();
+        Ok(doc)
+    }
+
+    /// The current source text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The document's blocks, as [`parse_with`](crate::parse_with) returns them for [`text`](Self::text).
+    pub fn blocks(&self) -> &[Block<'static>] {
+        self.filtered.as_deref().unwrap_or(&self.parsed)
+    }
+
+    /// The byte range of each top-level block in [`text`](Self::text), from its first to its last
+    /// non-blank line, with the blocks parsed from it. These are the blocks before conditional
+    /// directives are applied; a range holds more than one block where a single construct (such
+    /// as a definition list) produces several.
+    pub fn spans(&self) -> impl Iterator<Item = (Range<usize>, &[Block<'static>])> {
+        let mut rest = self.parsed.as_slice();
+        self.steps.iter().filter_map(move |step| {
+            let (blocks, after) = rest.split_at(step.blocks);
+            rest = after;
+            (!blocks.is_empty()).then(|| (step.span.clone(), blocks))
+        })
+    }
+
+    /// Replace `range` of the text with `replacement` and re-parse the blocks it affects, returning
+    /// the byte range of the new text that was parsed again.
+    ///
+    /// On error the document keeps its previous text and blocks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds or doesn't fall on `char` boundaries, like
+    /// [`String::replace_range`].
+    pub fn edit(&mut self, range: Range<usize>, replacement: &str) -> Result<Range<usize>, ParseError> {
+        let mut text = self.text.clone();
+        text.replace_range(range.clone(), replacement);
+        let old_starts = line_starts(&self.text);
+        let starts = line_starts(&text);
+        let first = line_of(&old_starts, range.start);
+        let last = line_of(&old_starts, range.end);
+        let line_delta = starts.len() as isize - old_starts.len() as isize;
+        let byte_delta = replacement.len() as isize - range.len() as isize;
+
+        let edited = self
+            .steps
+            .partition_point(|step| step.lines.start <= first)
+            .saturating_sub(1);
+        let from = edited.saturating_sub(1);
+        let mut lines = Lines::new(&text);
+        lines.seek(self.steps.get(from).map_or(0, |step| step.lines.start));
+
+        let mut steps = Vec::new();
+        let mut blocks = Vec::new();
+        let mut resume = self.steps.len();
+        while !lines.is_eof() {
+            let start = lines.position();
+            let old_start = start as isize - line_delta;
+            if old_start > last as isize
+                && let Ok(i) = self
+                    .steps
+                    .binary_search_by_key(&(old_start as usize), |step| step.lines.start)
+            {
+                resume = i;
+                break;
+            }
+            let parsed = into_owned(parse_next(&mut lines, &self.opts)?);
+            steps.push(Step::new(&text, &starts, start..lines.position(), parsed.len()));
+            blocks.extend(parsed);
+        }
+
+        let reparsed = starts
+            .get(self.steps.get(from).map_or(0, |step| step.lines.start))
+            .map_or(text.len(), |&start| start)
+            ..starts.get(lines.position()).map_or(text.len(), |&end| end);
+        let count = |steps: &[Step]| steps.iter().map(|step| step.blocks).sum::<usize>();
+        let removed = count(&self.steps[..from])..count(&self.steps[..resume]);
+        self.parsed.splice(removed, blocks);
+        let kept: Vec<Step> = self.steps[resume..]
+            .iter()
+            .map(|step| Step {
+                lines: shift(&step.lines, line_delta),
+                span: shift(&step.span, byte_delta),
+                blocks: step.blocks,
+            })
+            .collect();
+        self.steps.truncate(from);
+        self.steps.extend(steps);
+        self.steps.extend(kept);
+        self.text = text;
+        self.resolve();
+        Ok(reparsed)
+    }
+
+    /// Resolve Python names and heading ids across the document and apply conditional directives,
+    /// as [`parse_with`](crate::parse_with) does after parsing.
+    fn resolve(&mut self) {
+        resolve_py_names(&mut self.parsed);
+        assign_heading_ids(&mut self.parsed);
+        self.filtered = self
+            .opts
+            .active_tags()
+            .map(|tags| apply_conditionals(self.parsed.clone(), tags));
+    }
+}
+
+impl Step {
+    fn new(text: &str, starts: &[usize], lines: Range<usize>, blocks: usize) -> Self {
+        let line = |i: usize| text[starts[i]..].lines().next().unwrap_or_default();
+        let mut content = lines.clone().filter(|&i| !line(i).trim().is_empty());
+        let span = match (content.next(), content.next_back()) {
+            (Some(first), last) => {
+                let last = last.unwrap_or(first);
+                starts[first]..starts[last] + line(last).len()
+            }
+            (None, _) => {
+                let at = starts.get(lines.start).map_or(text.len(), |&start| start);
+                at..at
+            }
+        };
+        Self { lines, span, blocks }
+    }
+}
+
+/// Byte offset of the start of each line, as [`str::lines`] splits them.
+fn line_starts(text: &str) -> Vec<usize> {
+    let mut starts = Vec::new();
+    let mut at = 0;
+    for line in text.split_inclusive('\n') {
+        starts.push(at);
+        at += line.len();
+    }
+    starts
+}
+
+/// Index of the line holding byte `offset`.
+fn line_of(starts: &[usize], offset: usize) -> usize {
+    starts.partition_point(|&start| start <= offset).saturating_sub(1)
+}
+
+fn shift(range: &Range<usize>, delta: isize) -> Range<usize> {
+    range.start.saturating_add_signed(delta)..range.end.saturating_add_signed(delta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Tags, parse_with};
+
+    const DOC: &str = "\
+Title
+=====
+
+.. _usage:
+
+Usage
+-----
+
+Some *text* that
+runs on.
+
+- one
+- two
+  continued
+
+term
+    Definition.
+
+:param x: The value.
+
+.. note:: Careful.
+
+   Body.
+
+Example::
+
+    code
+
+===== =====
+A     B
+===== =====
+x     y
+===== =====
+
+.. py:module:: pkg
+
+.. py:function:: run()
+
+End.
+";
+
+    /// Apply the edit to a document and check it against parsing the new text from scratch.
+    fn check(text: &str, range: Range<usize>, replacement: &str, opts: &ParserOptions) {
+        let mut doc = Document::parse_with(text, opts).unwrap();
+        let mut new_text = text.to_string();
+        new_text.replace_range(range.clone(), replacement);
+        let context = format!("{range:?} -> {replacement:?}");
+        match Document::parse_with(new_text.as_str(), opts) {
+            Ok(fresh) => {
+                doc.edit(range, replacement).unwrap();
+                assert_eq!(doc.text(), new_text, "{context}");
+                assert_eq!(doc.blocks(), parse_with(&new_text, opts).unwrap(), "{context}");
+                assert!(doc.spans().eq(fresh.spans()), "{context}");
+            }
+            Err(_) => {
+                assert!(doc.edit(range, replacement).is_err(), "{context}");
+                assert_eq!(doc.text(), text);
+            }
+        }
+    }
+
+    #[test]
+    fn edits_anywhere_match_a_full_parse() {
+        let opts = ParserOptions::default();
+        let boundaries: Vec<usize> = (0..=DOC.len()).filter(|&i| DOC.is_char_boundary(i)).collect();
+        for &at in &boundaries {
+            for replacement in ["", "x", "\n", "\n\n", "  ", "- ", "::", "=====\n", ".. _x:\n\n", "`"] {
+                check(DOC, at..at, replacement, &opts);
+            }
+            for len in [1, 2, 7] {
+                if let Some(&end) = boundaries.iter().find(|&&end| end >= at + len) {
+                    check(DOC, at..end, "", &opts);
+                    check(DOC, at..end, "y\n", &opts);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reparses_only_around_the_edit() {
+        let mut doc = Document::parse(DOC).unwrap();
+        let at = DOC.find("Careful").unwrap();
+        let reparsed = doc.edit(at..at + 7, "Mind this").unwrap();
+        assert_eq!(
+            &doc.text()[reparsed],
+            "\n:param x: The value.\n\n.. note:: Mind this.\n\n   Body.\n"
+        );
+        let (span, blocks) = doc.spans().last().unwrap();
+        assert_eq!(&doc.text()[span], "End.");
+        assert_eq!(blocks, [Block::Paragraph(vec![crate::Inline::Text("End.".into())])]);
+    }
+
+    #[test]
+    fn heading_ids_and_conditionals_follow_edits() {
+        let opts = ParserOptions::default().tags(Tags::from_iter(["html"]));
+        let text = "Usage\n=====\n\n.. only:: html\n\n   Shown.\n\nText.\n";
+        let mut doc = Document::parse_with(text, &opts).unwrap();
+        doc.edit(text.len()..text.len(), "\n.. _usage:\n\nMore.\n").unwrap();
+        assert!(matches!(&doc.blocks()[0], Block::Heading { id, .. } if id == "usage-1"));
+        assert_eq!(doc.blocks(), parse_with(doc.text(), &opts).unwrap());
+        assert!(matches!(doc.blocks()[1], Block::Paragraph(_)));
+    }
+
+    #[test]
+    fn failed_edits_leave_the_document_alone() {
+        let opts = ParserOptions::new().file_resolver(|path: &str| match path {
+            "ok.html" => Ok("<hr>".to_string()),
+            _ => Err(std::io::Error::new(std::io::ErrorKind::NotFound, "missing")),
+        });
+        let text = "Intro.\n\n.. raw:: html\n   :file: ok.html\n";
+        let mut doc = Document::parse_with(text, &opts).unwrap();
+        assert!(matches!(doc.edit(32..34, "no"), Err(ParseError::File { line: 3, .. })));
+        assert_eq!(doc.text(), text);
+        assert_eq!(doc.blocks(), parse_with(text, &opts).unwrap());
+    }
+}
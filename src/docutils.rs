@@ -0,0 +1,1026 @@
+//! Docutils document trees, as XML and pseudo-XML.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+use crate::ast::{anchor_id, make_id, parse_inlines};
+use crate::html::ADMONITIONS;
+use crate::render::{RenderOptions, prepare};
+use crate::rst::write_blocks;
+use crate::text::Style;
+use crate::textutil::{display_width, place_cells};
+use crate::visit::{Visitor, walk_block};
+use crate::{Block, Field, Inline, ListKind, OptionItem, ParserOptions, PyObject, PyObjectKind, TableCell};
+
+/// Fields that the docinfo transform turns into elements of their own.
+const BIBLIOGRAPHIC: &[&str] = &[
+    "author",
+    "authors",
+    "organization",
+    "address",
+    "contact",
+    "version",
+    "revision",
+    "status",
+    "date",
+    "copyright",
+];
+
+/// Labels given to `[*]_` footnotes, in order; docutils doubles them up once they run out.
+const SYMBOLS: &[&str] = &["*", "†", "‡", "§", "¶", "#", "♠", "♥", "♦", "♣"];
+
+/// Elements whose content is inline: XML output never breaks lines inside them.
+const TEXT_ELEMENTS: &[&str] = &[
+    "title",
+    "subtitle",
+    "paragraph",
+    "literal_block",
+    "comment",
+    "raw",
+    "rubric",
+    "field_name",
+    "label",
+    "option_string",
+    "option_argument",
+    "desc_signature",
+    "target",
+    "emphasis",
+    "strong",
+    "literal",
+    "reference",
+    "footnote_reference",
+];
+
+/// Renders a document as the element tree docutils builds for it, in the Docutils Generic DTD's
+/// XML ([`render_xml`](Self::render_xml), like `rst2xml`) or as indented pseudo-XML
+/// ([`render_pseudo_xml`](Self::render_pseudo_xml), like `rst2pseudoxml`), so output can be diffed
+/// against golden files produced by docutils.
+///
+/// Headings become nested `section`s, and docutils' standard transforms are applied: a lone
+/// top-level section is promoted to the document title (and a lone subsection to its subtitle), a
+/// field list directly after them becomes `docinfo`, targets pass their ids to the element that
+/// follows, and footnotes are numbered and linked to their references.
+///
+/// The tree is only as detailed as ours: the bullet character and enumeration style of lists
+/// aren't recorded, so lists are always `-` and arabic, and table column widths are measured from
+/// the cell content rather than the source.
+///
+/// ```
+/// use parserst::{DocutilsRenderer, parse};
+///
+/// let blocks = parse("Some *emphasis*.").unwrap();
+/// assert_eq!(
+///     DocutilsRenderer::new().render_pseudo_xml(&blocks),
+///     "<document source=\"<string>\">\n    <paragraph>\n        Some \n        <emphasis>\n            emphasis\n        .\n"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct DocutilsRenderer {
+    source: String,
+    doctitle: bool,
+    docinfo: bool,
+    indent: bool,
+    options: RenderOptions,
+}
+
+impl Default for DocutilsRenderer {
+    fn default() -> Self {
+        Self {
+            source: "<string>".to_string(),
+            doctitle: true,
+            docinfo: true,
+            indent: false,
+            options: RenderOptions::default(),
+        }
+    }
+}
+
+impl DocutilsRenderer {
+    /// Defaults to docutils' own settings: title promotion and docinfo on, XML on a single line.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The document's `source` attribute, normally the input file's path; `<string>` by default.
+    pub fn source(mut self, source: impl Into<String>) -> Self {
+        self.source = source.into();
+        self
+    }
+
+    /// Promote a lone top-level section to the document title and subtitle (docutils'
+    /// `doctitle_xform` setting).
+    pub fn doctitle(mut self, doctitle: bool) -> Self {
+        self.doctitle = doctitle;
+        self
+    }
+
+    /// Turn a field list at the start of the document into `docinfo` (docutils'
+    /// `docinfo_xform` setting).
+    pub fn docinfo(mut self, docinfo: bool) -> Self {
+        self.docinfo = docinfo;
+        self
+    }
+
+    /// Put each element of the XML output on its own line, indented by nesting depth, except
+    /// inside elements holding inline content (docutils' `indents` setting).
+    pub fn indent(mut self, indent: bool) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Apply `options` (raw content handling, conditional directives) when rendering a document.
+    pub fn options(mut self, options: RenderOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Render a document as Docutils Generic DTD XML, with the XML declaration and doctype.
+    pub fn render_xml(&self, blocks: &[Block]) -> String {
+        let mut out = String::from(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+             <!DOCTYPE document PUBLIC \"+//IDN docutils.sourceforge.net//DTD Docutils Generic//EN//XML\" \
+             \"http://docutils.sourceforge.net/docs/ref/docutils.dtd\">\n",
+        );
+        let indent = if self.indent { Some(0) } else { None };
+        self.document(blocks).write_xml(&mut out, indent, false);
+        if !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Render a document as pseudo-XML: one element or line of text per line, indented four
+    /// spaces per level, with nothing escaped.
+    pub fn render_pseudo_xml(&self, blocks: &[Block]) -> String {
+        let mut out = String::new();
+        self.document(blocks).write_pseudo_xml(&mut out, 0);
+        out
+    }
+
+    /// The `document` element for `blocks`, with the transforms applied.
+    fn document(&self, blocks: &[Block]) -> Element {
+        let blocks = prepare(blocks, &self.options);
+        let out = Out::new(&blocks);
+        let mut children = out.sections(&blocks);
+        out.finish(&mut children);
+        link_footnotes(&mut children);
+
+        let mut document = Element::new("document").attr("source", &self.source);
+        if self.doctitle
+            && let Some(section) = lone_section(&mut children)
+        {
+            document.attrs.extend(section.ids_and_names());
+            let mut rest = section.children.into_iter();
+            let title = rest.next().expect("sections start with their title");
+            document = document.attr("title", &title.text());
+            children.insert(0, title);
+            children.extend(rest);
+            if let Some(sub) = lone_section(&mut children) {
+                let mut rest = sub.children.into_iter();
+                let Some(Node::Element(title)) = rest.next() else {
+                    unreachable!("sections start with their title")
+                };
+                let mut subtitle = Element::new("subtitle");
+                subtitle.attrs = sub.attrs;
+                subtitle.children = title.children;
+                children.insert(1, subtitle.into());
+                children.extend(rest);
+            }
+        }
+        if self.docinfo {
+            docinfo(&mut children);
+        }
+        document.children = children;
+        document
+    }
+}
+
+/// The section that is the last of `nodes` and follows nothing but comments and the title,
+/// removed from them.
+fn lone_section(nodes: &mut Vec<Node>) -> Option<Element> {
+    let candidate = nodes.iter().position(|node| !node.is("comment") && !node.is("title"))?;
+    if candidate + 1 != nodes.len() || !nodes[candidate].is("section") {
+        return None;
+    }
+    match nodes.pop() {
+        Some(Node::Element(section)) => Some(section),
+        _ => None,
+    }
+}
+
+/// Replace a field list right after the title and subtitle with `docinfo`, turning bibliographic
+/// fields that hold a single paragraph into their own elements.
+fn docinfo(children: &mut [Node]) {
+    let Some(node) = children
+        .iter_mut()
+        .find(|node| !node.is("title") && !node.is("subtitle") && !node.is("comment"))
+    else {
+        return;
+    };
+    let Node::Element(list) = node else { return };
+    if list.name != "field_list" {
+        return;
+    }
+    list.name = "docinfo";
+    for field in list.children.iter_mut() {
+        let Node::Element(field) = field else { continue };
+        let [Node::Element(name), Node::Element(body)] = field.children.as_mut_slice() else { continue };
+        let key = name.text().to_lowercase();
+        match body.children.as_mut_slice() {
+            [Node::Element(paragraph)] if paragraph.name == "paragraph" && known(BIBLIOGRAPHIC, &key).is_some() => {
+                let mut element = Element::new(known(BIBLIOGRAPHIC, &key).unwrap_or_default());
+                element.children = std::mem::take(&mut paragraph.children);
+                *field = element;
+            }
+            _ => field.attrs.push(("classes", make_id(&key))),
+        }
+    }
+}
+
+/// The entry of `names` equal to `name`, as an element name.
+fn known(names: &[&'static str], name: &str) -> Option<&'static str> {
+    names.iter().copied().find(|known| *known == name)
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Element(Element),
+    Text(String),
+}
+
+#[derive(Debug, Clone)]
+struct Element {
+    name: &'static str,
+    attrs: Vec<(&'static str, String)>,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn is(&self, name: &str) -> bool {
+        matches!(self, Node::Element(element) if element.name == name)
+    }
+
+    fn text(&self) -> String {
+        match self {
+            Node::Element(element) => element.text(),
+            Node::Text(text) => text.clone(),
+        }
+    }
+}
+
+impl Element {
+    fn new(name: &'static str) -> Self {
+        Self { name, attrs: Vec::new(), children: Vec::new() }
+    }
+
+    /// Add an attribute, unless `value` is empty (docutils leaves out empty list attributes).
+    fn attr(mut self, name: &'static str, value: &str) -> Self {
+        if !value.is_empty() {
+            self.attrs.push((name, value.to_string()));
+        }
+        self
+    }
+
+    fn child(mut self, child: impl Into<Node>) -> Self {
+        self.children.push(child.into());
+        self
+    }
+
+    fn children(mut self, children: impl IntoIterator<Item = Node>) -> Self {
+        self.children.extend(children);
+        self
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.attrs.iter().find(|(n, _)| *n == name).map(|(_, v)| v.as_str())
+    }
+
+    /// The `ids` and `names` attributes, for moving them to another element.
+    fn ids_and_names(&self) -> Vec<(&'static str, String)> {
+        self.attrs
+            .iter()
+            .filter(|(name, _)| matches!(*name, "ids" | "names"))
+            .cloned()
+            .collect()
+    }
+
+    fn text(&self) -> String {
+        self.children.iter().map(Node::text).collect()
+    }
+
+    /// The attributes in docutils' order (sorted by name), with values quoted by `quote`.
+    fn start_tag(&self, quote: fn(&str) -> String) -> String {
+        let mut attrs: Vec<&(&str, String)> = self.attrs.iter().collect();
+        attrs.sort_by_key(|(name, _)| *name);
+        let mut tag = format!("<{}", self.name);
+        for (name, value) in attrs {
+            tag.push_str(&format!(" {name}={}", quote(value)));
+        }
+        tag
+    }
+
+    fn write_pseudo_xml(&self, out: &mut String, level: usize) {
+        out.push_str(&"    ".repeat(level));
+        out.push_str(&self.start_tag(|value| format!("\"{value}\"")));
+        out.push_str(">\n");
+        for child in &self.children {
+            match child {
+                Node::Element(element) => element.write_pseudo_xml(out, level + 1),
+                Node::Text(text) => {
+                    for line in text.lines() {
+                        out.push_str(&"    ".repeat(level + 1));
+                        out.push_str(line);
+                        out.push('\n');
+                    }
+                }
+            }
+        }
+    }
+
+    /// Write the element as XML; `indent` is the nesting depth when each element goes on its own line.
+    fn write_xml(&self, out: &mut String, indent: Option<usize>, inline: bool) {
+        let inline = inline || TEXT_ELEMENTS.contains(&self.name);
+        let tag = self.start_tag(xml_attribute);
+        if self.children.is_empty() {
+            out.push_str(&tag);
+            out.push_str("/>");
+            return;
+        }
+        out.push_str(&tag);
+        out.push('>');
+        for child in &self.children {
+            if let (Some(depth), false) = (indent, inline) {
+                out.push('\n');
+                out.push_str(&"    ".repeat(depth + 1));
+            }
+            match child {
+                Node::Element(element) => element.write_xml(out, indent.map(|depth| depth + 1), inline),
+                Node::Text(text) => out.push_str(&xml_escape(text)),
+            }
+        }
+        if let (Some(depth), false) = (indent, inline) {
+            out.push('\n');
+            out.push_str(&"    ".repeat(depth));
+        }
+        out.push_str(&format!("</{}>", self.name));
+    }
+}
+
+impl From<Element> for Node {
+    fn from(element: Element) -> Self {
+        Node::Element(element)
+    }
+}
+
+impl From<&str> for Node {
+    fn from(text: &str) -> Self {
+        Node::Text(text.to_string())
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Quote an attribute value the way Python's `xml.sax.saxutils.quoteattr` does: in double quotes
+/// unless it contains them and no single quotes.
+fn xml_attribute(value: &str) -> String {
+    let value = xml_escape(value)
+        .replace('\n', "&#10;")
+        .replace('\r', "&#13;")
+        .replace('\t', "&#9;");
+    if !value.contains('"') {
+        format!("\"{value}\"")
+    } else if !value.contains('\'') {
+        format!("'{value}'")
+    } else {
+        format!("\"{}\"", value.replace('"', "&quot;"))
+    }
+}
+
+/// A list attribute value: entries separated by spaces, with spaces and backslashes inside
+/// entries escaped.
+fn serial(values: &[String]) -> String {
+    let escaped: Vec<String> = values
+        .iter()
+        .map(|value| value.replace('\\', "\\\\").replace(' ', "\\ "))
+        .collect();
+    escaped.join(" ")
+}
+
+/// A reference name as docutils normalizes it: lowercase, with runs of whitespace made single spaces.
+fn normalize_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Footnote definitions in document order.
+#[derive(Default)]
+struct Footnotes(Vec<String>);
+
+impl Visitor for Footnotes {
+    fn visit_block(&mut self, block: &Block) {
+        if let Block::Footnote { label, .. } = block {
+            self.0.push(label.clone());
+        }
+        walk_block(self, block);
+    }
+}
+
+/// How a footnote is identified and labelled once numbered.
+struct FootnoteInfo {
+    label: String,
+    id: String,
+    name: String,
+    auto: &'static str,
+}
+
+/// Building state: footnote numbering, the targets waiting for an element to take their ids, and
+/// the current `highlight` language.
+struct Out {
+    footnotes: Vec<(String, FootnoteInfo)>,
+    /// How many `[#]_` and `[*]_` references have taken the next footnote with that label.
+    taken: [Cell<usize>; 2],
+    /// Footnotes built so far, so each definition finds its numbering.
+    defined: Cell<usize>,
+    references: Cell<usize>,
+    /// Ids and names of targets not yet passed on, and of targets that found no element to take them.
+    pending: RefCell<Vec<(String, String)>>,
+    orphans: RefCell<HashMap<String, String>>,
+}
+
+impl Out {
+    fn new(blocks: &[Block]) -> Self {
+        let mut labels = Footnotes::default();
+        labels.visit_blocks(blocks);
+        let manual: Vec<usize> = labels.0.iter().filter_map(|label| label.parse().ok()).collect();
+        let mut number = 0;
+        let mut auto_ids = 0;
+        let mut symbols = 0;
+        let footnotes = labels
+            .0
+            .into_iter()
+            .map(|label| {
+                let (number_label, name, auto) = match label.as_str() {
+                    "*" => {
+                        let symbol = SYMBOLS[symbols % SYMBOLS.len()].repeat(symbols / SYMBOLS.len() + 1);
+                        symbols += 1;
+                        (symbol, String::new(), "*")
+                    }
+                    _ if label.starts_with('#') => {
+                        number += 1;
+                        while manual.contains(&number) {
+                            number += 1;
+                        }
+                        (number.to_string(), normalize_name(&label[1..]), "1")
+                    }
+                    _ => (label.clone(), normalize_name(&label), ""),
+                };
+                let id = match make_id(&name) {
+                    id if id.is_empty() => {
+                        auto_ids += 1;
+                        format!("footnote-{auto_ids}")
+                    }
+                    id => id,
+                };
+                (label, FootnoteInfo { label: number_label, id, name, auto })
+            })
+            .collect();
+        Self {
+            footnotes,
+            taken: Default::default(),
+            defined: Cell::new(0),
+            references: Cell::new(0),
+            pending: RefCell::new(Vec::new()),
+            orphans: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Start a block-level element with its own `ids` and `names`, which also takes those of any
+    /// targets directly before it.
+    fn start(&self, name: &'static str, mut ids: Vec<String>, mut names: Vec<String>) -> Element {
+        for (id, name) in self.pending.borrow_mut().drain(..) {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        Element::new(name)
+            .attr("ids", &serial(&ids))
+            .attr("names", &serial(&names))
+    }
+
+    /// Targets directly before an element that can't take their ids keep them.
+    fn keep_pending(&self) {
+        self.orphans.borrow_mut().extend(self.pending.borrow_mut().drain(..));
+    }
+
+    /// Give targets that found no element their own ids and names back.
+    fn finish(&self, nodes: &mut [Node]) {
+        self.keep_pending();
+        let orphans = self.orphans.borrow();
+        if orphans.is_empty() {
+            return;
+        }
+        fn fix(nodes: &mut [Node], orphans: &HashMap<String, String>) {
+            for node in nodes {
+                let Node::Element(element) = node else { continue };
+                if element.name == "target"
+                    && let Some(id) = element.get("refid").map(str::to_string)
+                    && let Some(name) = orphans.get(&id)
+                {
+                    element.attrs = vec![("ids", serial(&[id])), ("names", serial(std::slice::from_ref(name)))];
+                }
+                fix(&mut element.children, orphans);
+            }
+        }
+        fix(nodes, &orphans);
+    }
+
+    /// Nest the blocks after each heading, up to the next heading at its level or above, in a section.
+    fn sections(&self, blocks: &[Block]) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        let mut i = 0;
+        while i < blocks.len() {
+            let Block::Heading { level, inlines, id } = &blocks[i] else {
+                nodes.extend(self.block(&blocks[i]));
+                i += 1;
+                continue;
+            };
+            let end = blocks[i + 1..]
+                .iter()
+                .position(|block| matches!(block, Block::Heading { level: next, .. } if next <= level))
+                .map_or(blocks.len(), |n| i + 1 + n);
+            let title = self.inlines(inlines);
+            let text: String = title.iter().map(Node::text).collect();
+            let mut ids = vec![id.clone()];
+            if self.pending.borrow().iter().any(|(pending, _)| pending == id) {
+                ids[0] = make_id(&text);
+            }
+            ids.retain(|id| !id.is_empty());
+            let section = self
+                .start("section", ids, vec![normalize_name(&text)])
+                .child(Element::new("title").children(title))
+                .children(self.sections(&blocks[i + 1..end]));
+            nodes.push(section.into());
+            i = end;
+        }
+        nodes
+    }
+
+    fn blocks(&self, blocks: &[Block]) -> Vec<Node> {
+        blocks.iter().flat_map(|block| self.block(block)).collect()
+    }
+
+    fn block(&self, block: &Block) -> Vec<Node> {
+        let element = match block {
+            Block::Heading { .. } => return self.sections(std::slice::from_ref(block)),
+            Block::Paragraph(inlines) => self.start("paragraph", vec![], vec![]).children(self.inlines(inlines)),
+            Block::List { kind, items } => {
+                let list = match kind {
+                    ListKind::Unordered => self.start("bullet_list", vec![], vec![]).attr("bullet", "-"),
+                    ListKind::Ordered => self
+                        .start("enumerated_list", vec![], vec![])
+                        .attr("enumtype", "arabic")
+                        .attr("suffix", "."),
+                };
+                list.children(items.iter().map(|item| {
+                    let item_element = Element::new("list_item");
+                    if item.is_empty() {
+                        item_element.into()
+                    } else {
+                        item_element
+                            .child(Element::new("paragraph").children(self.inlines(item)))
+                            .into()
+                    }
+                }))
+            }
+            Block::CodeBlock(code) | Block::LiteralBlock(code) => self.literal_block(code, ""),
+            Block::Quote(children) => self
+                .start("block_quote", vec![], vec![])
+                .children(self.blocks(children)),
+            Block::Directive { name, argument, content } => return self.directive(name, argument, content),
+            Block::Table { headers, rows, widths } => self.table(headers, rows, widths),
+            Block::Comment(children) => {
+                self.keep_pending();
+                Element::new("comment").attr("xml:space", "preserve").children(
+                    Some(write_blocks(children, None))
+                        .filter(|text| !text.is_empty())
+                        .map(Node::Text),
+                )
+            }
+            Block::FieldList { fields } => self
+                .start("field_list", vec![], vec![])
+                .children(fields.iter().map(|field| self.field(field).into())),
+            Block::OptionList { items } => self
+                .start("option_list", vec![], vec![])
+                .children(items.iter().map(|item| self.option_item(item).into())),
+            Block::Raw { formats, content } => self
+                .start("raw", vec![], vec![])
+                .attr("format", &formats.join(" "))
+                .attr("xml:space", "preserve")
+                .child(content.as_str()),
+            Block::Target { name } => {
+                let id = make_id(name);
+                self.pending.borrow_mut().push((id.clone(), normalize_name(name)));
+                Element::new("target").attr("refid", &id)
+            }
+            Block::PyObject(obj) => return self.py_object(obj),
+            Block::Footnote { content, .. } => {
+                self.keep_pending();
+                let index = self.defined.get();
+                self.defined.set(index + 1);
+                let info = &self.footnotes[index].1;
+                Element::new("footnote")
+                    .attr("auto", info.auto)
+                    .attr("ids", &info.id)
+                    .attr("names", &serial(std::slice::from_ref(&info.name)))
+                    .child(Element::new("label").child(info.label.as_str()))
+                    .children(self.blocks(content))
+            }
+        };
+        vec![element.into()]
+    }
+
+    fn literal_block(&self, code: &str, classes: &str) -> Element {
+        self.start("literal_block", vec![], vec![])
+            .attr("classes", classes)
+            .attr("xml:space", "preserve")
+            .children(Some(code).filter(|code| !code.is_empty()).map(Node::from))
+    }
+
+    fn directive(&self, name: &str, argument: &str, content: &[Block]) -> Vec<Node> {
+        let argument_inlines = || self.inlines(&parse_inlines(argument, &ParserOptions::default()));
+        if let Some(kind) = known(ADMONITIONS, name) {
+            let argument = Some(argument)
+                .filter(|argument| !argument.is_empty())
+                .map(|_| Element::new("paragraph").children(argument_inlines()).into());
+            let element = self
+                .start(kind, vec![], vec![])
+                .children(argument)
+                .children(self.blocks(content));
+            return vec![element.into()];
+        }
+        let element = match name {
+            "admonition" | "topic" | "sidebar" => {
+                let kind = known(&["admonition", "topic", "sidebar"], name).unwrap_or_default();
+                let classes =
+                    if kind == "admonition" { format!("admonition-{}", make_id(argument)) } else { String::new() };
+                self.start(kind, vec![], vec![])
+                    .attr("classes", &classes)
+                    .child(Element::new("title").children(argument_inlines()))
+                    .children(self.blocks(content))
+            }
+            "rubric" => self.start("rubric", vec![], vec![]).children(argument_inlines()),
+            "code-block" | "code" => {
+                let code: Vec<&str> = content
+                    .iter()
+                    .filter_map(|block| match block {
+                        Block::LiteralBlock(text) => Some(text.as_ref()),
+                        _ => None,
+                    })
+                    .collect();
+                let classes = if argument.is_empty() { String::new() } else { format!("code {argument}") };
+                self.literal_block(&code.join("\n\n"), &classes)
+            }
+            "highlight" => return Vec::new(),
+            "only" | "ifconfig" => return self.blocks(content),
+            "image" => self.start("image", vec![], vec![]).attr("uri", argument),
+            "container" => self
+                .start("container", vec![], vec![])
+                .attr("classes", argument)
+                .children(self.blocks(content)),
+            _ => self
+                .start("container", vec![], vec![])
+                .attr("classes", name)
+                .children(self.blocks(content)),
+        };
+        vec![element.into()]
+    }
+
+    fn field(&self, field: &Field) -> Element {
+        let name = if field.argument.is_empty() {
+            field.name.clone()
+        } else {
+            format!("{} {}", field.name, field.argument)
+        };
+        Element::new("field")
+            .child(Element::new("field_name").child(name.as_str()))
+            .child(Element::new("field_body").children(self.blocks(&field.body)))
+    }
+
+    fn option_item(&self, item: &OptionItem) -> Element {
+        let options = item.options.iter().map(|option| {
+            let split = if option.starts_with("--") { option.find([' ', '=']) } else { option.find(' ') };
+            let element = Element::new("option");
+            match split {
+                Some(at) => element.child(Element::new("option_string").child(&option[..at])).child(
+                    Element::new("option_argument")
+                        .attr("delimiter", &option[at..at + 1])
+                        .child(&option[at + 1..]),
+                ),
+                None => element.child(Element::new("option_string").child(option.as_str())),
+            }
+            .into()
+        });
+        Element::new("option_list_item")
+            .child(Element::new("option_group").children(options))
+            .child(Element::new("description").children(self.blocks(&item.body)))
+    }
+
+    /// Sphinx's `desc` nodes, with the signature as plain text.
+    fn py_object(&self, obj: &PyObject) -> Vec<Node> {
+        match obj.kind {
+            PyObjectKind::CurrentModule => Vec::new(),
+            PyObjectKind::Module => {
+                let target = self.start("target", vec![anchor_id(obj)], vec![]).attr("ismod", "True");
+                std::iter::once(target.into())
+                    .chain(self.blocks(&obj.content))
+                    .collect()
+            }
+            kind => {
+                let signature = Element::new("desc_signature")
+                    .attr("fullname", &obj.fullname)
+                    .attr("ids", &anchor_id(obj))
+                    .child(obj.signature.to_string().as_str());
+                let desc = self
+                    .start("desc", vec![], vec![])
+                    .attr("classes", &format!("py {}", kind.name()))
+                    .attr("desctype", kind.name())
+                    .attr("domain", "py")
+                    .attr("objtype", kind.name())
+                    .child(signature)
+                    .child(Element::new("desc_content").children(self.blocks(&obj.content)));
+                vec![desc.into()]
+            }
+        }
+    }
+
+    /// A table with one `colspec` per grid column, as wide as the column in the source. Tables
+    /// without source widths size each column by its widest single-column cell.
+    fn table(&self, headers: &[Vec<TableCell>], rows: &[Vec<TableCell>], source: &[usize]) -> Element {
+        let all: Vec<&Vec<TableCell>> = headers.iter().chain(rows).collect();
+        let (slots, columns) = place_cells(&all, |blocks| Style::default().blocks(blocks, usize::MAX));
+        let mut widths = vec![1; columns];
+        if source.len() == columns {
+            widths = source.to_vec();
+        } else {
+            for slot in slots.iter().filter(|slot| slot.colspan == 1) {
+                let width = slot.lines.iter().map(|line| display_width(line)).max().unwrap_or(0);
+                widths[slot.col] = widths[slot.col].max(width);
+            }
+        }
+
+        let row = |cells: &Vec<TableCell>| -> Node {
+            Element::new("row")
+                .children(cells.iter().map(|cell| {
+                    let more = |span: usize| if span > 1 { (span - 1).to_string() } else { String::new() };
+                    Element::new("entry")
+                        .attr("morecols", &more(cell.colspan))
+                        .attr("morerows", &more(cell.rowspan))
+                        .children(self.blocks(&cell.content))
+                        .into()
+                }))
+                .into()
+        };
+        let mut group = Element::new("tgroup").attr("cols", &columns.to_string()).children(
+            widths
+                .iter()
+                .map(|width| Element::new("colspec").attr("colwidth", &width.to_string()).into()),
+        );
+        if !headers.is_empty() {
+            group = group.child(Element::new("thead").children(headers.iter().map(&row)));
+        }
+        let group = group.child(Element::new("tbody").children(rows.iter().map(&row)));
+        self.start("table", vec![], vec![]).child(group)
+    }
+
+    fn inlines(&self, inlines: &[Inline]) -> Vec<Node> {
+        let mut nodes: Vec<Node> = Vec::new();
+        for inline in inlines {
+            for node in self.inline(inline) {
+                match (nodes.last_mut(), node) {
+                    (Some(Node::Text(last)), Node::Text(text)) => last.push_str(&text),
+                    (_, node) => nodes.push(node),
+                }
+            }
+        }
+        nodes
+    }
+
+    fn inline(&self, inline: &Inline) -> Vec<Node> {
+        let element = match inline {
+            Inline::Text(text) => return vec![text.as_ref().into()],
+            Inline::Em(children) => Element::new("emphasis").children(self.inlines(children)),
+            Inline::Strong(children) => Element::new("strong").children(self.inlines(children)),
+            Inline::Code(code) => Element::new("literal").child(code.as_ref()),
+            Inline::Link { text, url } => {
+                let text = self.inlines(text);
+                let name: String = text.iter().map(Node::text).collect();
+                if let Some(id) = url.strip_prefix('#') {
+                    let reference = Element::new("reference")
+                        .attr("name", &name)
+                        .attr("refid", &make_id(id));
+                    return vec![reference.children(text).into()];
+                }
+                if name == *url {
+                    return vec![Element::new("reference").attr("refuri", url).children(text).into()];
+                }
+                let reference = Element::new("reference")
+                    .attr("name", &name)
+                    .attr("refuri", url)
+                    .children(text);
+                let target = Element::new("target")
+                    .attr("ids", &make_id(&name))
+                    .attr("names", &serial(&[normalize_name(&name)]))
+                    .attr("refuri", url);
+                return vec![reference.into(), target.into()];
+            }
+            Inline::FootnoteRef(label) => {
+                let index = self.references.get() + 1;
+                self.references.set(index);
+                let id = format!("footnote-reference-{index}");
+                match self.footnote(label) {
+                    Some(info) => Element::new("footnote_reference")
+                        .attr("auto", info.auto)
+                        .attr("ids", &id)
+                        .attr("refid", &info.id)
+                        .child(info.label.as_str()),
+                    None => Element::new("footnote_reference")
+                        .attr("ids", &id)
+                        .attr("refname", &normalize_name(label))
+                        .child(label.as_str()),
+                }
+            }
+        };
+        vec![element.into()]
+    }
+
+    /// The footnote a reference to `label` points at; `#` and `*` take the next unused one in order.
+    fn footnote(&self, label: &str) -> Option<&FootnoteInfo> {
+        let mut matching = self.footnotes.iter().filter(|(l, _)| l == label);
+        let found = match label {
+            "#" | "*" => {
+                let taken = &self.taken[usize::from(label == "*")];
+                taken.set(taken.get() + 1);
+                matching.nth(taken.get() - 1)
+            }
+            _ => matching.next(),
+        };
+        found.map(|(_, info)| info)
+    }
+}
+
+/// Point each footnote back at the references to it, as docutils' `backrefs` attribute.
+fn link_footnotes(nodes: &mut [Node]) {
+    fn references(nodes: &[Node], found: &mut HashMap<String, Vec<String>>) {
+        for node in nodes {
+            let Node::Element(element) = node else { continue };
+            if element.name == "footnote_reference"
+                && let (Some(refid), Some(id)) = (element.get("refid"), element.get("ids"))
+            {
+                found.entry(refid.to_string()).or_default().push(id.to_string());
+            }
+            references(&element.children, found);
+        }
+    }
+    fn backrefs(nodes: &mut [Node], found: &HashMap<String, Vec<String>>) {
+        for node in nodes {
+            let Node::Element(element) = node else { continue };
+            if element.name == "footnote"
+                && let Some(refs) = element.get("ids").and_then(|id| found.get(id))
+            {
+                element.attrs.push(("backrefs", serial(refs)));
+            }
+            backrefs(&mut element.children, found);
+        }
+    }
+    let mut found = HashMap::new();
+    references(nodes, &mut found);
+    backrefs(nodes, &found);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn pseudo_xml(doc: &str) -> String {
+        DocutilsRenderer::new().render_pseudo_xml(&parse(doc).unwrap())
+    }
+
+    #[test]
+    fn sections_nest_and_the_lone_top_section_is_the_title() {
+        let doc = "Title\n=====\n\nIntro\n-----\n\nText.\n\nMore\n----\n";
+        assert_eq!(
+            pseudo_xml(doc),
+            "\
+<document ids=\"title\" names=\"title\" source=\"<string>\" title=\"Title\">
+    <title>
+        Title
+    <section ids=\"intro\" names=\"intro\">
+        <title>
+            Intro
+        <paragraph>
+            Text.
+    <section ids=\"more\" names=\"more\">
+        <title>
+            More
+"
+        );
+        let blocks = parse(doc).unwrap();
+        let out = DocutilsRenderer::new().doctitle(false).render_pseudo_xml(&blocks);
+        assert!(out.starts_with("<document source=\"<string>\">\n    <section ids=\"title\" names=\"title\">\n"));
+    }
+
+    #[test]
+    fn subtitle_and_docinfo_follow_the_title() {
+        let doc = "Tool\n====\n\nDoes things\n-----------\n\n:Version: 1.0\n:Manual section: 1\n";
+        assert_eq!(
+            pseudo_xml(doc),
+            "\
+<document ids=\"tool\" names=\"tool\" source=\"<string>\" title=\"Tool\">
+    <title>
+        Tool
+    <subtitle ids=\"does-things\" names=\"does\\ things\">
+        Does things
+    <docinfo>
+        <version>
+            1.0
+        <field classes=\"manual-section\">
+            <field_name>
+                Manual section
+            <field_body>
+                <paragraph>
+                    1
+"
+        );
+    }
+
+    #[test]
+    fn targets_pass_their_ids_to_the_next_element() {
+        let doc = "Text.\n\n.. _first:\n.. _second:\n\n- item\n\n.. _last:\n";
+        assert_eq!(
+            pseudo_xml(doc),
+            "\
+<document source=\"<string>\">
+    <paragraph>
+        Text.
+    <target refid=\"first\">
+    <target refid=\"second\">
+    <bullet_list bullet=\"-\" ids=\"first second\" names=\"first second\">
+        <list_item>
+            <paragraph>
+                item
+    <target ids=\"last\" names=\"last\">
+"
+        );
+    }
+
+    #[test]
+    fn footnotes_are_numbered_and_linked() {
+        let doc = "See [#]_ and [1]_.\n\n.. [1] Manual.\n.. [#] Auto.\n";
+        let out = pseudo_xml(doc);
+        assert!(out.contains(
+            "<footnote_reference auto=\"1\" ids=\"footnote-reference-1\" refid=\"footnote-2\">\n            2\n"
+        ));
+        assert!(out.contains("<footnote backrefs=\"footnote-reference-2\" ids=\"footnote-1\" names=\"1\">\n        <label>\n            1\n"));
+        assert!(out.contains("<footnote auto=\"1\" backrefs=\"footnote-reference-1\" ids=\"footnote-2\">\n        <label>\n            2\n"));
+    }
+
+    #[test]
+    fn colspecs_take_column_widths_from_the_source() {
+        let grid = pseudo_xml("+---+-------+\n| a | b     |\n+---+-------+\n");
+        assert!(grid.contains("<colspec colwidth=\"3\">\n            <colspec colwidth=\"7\">\n"));
+        let simple = pseudo_xml("=====  ===\na      b\n=====  ===\n");
+        assert!(simple.contains("<colspec colwidth=\"5\">\n            <colspec colwidth=\"3\">\n"));
+    }
+
+    #[test]
+    fn option_lists_and_tables_use_docutils_elements() {
+        let doc = "-o FILE, --all  Do it.\n\n====== =\nA      B\n====== =\nlonger\n====== =\n";
+        let out = pseudo_xml(doc);
+        assert!(out.contains(
+            "<option>\n                    <option_string>\n                        -o\n                    \
+             <option_argument delimiter=\" \">\n                        FILE\n"
+        ));
+        assert!(out.contains("<tgroup cols=\"2\">\n            <colspec colwidth=\"6\">\n            <colspec colwidth=\"1\">\n            <thead>\n"));
+    }
+
+    #[test]
+    fn xml_is_escaped_and_optionally_indented() {
+        let blocks = parse("A & *b*\n\n::\n\n   <tag>\n").unwrap();
+        let xml = DocutilsRenderer::new().source("in.rst").render_xml(&blocks);
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<!DOCTYPE document PUBLIC"));
+        assert!(xml.ends_with(
+            "<document source=\"in.rst\"><paragraph>A &amp; <emphasis>b</emphasis></paragraph>\
+             <literal_block xml:space=\"preserve\">&lt;tag&gt;</literal_block></document>\n"
+        ));
+        let indented = DocutilsRenderer::new().indent(true).render_xml(&blocks);
+        assert!(indented.ends_with(
+            "<document source=\"&lt;string&gt;\">\n    <paragraph>A &amp; <emphasis>b</emphasis></paragraph>\n    \
+             <literal_block xml:space=\"preserve\">&lt;tag&gt;</literal_block>\n</document>\n"
+        ));
+    }
+}
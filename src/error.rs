@@ -6,4 +6,11 @@ pub enum ParseError {
     Eof,
     #[error("invalid syntax at line {line}: {msg}")]
     Invalid { line: usize, msg: String },
+    #[error("cannot read `{path}` referenced at line {line}: {source}")]
+    File {
+        line: usize,
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
 }
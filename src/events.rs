@@ -0,0 +1,633 @@
+//! Pull parsing: a document as a stream of [`Event`]s instead of a tree.
+//!
+//! [`Parser`] parses one top-level block at a time and yields its events, so only the events of
+//! the block being parsed are held at once. Events can be filtered or rewritten on the way
+//! through, and the tree rebuilt from them with [`Blocks`] or [`from_events`], or rendered
+//! directly with [`HtmlRenderer::render_events`](crate::HtmlRenderer::render_events) and
+//! [`TextRenderer::render_events`](crate::TextRenderer::render_events).
+//!
+//! ```
+//! use parserst::events::{Event, Parser, from_events};
+//! use parserst::{Inline, parse};
+//!
+//! let doc = "Some *emphasis* and ``code``.";
+//! let events: Vec<Event> = Parser::new(doc).collect::<Result<_, _>>().unwrap();
+//! assert_eq!(events[2], Event::Start(parserst::events::Tag::Em));
+//!
+//! // Drop inline code on the way through.
+//! let filtered = Parser::new(doc).filter(|event| !matches!(event, Ok(Event::Code(_))));
+//! assert_eq!(
+//!     from_events(filtered).unwrap(),
+//!     [parserst::Block::Paragraph(vec![
+//!         Inline::Text("Some ".into()),
+//!         Inline::Em(vec![Inline::Text("emphasis".into())]),
+//!         Inline::Text(" and ".into()),
+//!         Inline::Text(".".into()),
+//!     ])]
+//! );
+//! assert_eq!(from_events(Parser::new(doc)).unwrap(), parse(doc).unwrap());
+//! ```
+
+use std::borrow::Cow;
+use std::collections::VecDeque;
+
+use crate::ast::{HeadingIdStream, NameResolver, TargetIds, names_heading};
+use crate::render::{RenderOptions, prepare};
+use crate::visit::{Visitor, VisitorMut, walk_block};
+use crate::{
+    Block, Field, Inline, Lines, ListKind, OptionItem, ParseError, ParserOptions, PyObject, TableCell,
+    apply_conditionals, parse_next,
+};
+
+/// A piece of a document: the start or end of a container, or a leaf node.
+///
+/// Containers are bracketed by [`Event::Start`] and an [`Event::End`] carrying the same [`Tag`];
+/// their content comes as the events in between.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Event<'a> {
+    Start(Tag<'a>),
+    End(Tag<'a>),
+    /// [`Inline::Text`]
+    Text(Cow<'a, str>),
+    /// [`Inline::Code`]
+    Code(Cow<'a, str>),
+    /// [`Inline::FootnoteRef`]
+    FootnoteRef(String),
+    /// [`Block::CodeBlock`]
+    CodeBlock(Cow<'a, str>),
+    /// [`Block::LiteralBlock`]
+    LiteralBlock(Cow<'a, str>),
+    /// [`Block::Raw`]
+    Raw {
+        formats: Vec<String>,
+        content: String,
+    },
+    /// [`Block::Target`]
+    Target(String),
+}
+
+/// A container node, with the fields of its [`Block`] or [`Inline`] that aren't content.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Tag<'a> {
+    /// [`Block::Heading`], holding inline events
+    Heading {
+        level: u8,
+        id: String,
+    },
+    /// [`Block::Paragraph`], holding inline events
+    Paragraph,
+    /// [`Block::List`], holding [`Tag::Item`]s
+    List(ListKind),
+    /// A list item, holding inline events
+    Item,
+    Quote,
+    Directive {
+        name: String,
+        argument: String,
+    },
+    /// [`Block::Table`], holding an optional [`Tag::TableHead`] and then the body's [`Tag::TableRow`]s
+    Table {
+        widths: Vec<usize>,
+    },
+    /// The header rows of a table
+    TableHead,
+    TableRow,
+    TableCell {
+        colspan: usize,
+        rowspan: usize,
+    },
+    Comment,
+    /// [`Block::FieldList`], holding [`Tag::Field`]s
+    FieldList,
+    Field {
+        name: String,
+        argument: String,
+    },
+    /// [`Block::OptionList`], holding [`Tag::OptionItem`]s
+    OptionList,
+    OptionItem {
+        options: Vec<String>,
+    },
+    Footnote {
+        label: String,
+    },
+    /// [`Block::PyObject`]; its `content` is empty and comes as the events inside instead
+    PyObject(Box<PyObject<'a>>),
+    Em,
+    Strong,
+    Link {
+        url: Cow<'a, str>,
+    },
+}
+
+/// Parses a document into [`Event`]s, a top-level block at a time.
+///
+/// The events are those of [`parse_with`](crate::parse_with)'s result: Python names are resolved,
+/// headings get the same ids and conditional directives are applied. Parsing stops at the first
+/// error, which is the last item.
+///
+/// # Cost
+///
+/// The input is borrowed, not copied, but its lines are indexed up front: two words per line,
+/// kept until the parser is dropped. Each block's events are held only until they are yielded.
+/// Heading ids must not collide with targets anywhere in the document, including after the
+/// heading, so the first heading found sets off a parse of the rest of the input that keeps only
+/// the target ids. A document with headings is therefore parsed twice from its first heading on;
+/// one without is parsed once.
+#[derive(Debug)]
+pub struct Parser<'a> {
+    lines: Lines<'a>,
+    opts: ParserOptions,
+    names: NameResolver,
+    ids: HeadingIdStream,
+    /// Ids of the targets parsed so far, until the first heading needs the ones after it as well.
+    targets: Option<TargetIds>,
+    queue: VecDeque<Event<'a>>,
+    failed: bool,
+}
+
+impl<'a> Parser<'a> {
+    /// Parse `input` with the default [`ParserOptions`], like [`parse`](crate::parse).
+    pub fn new(input: &'a str) -> Self {
+        Self::with_options(input, &ParserOptions::default())
+    }
+
+    /// Parse `input` with `opts`, like [`parse_with`](crate::parse_with).
+    pub fn with_options(input: &'a str, opts: &ParserOptions) -> Self {
+        Self {
+            lines: Lines::new(input),
+            opts: opts.clone(),
+            names: NameResolver::default(),
+            ids: HeadingIdStream::new(),
+            targets: Some(TargetIds::default()),
+            queue: VecDeque::new(),
+            failed: false,
+        }
+    }
+
+    /// Record the targets in `blocks`, and once they hold a heading, those in the rest of the input.
+    fn collect_targets(&mut self, blocks: &[Block]) {
+        let Some(mut targets) = self.targets.take() else { return };
+        targets.visit_blocks(blocks);
+        if !has_heading(blocks) {
+            self.targets = Some(targets);
+            return;
+        }
+        let mut rest = Lines::new(self.lines.input);
+        rest.seek(self.lines.position());
+        while !rest.is_eof() {
+            match parse_next(&mut rest, &self.opts) {
+                Ok(blocks) => targets.visit_blocks(&blocks),
+                Err(_) => break,
+            }
+        }
+        self.ids.add_targets(targets);
+    }
+}
+
+/// Whether any of `blocks`, nested ones included, is a heading.
+fn has_heading(blocks: &[Block]) -> bool {
+    struct Find(bool);
+    impl Visitor for Find {
+        fn visit_block(&mut self, block: &Block) {
+            self.0 |= matches!(block, Block::Heading { .. });
+            walk_block(self, block);
+        }
+    }
+    let mut find = Find(false);
+    find.visit_blocks(blocks);
+    find.0
+}
+
+impl<'a> Iterator for Parser<'a> {
+    type Item = Result<Event<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.queue.is_empty() {
+            if self.failed || self.lines.is_eof() {
+                return None;
+            }
+            let mut blocks = match parse_next(&mut self.lines, &self.opts) {
+                Ok(blocks) => blocks,
+                Err(err) => {
+                    self.failed = true;
+                    return Some(Err(err));
+                }
+            };
+            self.collect_targets(&blocks);
+            self.names.visit_blocks_mut(&mut blocks);
+            self.ids.assign(&mut blocks);
+            if let Some(tags) = self.opts.active_tags() {
+                blocks = apply_conditionals(blocks, tags);
+            }
+            for block in blocks {
+                push_block(&mut self.queue, block);
+            }
+        }
+        self.queue.pop_front().map(Ok)
+    }
+}
+
+/// Push `Start(tag)`, the events `content` pushes and `End(tag)`.
+fn push_container<'a>(out: &mut VecDeque<Event<'a>>, tag: Tag<'a>, content: impl FnOnce(&mut VecDeque<Event<'a>>)) {
+    out.push_back(Event::Start(tag.clone()));
+    content(out);
+    out.push_back(Event::End(tag));
+}
+
+fn push_blocks<'a>(out: &mut VecDeque<Event<'a>>, blocks: Vec<Block<'a>>) {
+    for block in blocks {
+        push_block(out, block);
+    }
+}
+
+fn push_block<'a>(out: &mut VecDeque<Event<'a>>, block: Block<'a>) {
+    match block {
+        Block::Heading { level, inlines, id } => {
+            push_container(out, Tag::Heading { level, id }, |out| push_inlines(out, inlines))
+        }
+        Block::Paragraph(inlines) => push_container(out, Tag::Paragraph, |out| push_inlines(out, inlines)),
+        Block::List { kind, items } => push_container(out, Tag::List(kind), |out| {
+            for item in items {
+                push_container(out, Tag::Item, |out| push_inlines(out, item));
+            }
+        }),
+        Block::CodeBlock(code) => out.push_back(Event::CodeBlock(code)),
+        Block::Quote(children) => push_container(out, Tag::Quote, |out| push_blocks(out, children)),
+        Block::LiteralBlock(code) => out.push_back(Event::LiteralBlock(code)),
+        Block::Directive { name, argument, content } => {
+            push_container(out, Tag::Directive { name, argument }, |out| push_blocks(out, content))
+        }
+        Block::Table { headers, rows, widths } => push_container(out, Tag::Table { widths }, |out| {
+            if !headers.is_empty() {
+                push_container(out, Tag::TableHead, |out| push_rows(out, headers));
+            }
+            push_rows(out, rows);
+        }),
+        Block::Comment(children) => push_container(out, Tag::Comment, |out| push_blocks(out, children)),
+        Block::FieldList { fields } => push_container(out, Tag::FieldList, |out| {
+            for Field { name, argument, body } in fields {
+                push_container(out, Tag::Field { name, argument }, |out| push_blocks(out, body));
+            }
+        }),
+        Block::Raw { formats, content } => out.push_back(Event::Raw { formats, content }),
+        Block::Target { name } => out.push_back(Event::Target(name)),
+        Block::PyObject(mut obj) => {
+            let content = std::mem::take(&mut obj.content);
+            push_container(out, Tag::PyObject(obj), |out| push_blocks(out, content));
+        }
+        Block::OptionList { items } => push_container(out, Tag::OptionList, |out| {
+            for OptionItem { options, body } in items {
+                push_container(out, Tag::OptionItem { options }, |out| push_blocks(out, body));
+            }
+        }),
+        Block::Footnote { label, content } => {
+            push_container(out, Tag::Footnote { label }, |out| push_blocks(out, content))
+        }
+    }
+}
+
+fn push_rows<'a>(out: &mut VecDeque<Event<'a>>, rows: Vec<Vec<TableCell<'a>>>) {
+    for row in rows {
+        push_container(out, Tag::TableRow, |out| {
+            for TableCell { content, colspan, rowspan } in row {
+                push_container(out, Tag::TableCell { colspan, rowspan }, |out| {
+                    push_blocks(out, content)
+                });
+            }
+        });
+    }
+}
+
+fn push_inlines<'a>(out: &mut VecDeque<Event<'a>>, inlines: Vec<Inline<'a>>) {
+    for inline in inlines {
+        match inline {
+            Inline::Text(text) => out.push_back(Event::Text(text)),
+            Inline::Em(children) => push_container(out, Tag::Em, |out| push_inlines(out, children)),
+            Inline::Strong(children) => push_container(out, Tag::Strong, |out| push_inlines(out, children)),
+            Inline::Code(code) => out.push_back(Event::Code(code)),
+            Inline::Link { text, url } => push_container(out, Tag::Link { url }, |out| push_inlines(out, text)),
+            Inline::FootnoteRef(label) => out.push_back(Event::FootnoteRef(label)),
+        }
+    }
+}
+
+/// Builds top-level [`Block`]s from events, yielding each as soon as its last event arrives.
+///
+/// An [`Event::End`] closes the innermost open container whatever its tag, containers still open
+/// when the events run out are closed, and content that doesn't fit where it appears (such as
+/// text outside any block) is dropped.
+#[derive(Debug)]
+pub struct Blocks<'a, I> {
+    events: I,
+    open: Vec<(Tag<'a>, Vec<Node<'a>>)>,
+}
+
+/// A finished node, waiting to become part of its parent.
+#[derive(Debug)]
+enum Node<'a> {
+    Block(Block<'a>),
+    Inline(Inline<'a>),
+    Item(Vec<Inline<'a>>),
+    Head(Vec<Vec<TableCell<'a>>>),
+    Row(Vec<TableCell<'a>>),
+    Cell(TableCell<'a>),
+    Field(Field<'a>),
+    OptionItem(OptionItem<'a>),
+}
+
+impl<'a, I: Iterator<Item = Result<Event<'a>, ParseError>>> Blocks<'a, I> {
+    pub fn new(events: impl IntoIterator<IntoIter = I>) -> Self {
+        Self { events: events.into_iter(), open: Vec::new() }
+    }
+}
+
+impl<'a, I: Iterator<Item = Result<Event<'a>, ParseError>>> Iterator for Blocks<'a, I> {
+    type Item = Result<Block<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let node = match self.events.next() {
+                Some(Err(err)) => return Some(Err(err)),
+                Some(Ok(Event::Start(tag))) => {
+                    self.open.push((tag, Vec::new()));
+                    continue;
+                }
+                Some(Ok(Event::End(_))) => match self.open.pop() {
+                    Some((tag, children)) => close(tag, children),
+                    None => continue,
+                },
+                Some(Ok(event)) => leaf(event),
+                None => close_last(&mut self.open)?,
+            };
+            match self.open.last_mut() {
+                Some((_, children)) => children.push(node),
+                None => {
+                    if let Node::Block(block) = node {
+                        return Some(Ok(block));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn close_last<'a>(open: &mut Vec<(Tag<'a>, Vec<Node<'a>>)>) -> Option<Node<'a>> {
+    let (tag, children) = open.pop()?;
+    Some(close(tag, children))
+}
+
+fn leaf(event: Event<'_>) -> Node<'_> {
+    match event {
+        Event::Text(text) => Node::Inline(Inline::Text(text)),
+        Event::Code(code) => Node::Inline(Inline::Code(code)),
+        Event::FootnoteRef(label) => Node::Inline(Inline::FootnoteRef(label)),
+        Event::CodeBlock(code) => Node::Block(Block::CodeBlock(code)),
+        Event::LiteralBlock(code) => Node::Block(Block::LiteralBlock(code)),
+        Event::Raw { formats, content } => Node::Block(Block::Raw { formats, content }),
+        Event::Target(name) => Node::Block(Block::Target { name }),
+        Event::Start(_) | Event::End(_) => unreachable!("containers are handled by the caller"),
+    }
+}
+
+fn close<'a>(tag: Tag<'a>, children: Vec<Node<'a>>) -> Node<'a> {
+    let mut blocks = Vec::new();
+    let mut inlines = Vec::new();
+    let mut items = Vec::new();
+    let mut headers = Vec::new();
+    let mut rows = Vec::new();
+    let mut cells = Vec::new();
+    let mut fields = Vec::new();
+    let mut options = Vec::new();
+    for node in children {
+        match node {
+            Node::Block(block) => blocks.push(block),
+            Node::Inline(inline) => inlines.push(inline),
+            Node::Item(item) => items.push(item),
+            Node::Head(head) => headers.extend(head),
+            Node::Row(row) => rows.push(row),
+            Node::Cell(cell) => cells.push(cell),
+            Node::Field(field) => fields.push(field),
+            Node::OptionItem(item) => options.push(item),
+        }
+    }
+    match tag {
+        Tag::Heading { level, id } => Node::Block(Block::Heading { level, inlines, id }),
+        Tag::Paragraph => Node::Block(Block::Paragraph(inlines)),
+        Tag::List(kind) => Node::Block(Block::List { kind, items }),
+        Tag::Item => Node::Item(inlines),
+        Tag::Quote => Node::Block(Block::Quote(blocks)),
+        Tag::Directive { name, argument } => Node::Block(Block::Directive { name, argument, content: blocks }),
+        Tag::Table { widths } => Node::Block(Block::Table { headers, rows, widths }),
+        Tag::TableHead => Node::Head(rows),
+        Tag::TableRow => Node::Row(cells),
+        Tag::TableCell { colspan, rowspan } => Node::Cell(TableCell { content: blocks, colspan, rowspan }),
+        Tag::Comment => Node::Block(Block::Comment(blocks)),
+        Tag::FieldList => Node::Block(Block::FieldList { fields }),
+        Tag::Field { name, argument } => Node::Field(Field { name, argument, body: blocks }),
+        Tag::OptionList => Node::Block(Block::OptionList { items: options }),
+        Tag::OptionItem { options } => Node::OptionItem(OptionItem { options, body: blocks }),
+        Tag::Footnote { label } => Node::Block(Block::Footnote { label, content: blocks }),
+        Tag::PyObject(mut obj) => {
+            obj.content = blocks;
+            Node::Block(Block::PyObject(obj))
+        }
+        Tag::Em => Node::Inline(Inline::Em(inlines)),
+        Tag::Strong => Node::Inline(Inline::Strong(inlines)),
+        Tag::Link { url } => Node::Inline(Inline::Link { text: inlines, url }),
+    }
+}
+
+/// Build the whole tree from `events`; [`Blocks`] yields it a top-level block at a time instead.
+pub fn from_events<'a, I>(events: I) -> Result<Vec<Block<'a>>, ParseError>
+where
+    I: IntoIterator<Item = Result<Event<'a>, ParseError>>,
+{
+    Blocks::new(events).collect()
+}
+
+/// The top-level blocks of `events` with `opts` applied as [`prepare`] does for a whole document,
+/// each paired with whether it is a target that supplies the id of the heading after it.
+pub(crate) fn prepared_blocks<'a: 'o, 'o, I>(
+    events: I, opts: &'o RenderOptions,
+) -> impl Iterator<Item = Result<(Block<'a>, bool), ParseError>> + 'o
+where
+    I: IntoIterator<Item = Result<Event<'a>, ParseError>>,
+    I::IntoIter: 'o,
+{
+    let mut blocks = Blocks::new(events);
+    let mut ahead = VecDeque::new();
+    let mut error = None;
+    std::iter::from_fn(move || {
+        while ahead.len() < 2 && error.is_none() {
+            match blocks.next() {
+                Some(Ok(block)) => ahead.extend(prepare(std::slice::from_ref(&block), opts).into_owned()),
+                Some(Err(err)) => error = Some(err),
+                None => break,
+            }
+        }
+        match ahead.pop_front() {
+            Some(block) => {
+                let names = names_heading(&block, ahead.front());
+                Some(Ok((block, names)))
+            }
+            None => error.take().map(Err),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HtmlRenderer, Tags, TextRenderer, parse, parse_with};
+
+    const DOC: &str = "\
+Intro
+=====
+
+.. _intro:
+
+Setup
+-----
+
+Some *emphasis*, **strong** and `a link <https://a.b>`_ [1]_.
+
+- one
+- two
+
+.. note:: Careful.
+
+   Nested paragraph.
+
+-v, --verbose  Be loud.
+
+:param x: The value.
+
+===== =====
+A     B
+===== =====
+x     y
+===== =====
+
+.. py:module:: pkg
+
+.. py:class:: Thing
+
+   .. py:method:: run(self)
+
+.. [1] A footnote.
+
+.. a comment
+
+::
+
+   literal
+";
+
+    #[test]
+    fn builds_the_tree_parse_returns() {
+        assert_eq!(from_events(Parser::new(DOC)).unwrap(), parse(DOC).unwrap());
+        let opts = ParserOptions::strict().tags(Tags::from_iter(["html"]));
+        let doc = ".. only:: html\n\n   Shown.\n\n.. only:: latex\n\n   Hidden.\n";
+        assert_eq!(
+            from_events(Parser::with_options(doc, &opts)).unwrap(),
+            parse_with(doc, &opts).unwrap()
+        );
+    }
+
+    #[test]
+    fn heading_ids_avoid_later_targets() {
+        let doc = "Usage\n=====\n\nText.\n\n.. _usage:\n\nMore text.\n";
+        let blocks = from_events(Parser::new(doc)).unwrap();
+        assert!(matches!(&blocks[0], Block::Heading { id, .. } if id == "usage-1"));
+        assert_eq!(blocks, parse(doc).unwrap());
+
+        let doc = ".. _setup:\n\nIntro.\n\nSetup\n=====\n\nInstall\n-------\n\n.. note::\n\n   .. _install:\n";
+        let blocks = from_events(Parser::new(doc)).unwrap();
+        let ids: Vec<_> = blocks
+            .iter()
+            .filter_map(|block| match block {
+                Block::Heading { id, .. } => Some(id.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(ids, ["setup-1", "install-1"]);
+        assert_eq!(blocks, parse(doc).unwrap());
+    }
+
+    #[test]
+    fn yields_events_in_document_order() {
+        let events: Vec<Event> = Parser::new("Title\n=====\n\n- *a*\n")
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let heading = Tag::Heading { level: 1, id: "title".into() };
+        assert_eq!(
+            events,
+            [
+                Event::Start(heading.clone()),
+                Event::Text("Title".into()),
+                Event::End(heading),
+                Event::Start(Tag::List(ListKind::Unordered)),
+                Event::Start(Tag::Item),
+                Event::Start(Tag::Em),
+                Event::Text("a".into()),
+                Event::End(Tag::Em),
+                Event::End(Tag::Item),
+                Event::End(Tag::List(ListKind::Unordered)),
+            ]
+        );
+    }
+
+    #[test]
+    fn stops_at_the_first_error() {
+        let opts = ParserOptions::new()
+            .file_resolver(|_: &str| Err(std::io::Error::new(std::io::ErrorKind::NotFound, "missing")));
+        let doc = "Fine.\n\n.. raw:: html\n   :file: missing.html\n\nNever reached.\n";
+        let items: Vec<_> = Parser::with_options(doc, &opts).collect();
+        assert!(items[..3].iter().all(Result::is_ok));
+        assert_eq!(items.len(), 4);
+        assert_eq!(
+            items[3].as_ref().unwrap_err().to_string(),
+            parse_with(doc, &opts).unwrap_err().to_string()
+        );
+        assert!(from_events(Parser::with_options(doc, &opts)).is_err());
+    }
+
+    #[test]
+    fn unbalanced_streams_are_closed_off() {
+        let events = [
+            Event::Text("stray".into()),
+            Event::Start(Tag::Paragraph),
+            Event::Text("a".into()),
+            Event::End(Tag::Paragraph),
+            Event::End(Tag::Quote),
+            Event::Start(Tag::Quote),
+            Event::Start(Tag::Paragraph),
+            Event::Text("b".into()),
+        ];
+        let mut blocks = Blocks::new(events.into_iter().map(Ok));
+        assert_eq!(
+            blocks.next().unwrap().unwrap(),
+            Block::Paragraph(vec![Inline::Text("a".into())])
+        );
+        assert_eq!(
+            blocks.next().unwrap().unwrap(),
+            Block::Quote(vec![Block::Paragraph(vec![Inline::Text("b".into())])])
+        );
+        assert!(blocks.next().is_none());
+    }
+
+    #[test]
+    fn renderers_consume_events() {
+        let blocks = parse(DOC).unwrap();
+        for html in [HtmlRenderer::new(), HtmlRenderer::new().pretty(true)] {
+            let streamed: String = html.render_events(Parser::new(DOC)).collect::<Result<_, _>>().unwrap();
+            assert_eq!(streamed, html.render(&blocks));
+        }
+        let text = TextRenderer::new().width(40);
+        let streamed: String = text.render_events(Parser::new(DOC)).collect::<Result<_, _>>().unwrap();
+        assert_eq!(streamed, text.render(&blocks));
+    }
+}
@@ -0,0 +1,257 @@
+//! Source formatter for reStructuredText, in the spirit of `rustfmt`.
+
+use std::borrow::Cow;
+
+use crate::ADORNMENTS;
+use crate::rst::{BODY_INDENT, write_blocks, write_inlines};
+use crate::textutil::display_width;
+use crate::visit::{VisitorMut, walk_block_mut, walk_inline_mut};
+use crate::{Block, Inline, Lines, ParseError, ParserOptions, is_blank, parse_next, parse_with};
+
+/// Options for [`format()`].
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    width: usize,
+    overlined: u8,
+    parser: ParserOptions,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self { width: 79, overlined: 0, parser: ParserOptions::strict() }
+    }
+}
+
+impl FormatOptions {
+    /// Defaults to a line width of 79, reading input with [`ParserOptions::strict`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum line width that paragraphs and list items are rewrapped to.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Adorn section titles down to `level` with an overline as well as an underline, as in
+    /// `overlined(1)` for overlined top-level titles. The default of 0 underlines every title only.
+    ///
+    /// The adornment character always follows the title's level, `=`, `-`, `~`, `^` and `"` from
+    /// the top, and adornments are sized to the title.
+    pub fn overlined(mut self, level: u8) -> Self {
+        self.overlined = level;
+        self
+    }
+
+    /// Parse input with `parser` instead, e.g. [`ParserOptions::docstring`] for docstring sources.
+    pub fn parser(mut self, parser: ParserOptions) -> Self {
+        self.parser = parser;
+        self
+    }
+
+    /// `title` adorned in the house style for `level`.
+    fn title(&self, title: &str, level: u8) -> String {
+        let adornment = ADORNMENTS[usize::from(level.clamp(1, 5)) - 1];
+        let rule = adornment.to_string().repeat(display_width(title).max(1));
+        if level <= self.overlined {
+            format!("{rule}\n{title}\n{rule}")
+        } else {
+            format!("{title}\n{rule}")
+        }
+    }
+}
+
+/// Reformat reST source without changing what it means.
+///
+/// The source is read one top-level construct at a time. Paragraphs, section titles, bullet and
+/// arabic-numbered lists and tables are rewritten when the parsed blocks hold everything their
+/// source says: paragraphs and list items are rewrapped to the configured width, never breaking
+/// inside inline markup, titles are adorned in the house style set by
+/// [`FormatOptions::overlined`] with rules sized to the title, enumerated lists are renumbered
+/// from 1 and tables are realigned to their content. Directive bodies are re-indented to three
+/// spaces. Anything else, including text with interpreted roles or hyperlink references, `#.`
+/// lists and literal blocks, is copied as it is.
+///
+/// Formatting is idempotent, and the output parses to the same blocks as the input.
+///
+/// ```
+/// use parserst::{FormatOptions, format};
+///
+/// let src = "Title\n==\n\nSome   text that\nwraps.\n\n3. a\n7. b\n\nSee :ref:`this   one`.\n";
+/// let out = format(src, &FormatOptions::new()).unwrap();
+/// assert_eq!(out, "Title\n=====\n\nSome text that wraps.\n\n1. a\n2. b\n\nSee :ref:`this   one`.\n");
+/// ```
+pub fn format(input: &str, opts: &FormatOptions) -> Result<String, ParseError> {
+    let mut ls = Lines::new(input);
+    let mut out = String::new();
+    let mut gap = false;
+    while !ls.is_eof() {
+        let start = ls.position();
+        let blocks = parse_next(&mut ls, &opts.parser)?;
+        let text = ls.text_since(start);
+        let leading = text.lines().take_while(|l| is_blank(l)).count();
+        let source = text.lines().skip(leading).collect::<Vec<_>>().join("\n");
+        let source = source.trim_end();
+        gap |= leading > 0;
+        if !source.is_empty() {
+            if !out.is_empty() {
+                out.push_str(if gap { "\n\n" } else { "\n" });
+            }
+            match rewrite(source, &blocks, opts) {
+                Some(rewritten) => out.push_str(&rewritten),
+                None => out.push_str(source),
+            }
+            gap = false;
+        }
+        gap |= text.lines().last().is_some_and(is_blank);
+    }
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    // Each rewrite reparses on its own; this catches one that reads differently next to its
+    // neighbours, in which case the source is kept whole.
+    if unformatted(parse_with(&out, &opts.parser)?) != unformatted(parse_with(input, &opts.parser)?) {
+        let kept = input.trim_end();
+        return Ok(if kept.is_empty() { String::new() } else { format!("{kept}\n") });
+    }
+    Ok(out)
+}
+
+/// `source`, the text of one top-level construct that parsed to `blocks`, rewritten in the house
+/// style; `None` if the blocks lose something the source says or the rewrite would parse
+/// differently.
+fn rewrite(source: &str, blocks: &[Block], opts: &FormatOptions) -> Option<String> {
+    let [block] = blocks else { return None };
+    if source.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let unfilled = write_blocks(blocks, None);
+    let rewritten = match block {
+        Block::Paragraph(_) => {
+            let lossless = source
+                .lines()
+                .all(|l| !l.starts_with(char::is_whitespace) && !is_adornment(l) && !starts_like_item(l))
+                && words(source, |_| true) == words(&unfilled, |_| true);
+            lossless.then(|| write_blocks(blocks, Some(opts.width)))?
+        }
+        Block::Heading { level, inlines, .. } => {
+            let title = match source.lines().collect::<Vec<_>>()[..] {
+                [title, under] if is_adornment(under) => title,
+                [over, title, under] if is_adornment(over) && over == under => title.trim(),
+                _ => return None,
+            };
+            (title == write_inlines(inlines)).then(|| opts.title(title, *level))?
+        }
+        Block::List { .. } => {
+            let continuation = |l: &str| l.starts_with(char::is_whitespace) && !is_adornment(l) && !starts_like_item(l);
+            let text = |w: &&str| !is_enumerator(w);
+            let lossless = source.lines().all(|l| {
+                !is_blank(l) && (continuation(l) || !l.starts_with(char::is_whitespace) && starts_like_item(l))
+            }) && words(source, text) == words(&unfilled, text);
+            lossless.then(|| write_blocks(blocks, Some(opts.width)))?
+        }
+        Block::Table { headers, rows, .. } => {
+            let text = |w: &&str| !w.chars().all(|c| matches!(c, '+' | '-' | '=' | '|'));
+            let fitted = Block::Table { headers: headers.clone(), rows: rows.clone(), widths: Vec::new() };
+            (words(source, text) == words(&unfilled, text)).then(|| write_blocks(&[fitted], None))?
+        }
+        Block::Directive { .. } | Block::PyObject(_) | Block::Raw { .. } => reindent_body(source)?,
+        _ => return None,
+    };
+    let mut ls = Lines::new(&rewritten);
+    let reparsed = parse_next(&mut ls, &opts.parser).ok()?;
+    let done = parse_next(&mut ls, &opts.parser).ok()?.is_empty();
+    (done && unformatted(reparsed) == unformatted(blocks.to_vec())).then_some(rewritten)
+}
+
+/// The explicit markup in `source` with every line after the first moved to [`BODY_INDENT`],
+/// keeping the lines' indentation relative to each other; `None` if it is there already.
+fn reindent_body(source: &str) -> Option<String> {
+    let (head, body) = source.split_once('\n')?;
+    let indent = |l: &str| l.len() - l.trim_start_matches(' ').len();
+    let margin = body.lines().filter(|l| !is_blank(l)).map(indent).min()?;
+    if margin == BODY_INDENT || body.lines().any(|l| l.trim_start_matches(' ').starts_with('\t')) {
+        return None;
+    }
+    let pad = " ".repeat(BODY_INDENT);
+    let body = body
+        .lines()
+        .map(|l| if is_blank(l) { String::new() } else { format!("{pad}{}", &l[margin..]) });
+    Some(
+        std::iter::once(head.to_string())
+            .chain(body)
+            .collect::<Vec<_>>()
+            .join("\n"),
+    )
+}
+
+/// `blocks` with what formatting may change normalised away: each run of whitespace in their text
+/// is collapsed to one space, and table column widths are dropped.
+pub(crate) fn unformatted(mut blocks: Vec<Block>) -> Vec<Block> {
+    Unformat.visit_blocks_mut(&mut blocks);
+    blocks
+}
+
+struct Unformat;
+
+impl VisitorMut for Unformat {
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        if let Block::Table { widths, .. } = block {
+            widths.clear();
+        }
+        walk_block_mut(self, block);
+    }
+
+    fn visit_inline_mut(&mut self, inline: &mut Inline) {
+        if let Inline::Text(text) = inline {
+            let mut collapsed = String::with_capacity(text.len());
+            for c in text.chars() {
+                if !c.is_whitespace() {
+                    collapsed.push(c);
+                } else if !collapsed.ends_with(' ') {
+                    collapsed.push(' ');
+                }
+            }
+            *text = Cow::Owned(collapsed);
+        }
+        walk_inline_mut(self, inline);
+    }
+}
+
+/// The whitespace-separated words of `text` that `keep` accepts.
+fn words(text: &str, keep: impl Fn(&&str) -> bool) -> Vec<&str> {
+    text.split_whitespace().filter(keep).collect()
+}
+
+/// A line of one repeated punctuation character, which reads as a title adornment once it stands
+/// on its own line.
+fn is_adornment(line: &str) -> bool {
+    let line = line.trim();
+    let mut chars = line.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_punctuation() && chars.all(|d| d == c))
+}
+
+/// Whether `line` starts with something that reads as a list marker, which keeps the line from
+/// being joined to the one before.
+fn starts_like_item(line: &str) -> bool {
+    let Some(word) = line.split_whitespace().next() else { return false };
+    let body = word
+        .strip_prefix('(')
+        .and_then(|w| w.strip_suffix(')'))
+        .or_else(|| word.strip_suffix(['.', ')']))
+        .unwrap_or_default();
+    let roman = !body.is_empty() && body.chars().all(|c| "ivxlcdmIVXLCDM".contains(c));
+    is_enumerator(&word) || roman || body == "#" || (body.len() == 1 && body.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+/// A bullet or arabic enumerator, which the writer may replace with its own.
+fn is_enumerator(word: &&str) -> bool {
+    let digits = word
+        .strip_prefix('(')
+        .and_then(|w| w.strip_suffix(')'))
+        .or_else(|| word.strip_suffix(['.', ')']));
+    matches!(*word, "-" | "*" | "+") || digits.is_some_and(|d| !d.is_empty() && d.bytes().all(|b| b.is_ascii_digit()))
+}
@@ -0,0 +1,509 @@
+//! Bundled grammars for Python, Rust, shell, JSON and YAML.
+
+use super::Highlighter;
+use crate::ast::html_escape;
+
+/// Highlights Python, Rust, shell, JSON and YAML without any external dependencies.
+///
+/// Tokens are wrapped in `<span>`s with Pygments' short class names (`k` keyword, `kc` constant,
+/// `s` string, `c` comment, `m` number, `nt` mapping key, `nv` variable, `nd` decorator), so
+/// stylesheets written for Sphinx output apply unchanged. Other languages are left to the renderer.
+///
+/// ```
+/// use parserst::{BuiltinHighlighter, Highlighter};
+///
+/// let html = BuiltinHighlighter::new().highlight("python", "x = None  # unset").unwrap();
+/// assert_eq!(html, "x = <span class=\"kc\">None</span>  <span class=\"c\"># unset</span>");
+/// assert!(BuiltinHighlighter::new().highlight("cobol", "DISPLAY 'HI'.").is_none());
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BuiltinHighlighter;
+
+impl BuiltinHighlighter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Whether `language`, or an alias such as `py` or `bash`, has a bundled grammar.
+    pub fn supports(&self, language: &str) -> bool {
+        grammar(language).is_some()
+    }
+}
+
+impl Highlighter for BuiltinHighlighter {
+    fn highlight(&self, language: &str, code: &str) -> Option<String> {
+        let grammar = grammar(language)?;
+        Some(Lexer { grammar, src: code, pos: 0, out: String::new() }.run())
+    }
+}
+
+/// What a language's tokens look like.
+struct Grammar {
+    keywords: &'static [&'static str],
+    constants: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    quotes: &'static [char],
+    /// Characters besides alphanumerics and `_` that continue a word
+    word_chars: &'static str,
+    /// Line comments only start at the beginning of a word, as in shell and YAML
+    comments_need_space: bool,
+    /// `"""` strings and `r"..."`, `b'...'`, `f"..."` prefixes
+    python_strings: bool,
+    /// `r#"..."#` raw strings and `'c'` char literals, as opposed to `'a` lifetimes
+    rust_literals: bool,
+    /// `$NAME` and `${...}` expansions
+    variables: bool,
+    /// `@name` decorators at the start of a line
+    decorators: bool,
+    /// Strings followed by `:` are mapping keys
+    quoted_keys: bool,
+    /// Unquoted text before `: ` at the start of a line is a mapping key
+    plain_keys: bool,
+}
+
+const PYTHON: Grammar = Grammar {
+    keywords: &[
+        "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif", "else", "except",
+        "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass",
+        "raise", "return", "try", "while", "with", "yield",
+    ],
+    constants: &["True", "False", "None"],
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+    word_chars: "",
+    comments_need_space: false,
+    python_strings: true,
+    rust_literals: false,
+    variables: false,
+    decorators: true,
+    quoted_keys: false,
+    plain_keys: false,
+};
+
+const RUST: Grammar = Grammar {
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "fn", "for",
+        "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self",
+        "static", "struct", "super", "trait", "type", "unsafe", "use", "where", "while",
+    ],
+    constants: &["true", "false"],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"'],
+    word_chars: "",
+    comments_need_space: false,
+    python_strings: false,
+    rust_literals: true,
+    variables: false,
+    decorators: false,
+    quoted_keys: false,
+    plain_keys: false,
+};
+
+const SHELL: Grammar = Grammar {
+    keywords: &[
+        "if", "then", "else", "elif", "fi", "case", "esac", "for", "select", "while", "until", "do", "done", "in",
+        "function", "return", "export", "local", "readonly", "declare", "unset", "shift", "exit", "break", "continue",
+        "source",
+    ],
+    constants: &[],
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+    word_chars: "-.",
+    comments_need_space: true,
+    python_strings: false,
+    rust_literals: false,
+    variables: true,
+    decorators: false,
+    quoted_keys: false,
+    plain_keys: false,
+};
+
+const JSON: Grammar = Grammar {
+    keywords: &[],
+    constants: &["true", "false", "null"],
+    line_comments: &[],
+    block_comment: None,
+    quotes: &['"'],
+    word_chars: "",
+    comments_need_space: false,
+    python_strings: false,
+    rust_literals: false,
+    variables: false,
+    decorators: false,
+    quoted_keys: true,
+    plain_keys: false,
+};
+
+const YAML: Grammar = Grammar {
+    keywords: &[],
+    constants: &[
+        "true", "false", "null", "yes", "no", "True", "False", "Null", "Yes", "No", "TRUE", "FALSE", "NULL",
+    ],
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+    word_chars: "-.",
+    comments_need_space: true,
+    python_strings: false,
+    rust_literals: false,
+    variables: false,
+    decorators: false,
+    quoted_keys: true,
+    plain_keys: true,
+};
+
+fn grammar(language: &str) -> Option<&'static Grammar> {
+    match language.to_ascii_lowercase().as_str() {
+        "python" | "python3" | "py" | "py3" => Some(&PYTHON),
+        "rust" | "rs" => Some(&RUST),
+        "shell" | "sh" | "bash" | "zsh" => Some(&SHELL),
+        "json" => Some(&JSON),
+        "yaml" | "yml" => Some(&YAML),
+        _ => None,
+    }
+}
+
+struct Lexer<'a> {
+    grammar: &'static Grammar,
+    src: &'a str,
+    pos: usize,
+    out: String,
+}
+
+impl Lexer<'_> {
+    fn run(mut self) -> String {
+        while let Some(c) = self.rest().chars().next() {
+            if let Some(len) = self.comment() {
+                self.emit("c", len);
+            } else if let Some(len) = self.string(0) {
+                let class = if self.grammar.quoted_keys && self.followed_by_colon(len) { "nt" } else { "s" };
+                self.emit(class, len);
+            } else if let Some(len) = self.rust_char() {
+                self.emit("s", len);
+            } else if let Some(len) = self.variable() {
+                self.emit("nv", len);
+            } else if let Some(len) = self.decorator() {
+                self.emit("nd", len);
+            } else if let Some(len) = self.plain_key() {
+                self.emit("nt", len);
+            } else if c.is_ascii_digit() {
+                let len = self.number();
+                self.emit("m", len);
+            } else if self.is_word_char(c) {
+                self.word();
+            } else {
+                self.emit("", c.len_utf8());
+            }
+        }
+        self.out
+    }
+
+    fn rest(&self) -> &str {
+        &self.src[self.pos..]
+    }
+
+    /// The character before the current position.
+    fn prev(&self) -> Option<char> {
+        self.src[..self.pos].chars().next_back()
+    }
+
+    /// Write the next `len` bytes, in a span of `class` unless it is empty.
+    fn emit(&mut self, class: &str, len: usize) {
+        let text = html_escape(&self.src[self.pos..self.pos + len]);
+        if class.is_empty() {
+            self.out.push_str(&text);
+        } else {
+            self.out.push_str(&format!("<span class=\"{class}\">{text}</span>"));
+        }
+        self.pos += len;
+    }
+
+    fn is_word_char(&self, c: char) -> bool {
+        c.is_alphanumeric() || c == '_' || self.grammar.word_chars.contains(c)
+    }
+
+    fn at_word_start(&self) -> bool {
+        self.prev().is_none_or(|c| !self.is_word_char(c))
+    }
+
+    fn comment(&self) -> Option<usize> {
+        let rest = self.rest();
+        if let Some((open, close)) = self.grammar.block_comment
+            && rest.starts_with(open)
+        {
+            return Some(
+                rest[open.len()..]
+                    .find(close)
+                    .map_or(rest.len(), |i| open.len() + i + close.len()),
+            );
+        }
+        if !self.grammar.line_comments.iter().any(|marker| rest.starts_with(marker)) {
+            return None;
+        }
+        if self.grammar.comments_need_space && self.prev().is_some_and(|c| !c.is_whitespace()) {
+            return None;
+        }
+        Some(rest.find('\n').unwrap_or(rest.len()))
+    }
+
+    /// Length of a string literal starting `prefix` bytes into the rest of the input.
+    fn string(&self, prefix: usize) -> Option<usize> {
+        let rest = &self.rest()[prefix..];
+        let quote = rest.chars().next().filter(|c| self.grammar.quotes.contains(c))?;
+        if prefix == 0 && !self.grammar.variables && !self.at_word_start() {
+            return None;
+        }
+        let triple: String = std::iter::repeat_n(quote, 3).collect();
+        if self.grammar.python_strings && rest.starts_with(&triple) {
+            let end = rest[3..].find(&triple).map_or(rest.len(), |i| i + 6);
+            return Some(prefix + end);
+        }
+        let mut chars = rest.char_indices().skip(1);
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' if quote != '\'' || !self.grammar.variables => {
+                    chars.next();
+                }
+                '\n' if self.grammar.python_strings => return Some(prefix + i),
+                c if c == quote => return Some(prefix + i + 1),
+                _ => {}
+            }
+        }
+        Some(prefix + rest.len())
+    }
+
+    /// Length of a Rust raw string such as `r#"..."#`, starting `prefix` bytes in.
+    fn raw_string(&self, prefix: usize) -> Option<usize> {
+        let rest = &self.rest()[prefix..];
+        let hashes = rest.len() - rest.trim_start_matches('#').len();
+        let body = rest[hashes..].strip_prefix('"')?;
+        let close = format!("\"{}", "#".repeat(hashes));
+        let end = body.find(&close).map_or(body.len(), |i| i + close.len());
+        Some(prefix + hashes + 1 + end)
+    }
+
+    /// A Rust char literal like `'x'` or `'\n'`; a lone `'` starts a lifetime instead.
+    fn rust_char(&self) -> Option<usize> {
+        let rest = self.rest();
+        if !self.grammar.rust_literals || !rest.starts_with('\'') {
+            return None;
+        }
+        if rest[1..].starts_with('\\') {
+            return rest[2..].find('\'').map(|i| i + 3);
+        }
+        let mut chars = rest.char_indices().skip(1);
+        let (_, c) = chars.next()?;
+        match chars.next() {
+            Some((i, '\'')) if c != '\'' => Some(i + 1),
+            _ => None,
+        }
+    }
+
+    fn variable(&self) -> Option<usize> {
+        let rest = self.rest();
+        if !self.grammar.variables || !rest.starts_with('$') {
+            return None;
+        }
+        let after = &rest[1..];
+        if after.starts_with('{') {
+            return Some(after.find('}').map_or(rest.len(), |i| i + 2));
+        }
+        let name = after.len()
+            - after
+                .trim_start_matches(|c: char| c.is_alphanumeric() || c == '_')
+                .len();
+        match name {
+            0 => after
+                .chars()
+                .next()
+                .filter(|c| "?#@*!$-".contains(*c))
+                .map(|c| 1 + c.len_utf8()),
+            n => Some(1 + n),
+        }
+    }
+
+    fn decorator(&self) -> Option<usize> {
+        let rest = self.rest();
+        if !self.grammar.decorators || !rest.starts_with('@') || !self.line_prefix().trim().is_empty() {
+            return None;
+        }
+        let name = rest[1..].len()
+            - rest[1..]
+                .trim_start_matches(|c: char| self.is_word_char(c) || c == '.')
+                .len();
+        (name > 0).then_some(1 + name)
+    }
+
+    /// Text between the start of the current line and the current position.
+    fn line_prefix(&self) -> &str {
+        let before = &self.src[..self.pos];
+        &before[before.rfind('\n').map_or(0, |i| i + 1)..]
+    }
+
+    /// An unquoted YAML key: the start of a line (after any `- ` markers) up to a `:` that ends the line
+    /// or is followed by a space.
+    fn plain_key(&self) -> Option<usize> {
+        if !self.grammar.plain_keys || !self.line_prefix().chars().all(|c| c == ' ' || c == '-') {
+            return None;
+        }
+        let rest = self.rest();
+        if rest.starts_with(['-', ' ', '"', '\'', '#']) {
+            return None;
+        }
+        let line = &rest[..rest.find('\n').unwrap_or(rest.len())];
+        let mut chars = line.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                ':' if chars.peek().is_none_or(|(_, next)| next.is_whitespace()) => {
+                    return Some(line[..i].trim_end().len());
+                }
+                '#' if line[..i].ends_with(' ') => return None,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Whether a `:` follows the `len` bytes ahead, after optional spaces.
+    fn followed_by_colon(&self, len: usize) -> bool {
+        self.rest()[len..].trim_start_matches([' ', '\t']).starts_with(':')
+    }
+
+    fn number(&self) -> usize {
+        let rest = self.rest();
+        let bytes = rest.as_bytes();
+        let mut end = 0;
+        while end < bytes.len() {
+            let b = bytes[end];
+            let exponent_sign = matches!(b, b'+' | b'-')
+                && end > 0
+                && matches!(bytes[end - 1], b'e' | b'E')
+                && !rest[..end].starts_with("0x")
+                && bytes.get(end + 1).is_some_and(u8::is_ascii_digit);
+            let decimal_point = b == b'.' && bytes.get(end + 1).is_some_and(u8::is_ascii_digit);
+            if b.is_ascii_alphanumeric() || b == b'_' || exponent_sign || decimal_point {
+                end += 1;
+            } else {
+                break;
+            }
+        }
+        end
+    }
+
+    fn word(&mut self) {
+        let rest = self.rest();
+        let len = rest.len() - rest.trim_start_matches(|c: char| self.is_word_char(c)).len();
+        let word = &rest[..len];
+        let grammar = self.grammar;
+
+        let string_prefix = grammar.python_strings
+            && matches!(
+                word.to_ascii_lowercase().as_str(),
+                "r" | "b" | "f" | "u" | "rb" | "br" | "fr" | "rf"
+            );
+        if string_prefix && let Some(end) = self.string(len) {
+            return self.emit("s", end);
+        }
+        if grammar.rust_literals {
+            let literal = match word {
+                "r" | "br" => self.raw_string(len),
+                "b" => self.string(len),
+                _ => None,
+            };
+            if let Some(end) = literal {
+                return self.emit("s", end);
+            }
+        }
+
+        let class = if grammar.keywords.contains(&word) {
+            "k"
+        } else if grammar.constants.contains(&word) {
+            "kc"
+        } else {
+            ""
+        };
+        self.emit(class, len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hl(language: &str, code: &str) -> String {
+        BuiltinHighlighter::new().highlight(language, code).unwrap()
+    }
+
+    #[test]
+    fn python_tokens() {
+        assert_eq!(
+            hl(
+                "py",
+                "@app.route\ndef f(x=1.5e-3):\n    return rb'\\x00' if x else \"\"\"a\n\"b\"\"\"\"\""
+            ),
+            "<span class=\"nd\">@app.route</span>\n<span class=\"k\">def</span> f(x=<span class=\"m\">1.5e-3</span>):\n    \
+             <span class=\"k\">return</span> <span class=\"s\">rb&#39;\\x00&#39;</span> <span class=\"k\">if</span> x \
+             <span class=\"k\">else</span> <span class=\"s\">&quot;&quot;&quot;a\n&quot;b&quot;&quot;&quot;</span>\
+             <span class=\"s\">&quot;&quot;</span>"
+        );
+        assert_eq!(
+            hl("python", "print('it''s')"),
+            "print(<span class=\"s\">&#39;it&#39;</span><span class=\"s\">&#39;s&#39;</span>)"
+        );
+    }
+
+    #[test]
+    fn rust_tokens() {
+        assert_eq!(
+            hl(
+                "rust",
+                "fn f<'a>(s: &'a str) -> char { /* x */ let r = r#\"\"q\"\"#; 'c' } // done"
+            ),
+            "<span class=\"k\">fn</span> f&lt;&#39;a&gt;(s: &amp;&#39;a str) -&gt; char { <span class=\"c\">/* x */</span> \
+             <span class=\"k\">let</span> r = <span class=\"s\">r#&quot;&quot;q&quot;&quot;#</span>; \
+             <span class=\"s\">&#39;c&#39;</span> } <span class=\"c\">// done</span>"
+        );
+        assert_eq!(
+            hl("rs", "0..10"),
+            "<span class=\"m\">0</span>..<span class=\"m\">10</span>"
+        );
+    }
+
+    #[test]
+    fn shell_tokens() {
+        assert_eq!(
+            hl("bash", "for f in $FILES; do echo \"${f}\" # it's\ndone-ish a#b $?"),
+            "<span class=\"k\">for</span> f <span class=\"k\">in</span> <span class=\"nv\">$FILES</span>; \
+             <span class=\"k\">do</span> echo <span class=\"s\">&quot;${f}&quot;</span> <span class=\"c\"># it&#39;s</span>\n\
+             done-ish a#b <span class=\"nv\">$?</span>"
+        );
+    }
+
+    #[test]
+    fn json_and_yaml_keys() {
+        assert_eq!(
+            hl("json", "{\"a\": [1, true, \"x\"], \"b\" : null}"),
+            "{<span class=\"nt\">&quot;a&quot;</span>: [<span class=\"m\">1</span>, <span class=\"kc\">true</span>, \
+             <span class=\"s\">&quot;x&quot;</span>], <span class=\"nt\">&quot;b&quot;</span> : <span class=\"kc\">null</span>}"
+        );
+        assert_eq!(
+            hl(
+                "yaml",
+                "name: it's here # note\nitems:\n  - key two: yes\n  - 'q': 3\nurl: http://x\n"
+            ),
+            "<span class=\"nt\">name</span>: it&#39;s here <span class=\"c\"># note</span>\n\
+             <span class=\"nt\">items</span>:\n  - <span class=\"nt\">key two</span>: <span class=\"kc\">yes</span>\n  \
+             - <span class=\"nt\">&#39;q&#39;</span>: <span class=\"m\">3</span>\n<span class=\"nt\">url</span>: http://x\n"
+        );
+    }
+
+    #[test]
+    fn unknown_languages_are_not_highlighted() {
+        assert!(BuiltinHighlighter::new().highlight("text", "x").is_none());
+        assert!(BuiltinHighlighter::new().supports("YAML"));
+    }
+}
@@ -0,0 +1,33 @@
+//! Syntax highlighting for code and literal blocks.
+//!
+//! Renderers hand the language and text of every code block to the [`Highlighter`] configured on them
+//! (see [`HtmlRenderer::highlighter`](crate::HtmlRenderer::highlighter)). The language comes from the
+//! `code-block` argument or, for `::` literal blocks, from the last `.. highlight::` directive.
+//!
+//! With the `highlight` feature enabled, [`BuiltinHighlighter`] covers a few common languages using
+//! grammars bundled with the crate.
+
+#[cfg(feature = "highlight")]
+mod builtin;
+
+#[cfg(feature = "highlight")]
+pub use builtin::BuiltinHighlighter;
+
+/// Turns source code into HTML markup.
+pub trait Highlighter: Send + Sync {
+    /// Highlight `code` written in `language`.
+    ///
+    /// The returned HTML goes inside `<pre><code>` as is, so every piece of source text in it must
+    /// already be escaped. Return `None` for languages you don't handle; the renderer then escapes the
+    /// code itself.
+    fn highlight(&self, language: &str, code: &str) -> Option<String>;
+}
+
+impl<F> Highlighter for F
+where
+    F: Fn(&str, &str) -> Option<String> + Send + Sync,
+{
+    fn highlight(&self, language: &str, code: &str) -> Option<String> {
+        self(language, code)
+    }
+}
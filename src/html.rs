@@ -0,0 +1,934 @@
+//! Configurable HTML output.
+
+use std::fmt::{self, Write};
+use std::sync::Arc;
+
+use crate::ast::{anchor_id, capitalize, html_escape, make_id, names_heading};
+use crate::events::{Event, prepared_blocks};
+use crate::highlight::Highlighter;
+use crate::render::{RenderOptions, prepare};
+use crate::{Block, Inline, ListKind, ParamKind, ParseError, PyObject, PyObjectKind, PyParam, TableCell};
+
+/// URL schemes allowed in links and images unless [`HtmlRenderer::url_schemes`] says otherwise.
+const DEFAULT_URL_SCHEMES: &[&str] = &["http", "https", "mailto", "ftp"];
+
+/// Directive names rendered as admonition boxes.
+pub(crate) const ADMONITIONS: &[&str] = &["note", "warning", "tip", "caution", "danger", "attention", "important"];
+
+/// Renders blocks to HTML.
+///
+/// All text and attribute values are escaped and link targets are checked against an
+/// [allowlist of URL schemes](Self::url_schemes), so untrusted input cannot inject markup or scripts.
+/// `raw` directive content is dropped unless passed through with
+/// [`RawMode::Passthrough`](crate::RawMode::Passthrough), which is for trusted input only.
+///
+/// The defaults produce compact HTML5 with unprefixed class names; [`html_of`](crate::html_of) and the
+/// [`Display`](std::fmt::Display) impls of [`Block`] and [`Inline`] use them.
+///
+/// ```
+/// use parserst::{HtmlRenderer, parse};
+///
+/// let blocks = parse(".. note::\n\n   Careful.\n").unwrap();
+/// let html = HtmlRenderer::new()
+///     .class_prefix("rst-")
+///     .admonition_title("{title}:")
+///     .pretty(true)
+///     .render(&blocks);
+/// assert_eq!(
+///     html,
+///     "<div class=\"rst-admonition rst-note\">\n  \
+///      <p class=\"rst-admonition-title\">Note:</p>\n  \
+///      <p>Careful.</p>\n\
+///      </div>\n"
+/// );
+/// ```
+#[derive(Clone)]
+pub struct HtmlRenderer {
+    class_prefix: String,
+    xhtml: bool,
+    pretty: bool,
+    heading_offset: u8,
+    admonition_class: String,
+    admonition_title: String,
+    comments: bool,
+    permalinks: bool,
+    url_schemes: Vec<String>,
+    highlighter: Option<Arc<dyn Highlighter>>,
+    options: RenderOptions,
+}
+
+impl Default for HtmlRenderer {
+    fn default() -> Self {
+        Self {
+            class_prefix: String::new(),
+            xhtml: false,
+            pretty: false,
+            heading_offset: 0,
+            admonition_class: "admonition {name}".into(),
+            admonition_title: "{title}".into(),
+            comments: false,
+            permalinks: false,
+            url_schemes: DEFAULT_URL_SCHEMES.iter().map(|scheme| scheme.to_string()).collect(),
+            highlighter: None,
+            options: RenderOptions::default(),
+        }
+    }
+}
+
+impl HtmlRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prepend `prefix` to every class name in the output.
+    pub fn class_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.class_prefix = prefix.into();
+        self
+    }
+
+    /// Close void elements XHTML style (`<img ... />`) instead of HTML5 style (`<img ...>`).
+    pub fn xhtml(mut self, enabled: bool) -> Self {
+        self.xhtml = enabled;
+        self
+    }
+
+    /// Put every block-level element on its own line, indenting nested elements by two spaces.
+    pub fn pretty(mut self, enabled: bool) -> Self {
+        self.pretty = enabled;
+        self
+    }
+
+    /// Shift heading levels down, e.g. an offset of 1 renders top-level titles as `<h2>`.
+    pub fn heading_offset(mut self, offset: u8) -> Self {
+        self.heading_offset = offset;
+        self
+    }
+
+    /// Class list of an admonition's `<div>`; `{name}` is replaced by the directive name.
+    pub fn admonition_class(mut self, template: impl Into<String>) -> Self {
+        self.admonition_class = template.into();
+        self
+    }
+
+    /// Title text of an admonition; `{title}` is the capitalized directive name and `{name}` the name itself.
+    pub fn admonition_title(mut self, template: impl Into<String>) -> Self {
+        self.admonition_title = template.into();
+        self
+    }
+
+    /// Emit comments as `<!-- ... -->` instead of dropping them.
+    pub fn comments(mut self, enabled: bool) -> Self {
+        self.comments = enabled;
+        self
+    }
+
+    /// Append a `¶` permalink to every heading that has an id.
+    pub fn permalinks(mut self, enabled: bool) -> Self {
+        self.permalinks = enabled;
+        self
+    }
+
+    /// Schemes that link targets and image sources may use, replacing the default `http`, `https`,
+    /// `mailto` and `ftp`.
+    ///
+    /// Relative URLs are always allowed. A link to any other scheme, such as `javascript:` or `data:`,
+    /// is rendered as its plain text, and an image from one is left out.
+    pub fn url_schemes<I, S>(mut self, schemes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.url_schemes = schemes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Highlight code and literal blocks that have a language with `highlighter`.
+    pub fn highlighter(mut self, highlighter: impl Highlighter + 'static) -> Self {
+        self.highlighter = Some(Arc::new(highlighter));
+        self
+    }
+
+    /// Apply `options` (raw content handling, conditional directives) when rendering a document.
+    pub fn options(mut self, options: RenderOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Render a document, applying the configured [`RenderOptions`] first.
+    ///
+    /// In compact mode top-level blocks are separated by newlines; pretty output ends with one.
+    pub fn render(&self, blocks: &[Block]) -> String {
+        let blocks = prepare(blocks, &self.options);
+        let mut out = Out::new(self);
+        let mut first = true;
+        for (i, block) in blocks.iter().enumerate() {
+            if names_heading(block, blocks.get(i + 1)) {
+                continue;
+            }
+            if !first && !self.pretty {
+                out.buf.push('\n');
+            }
+            first = false;
+            out.block(block);
+        }
+        if self.pretty && !out.buf.is_empty() {
+            out.buf.push('\n');
+        }
+        out.buf
+    }
+
+    /// Render a document from an [event stream](crate::events), a top-level block at a time.
+    ///
+    /// Each item is the output for the next block; together they are what [`render`](Self::render)
+    /// gives for the blocks the events describe. Errors in the stream are passed through.
+    pub fn render_events<'r, 'a: 'r, I>(&'r self, events: I) -> impl Iterator<Item = Result<String, ParseError>> + 'r
+    where
+        I: IntoIterator<Item = Result<Event<'a>, ParseError>>,
+        I::IntoIter: 'r,
+    {
+        let mut out = Out::new(self);
+        let mut first = true;
+        prepared_blocks(events, &self.options).filter_map(move |item| {
+            let (block, names_heading) = match item {
+                Ok(item) => item,
+                Err(err) => return Some(Err(err)),
+            };
+            if names_heading {
+                return None;
+            }
+            if !first && !self.pretty {
+                out.buf.push('\n');
+            }
+            first = false;
+            out.block(&block);
+            if self.pretty && !out.buf.is_empty() {
+                out.buf.push('\n');
+            }
+            Some(Ok(std::mem::take(&mut out.buf)))
+        })
+    }
+
+    /// Render a single block as it appears in a document, applying the [`RenderOptions`] (so raw
+    /// content is dropped unless passed through).
+    pub fn render_block(&self, block: &Block) -> String {
+        let mut out = Out::new(self);
+        for block in prepare(std::slice::from_ref(block), &self.options).iter() {
+            out.block(block);
+        }
+        out.buf
+    }
+
+    pub fn render_inlines(&self, inlines: &[Inline]) -> String {
+        let mut out = Out::new(self);
+        out.inlines(inlines);
+        out.buf
+    }
+
+    /// Whether `url` is relative or uses one of the allowed [schemes](Self::url_schemes).
+    ///
+    /// Whitespace and control characters are ignored, as browsers do, so `java\tscript:` is still
+    /// recognised as `javascript:`.
+    pub fn allows_url(&self, url: &str) -> bool {
+        let url: String = url
+            .chars()
+            .filter(|c| !c.is_ascii_whitespace() && !c.is_ascii_control())
+            .collect();
+        match url.find([':', '/', '?', '#']) {
+            Some(i) if url[i..].starts_with(':') => self
+                .url_schemes
+                .iter()
+                .any(|scheme| scheme.eq_ignore_ascii_case(&url[..i])),
+            _ => true,
+        }
+    }
+
+    /// `class="..."` with every name in `names` prefixed.
+    fn class(&self, names: &str) -> String {
+        let names: Vec<String> = names
+            .split_whitespace()
+            .map(|name| format!("{}{name}", self.class_prefix))
+            .collect();
+        format!("class=\"{}\"", html_escape(&names.join(" ")))
+    }
+
+    fn void_end(&self) -> &'static str {
+        if self.xhtml { " />" } else { ">" }
+    }
+}
+
+impl fmt::Debug for HtmlRenderer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HtmlRenderer")
+            .field("class_prefix", &self.class_prefix)
+            .field("xhtml", &self.xhtml)
+            .field("pretty", &self.pretty)
+            .field("heading_offset", &self.heading_offset)
+            .field("admonition_class", &self.admonition_class)
+            .field("admonition_title", &self.admonition_title)
+            .field("comments", &self.comments)
+            .field("permalinks", &self.permalinks)
+            .field("url_schemes", &self.url_schemes)
+            .field("highlighter", &self.highlighter.is_some())
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+/// Output buffer that tracks nesting depth for pretty printing, and the default language that
+/// `.. highlight::` sets for literal blocks.
+struct Out<'r> {
+    r: &'r HtmlRenderer,
+    buf: String,
+    depth: usize,
+    language: Option<String>,
+}
+
+impl<'r> Out<'r> {
+    fn new(r: &'r HtmlRenderer) -> Self {
+        Self { r, buf: String::new(), depth: 0, language: None }
+    }
+
+    /// Start a block-level element: on a fresh, indented line when pretty printing.
+    fn line(&mut self) {
+        if self.r.pretty {
+            if !self.buf.is_empty() && !self.buf.ends_with('\n') {
+                self.buf.push('\n');
+            }
+            self.buf.push_str(&"  ".repeat(self.depth));
+        }
+    }
+
+    fn open(&mut self, tag: &str) {
+        self.line();
+        self.buf.push_str(tag);
+        self.depth += 1;
+    }
+
+    fn close(&mut self, name: &str) {
+        self.depth -= 1;
+        self.line();
+        let _ = write!(self.buf, "</{name}>");
+    }
+
+    /// A block-level element holding inline content on a single line.
+    fn leaf(&mut self, open: &str, inlines: &[Inline], name: &str) {
+        self.line();
+        self.buf.push_str(open);
+        self.inlines(inlines);
+        let _ = write!(self.buf, "</{name}>");
+    }
+
+    fn blocks(&mut self, blocks: &[Block]) {
+        for (i, block) in blocks.iter().enumerate() {
+            if !names_heading(block, blocks.get(i + 1)) {
+                self.block(block);
+            }
+        }
+    }
+
+    fn inlines(&mut self, inlines: &[Inline]) {
+        for inline in inlines {
+            self.inline(inline);
+        }
+    }
+
+    fn inline(&mut self, inline: &Inline) {
+        match inline {
+            Inline::Text(t) => self.buf.push_str(&html_escape(t)),
+            Inline::Em(children) => {
+                self.buf.push_str("<em>");
+                self.inlines(children);
+                self.buf.push_str("</em>");
+            }
+            Inline::Strong(children) => {
+                self.buf.push_str("<strong>");
+                self.inlines(children);
+                self.buf.push_str("</strong>");
+            }
+            Inline::Code(t) => {
+                let _ = write!(self.buf, "<code>{}</code>", html_escape(t));
+            }
+            Inline::Link { text, url } if self.r.allows_url(url) => {
+                let _ = write!(self.buf, "<a href=\"{}\">", html_escape(url));
+                self.inlines(text);
+                self.buf.push_str("</a>");
+            }
+            Inline::Link { text, .. } => self.inlines(text),
+            Inline::FootnoteRef(label) => {
+                let _ = write!(
+                    self.buf,
+                    "<a {} href=\"#{}\">[{}]</a>",
+                    self.r.class("footnote-reference"),
+                    footnote_id(label),
+                    html_escape(label)
+                );
+            }
+        }
+    }
+
+    fn block(&mut self, block: &Block) {
+        match block {
+            Block::Heading { level, inlines, id } => {
+                let level = level.saturating_add(self.r.heading_offset).clamp(1, 6);
+                self.line();
+                if id.is_empty() {
+                    let _ = write!(self.buf, "<h{level}>");
+                } else {
+                    let _ = write!(self.buf, "<h{level} id=\"{}\">", html_escape(id));
+                }
+                self.inlines(inlines);
+                if self.r.permalinks && !id.is_empty() {
+                    let _ = write!(
+                        self.buf,
+                        "<a {} href=\"#{}\" title=\"Link to this heading\">\u{b6}</a>",
+                        self.r.class("headerlink"),
+                        html_escape(id)
+                    );
+                }
+                let _ = write!(self.buf, "</h{level}>");
+            }
+            Block::Paragraph(inlines) => self.leaf("<p>", inlines, "p"),
+            Block::List { kind, items } => {
+                let tag = match kind {
+                    ListKind::Unordered => "ul",
+                    ListKind::Ordered => "ol",
+                };
+                self.open(&format!("<{tag}>"));
+                for item in items {
+                    self.leaf("<li>", item, "li");
+                }
+                self.close(tag);
+            }
+            Block::CodeBlock(code) => self.pre(None, &html_escape(code)),
+            Block::LiteralBlock(code) => {
+                let language = self.language.clone();
+                let body = self.highlight(language.as_deref(), code);
+                self.pre(language.as_deref(), &body);
+            }
+            Block::Quote(children) => {
+                self.open("<blockquote>");
+                self.blocks(children);
+                self.close("blockquote");
+            }
+            Block::Directive { name, argument, content } => self.directive(name, argument, content),
+            Block::Table { headers, rows, .. } => {
+                self.open("<table>");
+                for (section, rows, cell) in [("thead", headers, "th"), ("tbody", rows, "td")] {
+                    if rows.is_empty() {
+                        continue;
+                    }
+                    self.open(&format!("<{section}>"));
+                    for row in rows {
+                        self.table_row(row, cell);
+                    }
+                    self.close(section);
+                }
+                self.close("table");
+            }
+            Block::Comment(children) => {
+                if self.r.comments {
+                    let text = crate::rst::write_blocks(children, None).replace("--", "- -");
+                    self.line();
+                    let _ = write!(self.buf, "<!-- {text} -->");
+                }
+            }
+            Block::FieldList { fields } => {
+                self.open("<dl>");
+                for field in fields {
+                    let term = if field.argument.is_empty() {
+                        field.name.clone()
+                    } else {
+                        format!("{} {}", field.name, field.argument)
+                    };
+                    self.line();
+                    let _ = write!(self.buf, "<dt>{}</dt>", html_escape(&term));
+                    self.open("<dd>");
+                    self.blocks(&field.body);
+                    self.close("dd");
+                }
+                self.close("dl");
+            }
+            Block::OptionList { items } => {
+                self.open(&format!("<dl {}>", self.r.class("option-list")));
+                for item in items {
+                    let options: Vec<String> = item
+                        .options
+                        .iter()
+                        .map(|option| format!("<kbd>{}</kbd>", html_escape(option)))
+                        .collect();
+                    self.line();
+                    let _ = write!(self.buf, "<dt>{}</dt>", options.join(", "));
+                    self.open("<dd>");
+                    self.blocks(&item.body);
+                    self.close("dd");
+                }
+                self.close("dl");
+            }
+            Block::Raw { formats, content } => {
+                if formats.iter().any(|format| format == "html") {
+                    self.line();
+                    self.buf.push_str(content);
+                }
+            }
+            Block::Target { name } => {
+                self.line();
+                let _ = write!(self.buf, "<span id=\"{}\"></span>", html_escape(&make_id(name)));
+            }
+            Block::PyObject(obj) => self.py_object(obj),
+            Block::Footnote { label, content } => {
+                self.open(&format!(
+                    "<aside {} id=\"{}\">",
+                    self.r.class("footnote"),
+                    footnote_id(label)
+                ));
+                self.line();
+                let _ = write!(
+                    self.buf,
+                    "<span {}>[{}]</span>",
+                    self.r.class("label"),
+                    html_escape(label)
+                );
+                self.blocks(content);
+                self.close("aside");
+            }
+        }
+    }
+
+    /// A `<pre>` block around already escaped `body`; its text is never indented, even when pretty printing.
+    fn pre(&mut self, language: Option<&str>, body: &str) {
+        let lang_attr = match language {
+            Some(language) => format!(" {}", self.r.class(&format!("language-{language}"))),
+            None => String::new(),
+        };
+        self.line();
+        let _ = write!(self.buf, "<pre><code{lang_attr}>{body}</code></pre>");
+    }
+
+    /// `code` as HTML, run through the highlighter when it has a language.
+    fn highlight(&self, language: Option<&str>, code: &str) -> String {
+        language
+            .zip(self.r.highlighter.as_ref())
+            .and_then(|(language, highlighter)| highlighter.highlight(language, code))
+            .unwrap_or_else(|| html_escape(code))
+    }
+
+    /// Render one table row, emitting `colspan`/`rowspan` for cells that cover more than one slot
+    fn table_row(&mut self, row: &[TableCell], tag: &str) {
+        self.open("<tr>");
+        for cell in row {
+            let mut open = format!("<{tag}");
+            if cell.colspan > 1 {
+                let _ = write!(open, " colspan=\"{}\"", cell.colspan);
+            }
+            if cell.rowspan > 1 {
+                let _ = write!(open, " rowspan=\"{}\"", cell.rowspan);
+            }
+            open.push('>');
+            match cell.as_inlines() {
+                Some(inlines) => self.leaf(&open, inlines, tag),
+                None => {
+                    self.open(&open);
+                    self.blocks(&cell.content);
+                    self.close(tag);
+                }
+            }
+        }
+        self.close("tr");
+    }
+
+    /// Render directive to HTML based on directive type
+    fn directive(&mut self, name: &str, argument: &str, content: &[Block]) {
+        match name {
+            _ if ADMONITIONS.contains(&name) => {
+                let class = self.r.admonition_class.replace("{name}", name);
+                let title = self
+                    .r
+                    .admonition_title
+                    .replace("{title}", &capitalize(name))
+                    .replace("{name}", name);
+                self.open(&format!("<div {}>", self.r.class(&class)));
+                self.line();
+                let _ = write!(
+                    self.buf,
+                    "<p {}>{}</p>",
+                    self.r.class("admonition-title"),
+                    html_escape(&title)
+                );
+                self.blocks(content);
+                self.close("div");
+            }
+            "code-block" | "code" => {
+                let language = if argument.is_empty() { self.language.clone() } else { Some(argument.to_string()) };
+                let mut body = String::new();
+                for block in content {
+                    if let Block::LiteralBlock(text) = block {
+                        body.push_str(&self.highlight(language.as_deref(), text));
+                    } else if let Block::Paragraph(inlines) = block {
+                        body.push_str(&self.r.render_inlines(inlines));
+                    }
+                }
+                self.pre(language.as_deref(), &body);
+            }
+            "highlight" => {
+                self.language = Some(argument)
+                    .filter(|language| !language.is_empty() && *language != "none")
+                    .map(str::to_string);
+            }
+            "only" | "ifconfig" => self.blocks(content),
+            "image" if !self.r.allows_url(argument) => {}
+            "image" => {
+                let alt = if content.is_empty() { "" } else { "image" };
+                self.line();
+                let _ = write!(
+                    self.buf,
+                    "<img src=\"{}\" alt=\"{alt}\"{}",
+                    html_escape(argument),
+                    self.r.void_end()
+                );
+            }
+            _ => {
+                self.open(&format!(
+                    "<div {}>",
+                    self.r.class(&format!("directive directive-{name}"))
+                ));
+                if !argument.is_empty() {
+                    self.line();
+                    let _ = write!(self.buf, "<p><code>{}</code></p>", html_escape(argument));
+                }
+                self.blocks(content);
+                self.close("div");
+            }
+        }
+    }
+
+    fn param(&mut self, param: &PyParam) {
+        let stars = match param.kind {
+            ParamKind::VarPositional => "*",
+            ParamKind::VarKeyword => "**",
+            _ => "",
+        };
+        let r = self.r;
+        let _ = write!(self.buf, "<em {}>", r.class("sig-param"));
+        if !stars.is_empty() {
+            let _ = write!(self.buf, "<span {}>{stars}</span>", r.class("o"));
+        }
+        let _ = write!(self.buf, "<span {}>{}</span>", r.class("n"), html_escape(&param.name));
+        if let Some(annotation) = &param.annotation {
+            let _ = write!(
+                self.buf,
+                "<span {}>:</span> <span {}>{}</span>",
+                r.class("p"),
+                r.class("n"),
+                html_escape(annotation)
+            );
+        }
+        if let Some(default) = &param.default {
+            let op = if param.annotation.is_some() { " = " } else { "=" };
+            let _ = write!(
+                self.buf,
+                "<span {}>{op}</span><span {}>{}</span>",
+                r.class("o"),
+                r.class("default_value"),
+                html_escape(default)
+            );
+        }
+        self.buf.push_str("</em>");
+    }
+
+    /// Render a [`PyObject`] the way Sphinx lays out object descriptions.
+    fn py_object(&mut self, obj: &PyObject) {
+        let r = self.r;
+        let sig = &obj.signature;
+        if obj.kind == PyObjectKind::CurrentModule {
+            return;
+        }
+        if obj.kind == PyObjectKind::Module {
+            self.line();
+            let _ = write!(self.buf, "<span id=\"{}\"></span>", html_escape(&anchor_id(obj)));
+            self.blocks(&obj.content);
+            return;
+        }
+
+        self.open(&format!("<dl {}>", r.class(&format!("py {}", obj.kind.name()))));
+        self.line();
+        let _ = write!(
+            self.buf,
+            "<dt {} id=\"{}\">",
+            r.class("sig sig-object py"),
+            html_escape(&anchor_id(obj))
+        );
+
+        for decorator in &sig.decorators {
+            let _ = write!(
+                self.buf,
+                "<em {}>@{}</em> ",
+                r.class("property"),
+                html_escape(decorator)
+            );
+        }
+        let mut properties: Vec<&str> = Vec::new();
+        if obj.option("async").is_some() {
+            properties.push("async");
+        }
+        if obj.option("abstractmethod").is_some() {
+            properties.push("abstract");
+        }
+        properties.extend(match obj.kind {
+            PyObjectKind::Class => Some("class"),
+            PyObjectKind::Exception => Some("exception"),
+            PyObjectKind::ClassMethod => Some("classmethod"),
+            PyObjectKind::StaticMethod => Some("static"),
+            PyObjectKind::Property => Some("property"),
+            _ => None,
+        });
+        if obj.option("classmethod").is_some() {
+            properties.push("classmethod");
+        }
+        if obj.option("staticmethod").is_some() {
+            properties.push("static");
+        }
+        for property in properties {
+            let _ = write!(self.buf, "<em {}>{property} </em>", r.class("property"));
+        }
+
+        if !sig.prefix.is_empty() {
+            let _ = write!(
+                self.buf,
+                "<span {}>{}</span>",
+                r.class("sig-prename descclassname"),
+                html_escape(&sig.prefix)
+            );
+        }
+        let _ = write!(
+            self.buf,
+            "<span {}>{}</span>",
+            r.class("sig-name descname"),
+            html_escape(&sig.name)
+        );
+
+        match &sig.params {
+            Some(params) => {
+                let _ = write!(self.buf, "<span {}>(</span>", r.class("sig-paren"));
+                let mut previous: Option<ParamKind> = None;
+                for (i, param) in params.iter().enumerate() {
+                    if i > 0 {
+                        self.buf.push_str(", ");
+                    }
+                    if param.kind == ParamKind::KeywordOnly
+                        && !matches!(previous, Some(ParamKind::KeywordOnly | ParamKind::VarPositional))
+                    {
+                        let _ = write!(
+                            self.buf,
+                            "<em {}><span {}>*</span></em>, ",
+                            r.class("sig-param"),
+                            r.class("o")
+                        );
+                    }
+                    self.param(param);
+                    if param.kind == ParamKind::PositionalOnly
+                        && params
+                            .get(i + 1)
+                            .is_none_or(|next| next.kind != ParamKind::PositionalOnly)
+                    {
+                        let _ = write!(
+                            self.buf,
+                            ", <em {}><span {}>/</span></em>",
+                            r.class("sig-param"),
+                            r.class("o")
+                        );
+                    }
+                    previous = Some(param.kind);
+                }
+                let _ = write!(self.buf, "<span {}>)</span>", r.class("sig-paren"));
+            }
+            None if obj.kind.takes_arguments() && obj.kind != PyObjectKind::Class => {
+                let _ = write!(self.buf, "<span {}>()</span>", r.class("sig-paren"));
+            }
+            None => {}
+        }
+
+        if let Some(ret) = &sig.return_annotation {
+            let _ = write!(
+                self.buf,
+                " <span {}><span {}>&#x2192;</span> <span {}>{}</span></span>",
+                r.class("sig-return"),
+                r.class("sig-return-icon"),
+                r.class("sig-return-typehint"),
+                html_escape(ret)
+            );
+        }
+        if let Some(ty) = obj.option("type") {
+            let _ = write!(
+                self.buf,
+                "<em {}><span {}>:</span> {}</em>",
+                r.class("property"),
+                r.class("p"),
+                html_escape(ty)
+            );
+        }
+        if let Some(value) = obj.option("value") {
+            let _ = write!(self.buf, "<em {}> = {}</em>", r.class("property"), html_escape(value));
+        }
+
+        self.buf.push_str("</dt>");
+        self.open("<dd>");
+        self.blocks(&obj.content);
+        self.close("dd");
+        self.close("dl");
+    }
+}
+
+/// The anchor id of the footnote labelled `label`.
+fn footnote_id(label: &str) -> String {
+    format!(
+        "footnote-{}",
+        html_escape(&label.replace('#', "auto").replace('*', "symbol"))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{html_of, parse};
+
+    const DOC: &str =
+        "Title\n=====\n\n.. tip::\n\n   Use *this*.\n\n.. a comment\n\n.. image:: logo.png\n\n- one\n- two\n";
+
+    #[test]
+    fn defaults_match_html_of() {
+        let blocks = parse(DOC).unwrap();
+        assert_eq!(HtmlRenderer::new().render(&blocks), html_of(DOC));
+        assert_eq!(
+            html_of(DOC),
+            "<h1 id=\"title\">Title</h1>\n\
+             <div class=\"admonition tip\"><p class=\"admonition-title\">Tip</p><p>Use <em>this</em>.</p></div>\n\
+             \n\
+             <img src=\"logo.png\" alt=\"\">\n\
+             <ul><li>one</li><li>two</li></ul>"
+        );
+    }
+
+    #[test]
+    fn pretty_output_indents_nested_elements() {
+        let html = HtmlRenderer::new().pretty(true).render(&parse(DOC).unwrap());
+        assert_eq!(
+            html,
+            "<h1 id=\"title\">Title</h1>\n\
+             <div class=\"admonition tip\">\n  \
+               <p class=\"admonition-title\">Tip</p>\n  \
+               <p>Use <em>this</em>.</p>\n\
+             </div>\n\
+             <img src=\"logo.png\" alt=\"\">\n\
+             <ul>\n  \
+               <li>one</li>\n  \
+               <li>two</li>\n\
+             </ul>\n"
+        );
+    }
+
+    #[test]
+    fn pretty_output_keeps_preformatted_text_intact() {
+        let blocks = parse(".. note::\n\n   ::\n\n      a\n        b\n").unwrap();
+        let html = HtmlRenderer::new().pretty(true).render(&blocks);
+        assert!(html.contains("\n  <pre><code>a\n  b</code></pre>\n"), "{html}");
+    }
+
+    #[test]
+    fn xhtml_closes_void_elements() {
+        let blocks = parse(".. image:: logo.png\n").unwrap();
+        assert_eq!(
+            HtmlRenderer::new().xhtml(true).render(&blocks),
+            "<img src=\"logo.png\" alt=\"\" />"
+        );
+    }
+
+    #[test]
+    fn class_prefix_applies_to_every_class() {
+        let blocks =
+            parse(".. warning::\n\n   Hot.\n\n.. code-block:: rust\n\n   fn main() {}\n\n.. custom:: arg\n").unwrap();
+        let html = HtmlRenderer::new().class_prefix("x-").render(&blocks);
+        assert!(html.contains("<div class=\"x-admonition x-warning\"><p class=\"x-admonition-title\">"));
+        assert!(html.contains("<code class=\"x-language-rust\">"));
+        assert!(html.contains("<div class=\"x-directive x-directive-custom\">"));
+        assert!(!html.contains("class=\"admonition"));
+    }
+
+    #[test]
+    fn heading_offset_shifts_and_clamps_levels() {
+        let blocks = parse("Title\n=====\n\nSection\n-------\n").unwrap();
+        assert_eq!(
+            HtmlRenderer::new().heading_offset(1).render(&blocks),
+            "<h2 id=\"title\">Title</h2>\n<h3 id=\"section\">Section</h3>"
+        );
+        assert_eq!(
+            HtmlRenderer::new().heading_offset(9).render(&blocks),
+            "<h6 id=\"title\">Title</h6>\n<h6 id=\"section\">Section</h6>"
+        );
+    }
+
+    #[test]
+    fn admonition_class_and_title_are_templates() {
+        let blocks = parse(".. danger::\n\n   Stop.\n").unwrap();
+        let html = HtmlRenderer::new()
+            .admonition_class("callout callout-{name}")
+            .admonition_title("{title} ({name})")
+            .render(&blocks);
+        assert_eq!(
+            html,
+            "<div class=\"callout callout-danger\"><p class=\"admonition-title\">Danger (danger)</p><p>Stop.</p></div>"
+        );
+    }
+
+    #[test]
+    fn headings_carry_ids_and_optional_permalinks() {
+        let blocks = parse(".. _start:\n\nIntro <1>\n=========\n\n.. _note-anchor:\n\nText.\n").unwrap();
+        assert_eq!(
+            HtmlRenderer::new().render(&blocks),
+            "<h1 id=\"start\">Intro &lt;1&gt;</h1>\n<span id=\"note-anchor\"></span>\n<p>Text.</p>"
+        );
+        let html = HtmlRenderer::new().permalinks(true).class_prefix("x-").render(&blocks);
+        assert!(html.starts_with(
+            "<h1 id=\"start\">Intro &lt;1&gt;<a class=\"x-headerlink\" href=\"#start\" \
+             title=\"Link to this heading\">\u{b6}</a></h1>\n"
+        ));
+    }
+
+    #[test]
+    fn highlight_directive_sets_language_of_later_literal_blocks() {
+        let doc = "::\n\n   a<b\n\n.. highlight:: python\n\nCode::\n\n   x\n\n\
+                   .. code-block:: rust\n\n   y\n\n.. highlight:: none\n\n::\n\n   z\n";
+        let blocks = parse(doc).unwrap();
+        let upper =
+            |language: &str, code: &str| (language != "rust").then(|| format!("<b>{}</b>", code.to_uppercase()));
+        assert_eq!(
+            HtmlRenderer::new().highlighter(upper).render(&blocks),
+            "<pre><code>a&lt;b</code></pre>\n\n<p>Code:</p>\n\
+             <pre><code class=\"language-python\"><b>X</b></code></pre>\n\
+             <pre><code class=\"language-rust\">y</code></pre>\n\n<pre><code>z</code></pre>"
+        );
+    }
+
+    #[cfg(feature = "highlight")]
+    #[test]
+    fn builtin_highlighter_marks_up_code_blocks() {
+        let blocks = parse(".. code-block:: json\n\n   {\"a\": 1}\n").unwrap();
+        let html = HtmlRenderer::new()
+            .highlighter(crate::BuiltinHighlighter::new())
+            .render(&blocks);
+        assert_eq!(
+            html,
+            "<pre><code class=\"language-json\">{<span class=\"nt\">&quot;a&quot;</span>: \
+             <span class=\"m\">1</span>}</code></pre>"
+        );
+    }
+
+    #[test]
+    fn comments_are_emitted_when_enabled() {
+        let blocks = parse(".. keep -- me\n\nText.\n").unwrap();
+        assert_eq!(HtmlRenderer::new().render(&blocks), "\n<p>Text.</p>");
+        assert_eq!(
+            HtmlRenderer::new().comments(true).render(&blocks),
+            "<!-- keep - - me -->\n<p>Text.</p>"
+        );
+    }
+}
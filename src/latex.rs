@@ -0,0 +1,637 @@
+//! LaTeX output for PDF builds.
+
+use std::cell::{Cell, RefCell};
+
+use crate::ast::{capitalize, into_owned, make_id, names_heading};
+use crate::html::ADMONITIONS;
+use crate::render::{RenderOptions, prepare};
+use crate::textutil::place_cells;
+use crate::visit::{Visitor, walk_block};
+use crate::{Block, Field, Inline, ListKind, PyObject, PyObjectKind, TableCell};
+
+/// Sectioning commands from the top level down.
+const SECTIONS: &[&str] = &[
+    "chapter",
+    "section",
+    "subsection",
+    "subsubsection",
+    "paragraph",
+    "subparagraph",
+];
+
+/// Environment admonitions use unless [`LatexRenderer::admonition_environment`] maps them elsewhere;
+/// [`LatexRenderer::preamble`] defines it.
+const DEFAULT_ADMONITION_ENVIRONMENT: &str = "rstadmonition";
+
+/// Languages `listings` knows, by the names reST documents use for them.
+const LISTINGS_LANGUAGES: &[(&str, &str)] = &[
+    ("python", "Python"),
+    ("py", "Python"),
+    ("python3", "Python"),
+    ("c", "C"),
+    ("cpp", "C++"),
+    ("c++", "C++"),
+    ("java", "Java"),
+    ("sh", "bash"),
+    ("bash", "bash"),
+    ("shell", "bash"),
+    ("console", "bash"),
+    ("sql", "SQL"),
+    ("html", "HTML"),
+    ("xml", "XML"),
+    ("ruby", "Ruby"),
+    ("perl", "Perl"),
+    ("make", "make"),
+    ("tex", "TeX"),
+    ("latex", "TeX"),
+];
+
+/// Renders blocks to LaTeX source.
+///
+/// Headings become sectioning commands at their depth, code goes in `verbatim` or `lstlisting`
+/// environments, tables in `tabular` or `longtable` with `\multicolumn`/`\multirow` for spans, and
+/// footnotes are moved to `\footnote` at their reference. Admonitions use an environment taking the
+/// title as its argument, configurable per kind.
+///
+/// By default the output is a body fragment; [`document`](Self::document) wraps it in a complete
+/// document whose preamble loads the packages the body uses. Compiling the result is left to a TeX
+/// distribution.
+///
+/// ```
+/// use parserst::{LatexRenderer, parse};
+///
+/// let blocks = parse("Setup\n=====\n\nCosts 5% of *R&D*.\n").unwrap();
+/// assert_eq!(
+///     LatexRenderer::new().render(&blocks),
+///     "\\section{Setup}\n\\label{setup}\n\nCosts 5\\% of \\emph{R\\&D}.\n"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct LatexRenderer {
+    document: bool,
+    document_class: String,
+    chapters: bool,
+    long_tables: bool,
+    listings: bool,
+    admonition_environments: Vec<(String, String)>,
+    options: RenderOptions,
+}
+
+impl Default for LatexRenderer {
+    fn default() -> Self {
+        Self {
+            document: false,
+            document_class: "article".to_string(),
+            chapters: false,
+            long_tables: false,
+            listings: true,
+            admonition_environments: Vec::new(),
+            options: RenderOptions::default(),
+        }
+    }
+}
+
+impl LatexRenderer {
+    /// Defaults to an `article` body fragment with `\section` at the top level.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Produce a complete document with a preamble instead of a body fragment.
+    pub fn document(mut self, document: bool) -> Self {
+        self.document = document;
+        self
+    }
+
+    /// The `\documentclass` of a complete document.
+    pub fn document_class(mut self, class: impl Into<String>) -> Self {
+        self.document_class = class.into();
+        self
+    }
+
+    /// Start top-level headings at `\chapter`, for the `report` and `book` classes.
+    pub fn chapters(mut self, chapters: bool) -> Self {
+        self.chapters = chapters;
+        self
+    }
+
+    /// Use `longtable`, which can break across pages, instead of `tabular`.
+    pub fn long_tables(mut self, long_tables: bool) -> Self {
+        self.long_tables = long_tables;
+        self
+    }
+
+    /// Put code with a known language in `lstlisting` environments; otherwise all code is `verbatim`.
+    pub fn listings(mut self, listings: bool) -> Self {
+        self.listings = listings;
+        self
+    }
+
+    /// Render `name` admonitions (`note`, `warning`, ...) in the environment `env`, which takes the
+    /// admonition's title as its one argument.
+    pub fn admonition_environment(mut self, name: impl Into<String>, env: impl Into<String>) -> Self {
+        let name = name.into();
+        self.admonition_environments.retain(|(n, _)| *n != name);
+        self.admonition_environments.push((name, env.into()));
+        self
+    }
+
+    /// Apply `options` (raw content handling, conditional directives) when rendering a document.
+    pub fn options(mut self, options: RenderOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// The packages and definitions rendered bodies rely on, for including fragments in a document of
+    /// your own.
+    pub fn preamble(&self) -> String {
+        let mut out = String::from(
+            "\\usepackage[utf8]{inputenc}\n\\usepackage[T1]{fontenc}\n\\usepackage{graphicx}\n\
+             \\usepackage{longtable}\n\\usepackage{multirow}\n\\usepackage{alltt}\n",
+        );
+        if self.listings {
+            out.push_str("\\usepackage{listings}\n");
+        }
+        out.push_str("\\usepackage{hyperref}\n");
+        out.push_str(&format!(
+            "\\newenvironment{{{DEFAULT_ADMONITION_ENVIRONMENT}}}[1]{{\\begin{{quote}}\\textbf{{#1}}\\par}}{{\\end{{quote}}}}\n"
+        ));
+        out
+    }
+
+    /// Render a document: the body, wrapped in a complete document when [`document`](Self::document)
+    /// is set.
+    pub fn render(&self, blocks: &[Block]) -> String {
+        let blocks = prepare(blocks, &self.options);
+        let mut body = Out::new(self, &blocks).blocks(&blocks);
+        if !body.is_empty() {
+            body.push('\n');
+        }
+        if self.document {
+            format!(
+                "\\documentclass{{{}}}\n{}\n\\begin{{document}}\n\n{body}\n\\end{{document}}\n",
+                self.document_class,
+                self.preamble()
+            )
+        } else {
+            body
+        }
+    }
+
+    /// Render inline content; footnote references stay as their labels.
+    pub fn render_inlines(&self, inlines: &[Inline]) -> String {
+        Out::new(self, &[]).inlines(inlines)
+    }
+
+    fn admonition_env(&self, name: &str) -> &str {
+        self.admonition_environments
+            .iter()
+            .find(|(n, _)| n == name)
+            .map_or(DEFAULT_ADMONITION_ENVIRONMENT, |(_, env)| env)
+    }
+}
+
+/// Footnote definitions in document order.
+#[derive(Default)]
+struct Footnotes(Vec<(String, Vec<Block<'static>>)>);
+
+impl Visitor for Footnotes {
+    fn visit_block(&mut self, block: &Block) {
+        if let Block::Footnote { label, content } = block {
+            self.0.push((label.clone(), into_owned(content.clone())));
+        }
+        walk_block(self, block);
+    }
+}
+
+/// Rendering state: the footnotes to move to their references and the current `highlight` language.
+struct Out<'r> {
+    r: &'r LatexRenderer,
+    footnotes: Vec<(String, Vec<Block<'static>>)>,
+    /// How many `[#]_` and `[*]_` references have taken the next footnote with that label.
+    taken: [Cell<usize>; 2],
+    in_footnote: Cell<bool>,
+    language: RefCell<Option<String>>,
+}
+
+impl<'r> Out<'r> {
+    fn new(r: &'r LatexRenderer, blocks: &[Block]) -> Self {
+        let mut footnotes = Footnotes::default();
+        footnotes.visit_blocks(blocks);
+        Self {
+            r,
+            footnotes: footnotes.0,
+            taken: Default::default(),
+            in_footnote: Cell::new(false),
+            language: RefCell::new(None),
+        }
+    }
+
+    fn blocks(&self, blocks: &[Block]) -> String {
+        let mut parts = Vec::new();
+        for (i, block) in blocks.iter().enumerate() {
+            if names_heading(block, blocks.get(i + 1)) {
+                continue;
+            }
+            let text = self.block(block);
+            if !text.is_empty() {
+                parts.push(text);
+            }
+        }
+        parts.join("\n\n")
+    }
+
+    fn block(&self, block: &Block) -> String {
+        match block {
+            Block::Heading { level, inlines, id } => {
+                let depth = usize::from(level.max(&1) - 1) + usize::from(!self.r.chapters);
+                let command = SECTIONS[depth.min(SECTIONS.len() - 1)];
+                let heading = format!("\\{command}{{{}}}", self.inlines(inlines));
+                if id.is_empty() { heading } else { format!("{heading}\n\\label{{{id}}}") }
+            }
+            Block::Paragraph(inlines) => self.inlines(inlines),
+            Block::List { kind, items } => {
+                let env = match kind {
+                    ListKind::Unordered => "itemize",
+                    ListKind::Ordered => "enumerate",
+                };
+                let items: Vec<String> = items
+                    .iter()
+                    .map(|item| format!("\\item {}", self.inlines(item)))
+                    .collect();
+                environment(env, &items.join("\n"))
+            }
+            Block::CodeBlock(code) => self.code(None, code),
+            Block::LiteralBlock(code) => self.code(self.language.borrow().as_deref(), code),
+            Block::Quote(children) => environment("quote", &self.blocks(children)),
+            Block::Directive { name, argument, content } => self.directive(name, argument, content),
+            Block::Table { headers, rows, .. } => self.table(headers, rows),
+            Block::Comment(_) | Block::Footnote { .. } => String::new(),
+            Block::FieldList { fields } => {
+                let items: Vec<String> = fields.iter().map(|field| self.field(field)).collect();
+                environment("description", &items.join("\n"))
+            }
+            Block::OptionList { items } => {
+                let items: Vec<String> = items
+                    .iter()
+                    .map(|item| {
+                        let options: Vec<String> = item
+                            .options
+                            .iter()
+                            .map(|option| format!("\\texttt{{{}}}", escape(option)))
+                            .collect();
+                        format!("\\item[{{{}}}] {}", options.join(", "), self.blocks(&item.body))
+                    })
+                    .collect();
+                environment("description", &items.join("\n"))
+            }
+            Block::Raw { formats, content } if formats.iter().any(|format| format == "latex") => content.clone(),
+            Block::Raw { .. } => String::new(),
+            Block::Target { name } => format!("\\phantomsection\\label{{{}}}", make_id(name)),
+            Block::PyObject(obj) => self.py_object(obj),
+        }
+    }
+
+    fn directive(&self, name: &str, argument: &str, content: &[Block]) -> String {
+        match name {
+            _ if ADMONITIONS.contains(&name) => {
+                let mut body = escape(argument);
+                let rest = self.blocks(content);
+                if !body.is_empty() && !rest.is_empty() {
+                    body.push_str("\n\n");
+                }
+                body.push_str(&rest);
+                let env = self.r.admonition_env(name);
+                format!("\\begin{{{env}}}{{{}}}\n{body}\n\\end{{{env}}}", capitalize(name))
+            }
+            "code-block" | "code" => {
+                let language =
+                    if argument.is_empty() { self.language.borrow().clone() } else { Some(argument.to_string()) };
+                let code: Vec<&str> = content
+                    .iter()
+                    .filter_map(|block| match block {
+                        Block::LiteralBlock(text) => Some(text.as_ref()),
+                        _ => None,
+                    })
+                    .collect();
+                self.code(language.as_deref(), &code.join("\n\n"))
+            }
+            "highlight" => {
+                *self.language.borrow_mut() = Some(argument)
+                    .filter(|language| !language.is_empty() && *language != "none")
+                    .map(str::to_string);
+                String::new()
+            }
+            "only" | "ifconfig" => self.blocks(content),
+            "image" => format!("\\includegraphics{{{}}}", argument.replace(['{', '}'], "")),
+            _ => {
+                let mut head = format!("\\textbf{{{}:}}", escape(&capitalize(name)));
+                if !argument.is_empty() {
+                    head.push_str(&format!(" \\texttt{{{}}}", escape(argument)));
+                }
+                match self.blocks(content) {
+                    body if body.is_empty() => head,
+                    body => format!("{head}\n\n{body}"),
+                }
+            }
+        }
+    }
+
+    /// Code in an `lstlisting` environment when `listings` knows its language, otherwise `verbatim`.
+    ///
+    /// Code holding the line that would end its environment is escaped into `alltt` instead.
+    fn code(&self, language: Option<&str>, code: &str) -> String {
+        let listing = language.filter(|_| self.r.listings).and_then(|language| {
+            LISTINGS_LANGUAGES
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(language))
+        });
+        let env = if listing.is_some() { "lstlisting" } else { "verbatim" };
+        if code.contains(&format!("\\end{{{env}}}")) {
+            return environment("alltt", &escape(code));
+        }
+        match listing {
+            Some((_, language)) => format!("\\begin{{lstlisting}}[language={language}]\n{code}\n\\end{{lstlisting}}"),
+            None => environment("verbatim", code),
+        }
+    }
+
+    fn field(&self, field: &Field) -> String {
+        let term = if field.argument.is_empty() {
+            escape(&field.name)
+        } else {
+            format!("{} {}", escape(&field.name), escape(&field.argument))
+        };
+        format!("\\item[{{{term}}}] {}", self.blocks(&field.body))
+    }
+
+    fn py_object(&self, obj: &PyObject) -> String {
+        let body = self.blocks(&obj.content);
+        match obj.kind {
+            PyObjectKind::CurrentModule => String::new(),
+            PyObjectKind::Module => body,
+            _ => {
+                let item = format!("\\item[{{\\texttt{{{}}}}}] {body}", escape(&obj.signature.to_string()));
+                environment("description", &item)
+            }
+        }
+    }
+
+    /// A `tabular` or `longtable` with one `l` column per grid column; spanning cells use
+    /// `\multicolumn` and `\multirow`, and rules skip the columns a row span continues through.
+    fn table(&self, headers: &[Vec<TableCell>], rows: &[Vec<TableCell>]) -> String {
+        let all: Vec<&Vec<TableCell>> = headers.iter().chain(rows).collect();
+        let (slots, columns) = place_cells(&all, |blocks| self.blocks(blocks));
+        let env = if self.r.long_tables { "longtable" } else { "tabular" };
+
+        let mut lines = vec![
+            format!("\\begin{{{env}}}{{|{}}}", "l|".repeat(columns)),
+            "\\hline".to_string(),
+        ];
+        for y in 0..all.len() {
+            let covering = |col: usize| {
+                slots
+                    .iter()
+                    .find(|s| s.col <= col && col < s.col + s.colspan && s.row <= y && y < s.row + s.rowspan)
+            };
+            let mut cells = Vec::new();
+            let mut col = 0;
+            while let Some(slot) = covering(col) {
+                let mut text = if slot.row == y { slot.lines.join(" ") } else { String::new() };
+                if slot.row == y && slot.rowspan > 1 {
+                    text = format!("\\multirow{{{}}}{{*}}{{{text}}}", slot.rowspan);
+                }
+                if slot.colspan > 1 {
+                    let border = if slot.col == 0 { "|" } else { "" };
+                    text = format!("\\multicolumn{{{}}}{{{border}l|}}{{{text}}}", slot.colspan);
+                }
+                cells.push(text);
+                col = slot.col + slot.colspan;
+            }
+            lines.push(format!("{} \\\\", cells.join(" & ")));
+
+            let ends: Vec<bool> = (0..columns)
+                .map(|col| covering(col).is_none_or(|s| s.row + s.rowspan == y + 1))
+                .collect();
+            if ends.iter().all(|&end| end) {
+                lines.push("\\hline".to_string());
+            } else {
+                let mut start = None;
+                for (col, &end) in ends.iter().chain([&false]).enumerate() {
+                    match (end, start) {
+                        (true, None) => start = Some(col),
+                        (false, Some(first)) => {
+                            lines.push(format!("\\cline{{{}-{col}}}", first + 1));
+                            start = None;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            if y + 1 == headers.len() && headers.len() < all.len() {
+                lines.push("\\hline".to_string());
+                if self.r.long_tables {
+                    lines.push("\\endhead".to_string());
+                }
+            }
+        }
+        lines.push(format!("\\end{{{env}}}"));
+        lines.join("\n")
+    }
+
+    fn inlines(&self, inlines: &[Inline]) -> String {
+        inlines.iter().map(|inline| self.inline(inline)).collect()
+    }
+
+    fn inline(&self, inline: &Inline) -> String {
+        match inline {
+            Inline::Text(t) => escape(t),
+            Inline::Em(children) => format!("\\emph{{{}}}", self.inlines(children)),
+            Inline::Strong(children) => format!("\\textbf{{{}}}", self.inlines(children)),
+            Inline::Code(t) => format!("\\texttt{{{}}}", escape(t)),
+            Inline::Link { text, url } => match url.strip_prefix('#') {
+                Some(id) => format!("\\hyperref[{}]{{{}}}", make_id(id), self.inlines(text)),
+                None => format!("\\href{{{}}}{{{}}}", escape_url(url), self.inlines(text)),
+            },
+            Inline::FootnoteRef(label) => match self.footnote(label) {
+                Some(content) if !self.in_footnote.get() => {
+                    self.in_footnote.set(true);
+                    let body = self.blocks(content);
+                    self.in_footnote.set(false);
+                    format!("\\footnote{{{body}}}")
+                }
+                _ => escape(&format!("[{label}]")),
+            },
+        }
+    }
+
+    /// The footnote a reference to `label` points at; `#` and `*` take the next unused one in order.
+    fn footnote(&self, label: &str) -> Option<&[Block<'static>]> {
+        let mut matching = self.footnotes.iter().filter(|(l, _)| l == label);
+        let found = match label {
+            "#" | "*" => {
+                let taken = &self.taken[usize::from(label == "*")];
+                taken.set(taken.get() + 1);
+                matching.nth(taken.get() - 1)
+            }
+            _ => matching.next(),
+        };
+        found.map(|(_, content)| content.as_slice())
+    }
+}
+
+/// `body` inside `\begin{env}` and `\end{env}`.
+fn environment(env: &str, body: &str) -> String {
+    format!("\\begin{{{env}}}\n{body}\n\\end{{{env}}}")
+}
+
+/// Escape the characters LaTeX treats specially in text.
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\textbackslash{}"),
+            '{' | '}' | '$' | '&' | '#' | '_' | '%' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '^' => out.push_str("\\textasciicircum{}"),
+            '~' => out.push_str("\\textasciitilde{}"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escape a URL for `\href`, where `%`, `#` and braces still need a backslash.
+fn escape_url(url: &str) -> String {
+    let mut out = String::with_capacity(url.len());
+    for c in url.chars() {
+        match c {
+            '%' | '#' | '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\\' => out.push_str("%5C"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn headings_follow_the_section_depth() {
+        let blocks = parse("Top\n===\n\nSub\n---\n").unwrap();
+        assert_eq!(
+            LatexRenderer::new().render(&blocks),
+            "\\section{Top}\n\\label{top}\n\n\\subsection{Sub}\n\\label{sub}\n"
+        );
+        assert!(
+            LatexRenderer::new()
+                .chapters(true)
+                .render(&blocks)
+                .starts_with("\\chapter{Top}")
+        );
+    }
+
+    #[test]
+    fn special_characters_are_escaped() {
+        let blocks = parse("a_b {c} $d ~e ^f \\\\g #h ``x_y``").unwrap();
+        assert_eq!(
+            LatexRenderer::new().render(&blocks),
+            "a\\_b \\{c\\} \\$d \\textasciitilde{}e \\textasciicircum{}f \\textbackslash{}g \\#h \\texttt{x\\_y}\n"
+        );
+    }
+
+    #[test]
+    fn code_uses_verbatim_or_listings() {
+        let doc = "::\n\n   raw {text}\n\n.. code-block:: python\n\n   x = 1\n\n.. code-block:: brainfuck\n\n   +.\n";
+        let blocks = parse(doc).unwrap();
+        assert_eq!(
+            LatexRenderer::new().render(&blocks),
+            "\\begin{verbatim}\nraw {text}\n\\end{verbatim}\n\n\
+             \\begin{lstlisting}[language=Python]\nx = 1\n\\end{lstlisting}\n\n\
+             \\begin{verbatim}\n+.\n\\end{verbatim}\n"
+        );
+        assert!(
+            !LatexRenderer::new()
+                .listings(false)
+                .render(&blocks)
+                .contains("lstlisting")
+        );
+    }
+
+    #[test]
+    fn code_ending_its_environment_is_escaped() {
+        let doc = "::\n\n   a \\end{verbatim} b_c\n\n.. code-block:: python\n\n   s = r\"\\end{lstlisting}\"\n";
+        let out = LatexRenderer::new().render(&parse(doc).unwrap());
+        assert_eq!(
+            out,
+            "\\begin{alltt}\na \\textbackslash{}end\\{verbatim\\} b\\_c\n\\end{alltt}\n\n\
+             \\begin{alltt}\ns = r\"\\textbackslash{}end\\{lstlisting\\}\"\n\\end{alltt}\n"
+        );
+        assert!(LatexRenderer::new().preamble().contains("\\usepackage{alltt}"));
+    }
+
+    #[test]
+    fn tables_count_columns_and_spans() {
+        let doc = "\
++-----+-----+-----+
+| A   | B   | C   |
++=====+=====+=====+
+| wide      | r   |
++-----+-----+ o   |
+| x   | y   | w   |
++-----+-----+-----+
+";
+        let blocks = parse(doc).unwrap();
+        assert_eq!(
+            LatexRenderer::new().render(&blocks),
+            "\\begin{tabular}{|l|l|l|}\n\\hline\nA & B & C \\\\\n\\hline\n\\hline\n\
+             \\multicolumn{2}{|l|}{wide} & \\multirow{2}{*}{r o w} \\\\\n\\cline{1-2}\n\
+             x & y &  \\\\\n\\hline\n\\end{tabular}\n"
+        );
+        let long = LatexRenderer::new().long_tables(true).render(&blocks);
+        assert!(long.starts_with("\\begin{longtable}{|l|l|l|}"));
+        assert!(long.contains("\\hline\n\\endhead\n"));
+    }
+
+    #[test]
+    fn admonitions_use_configurable_environments() {
+        let blocks = parse(".. note::\n\n   Read this.\n\n.. warning::\n\n   Careful.\n").unwrap();
+        assert_eq!(
+            LatexRenderer::new()
+                .admonition_environment("warning", "warnbox")
+                .render(&blocks),
+            "\\begin{rstadmonition}{Note}\nRead this.\n\\end{rstadmonition}\n\n\
+             \\begin{warnbox}{Warning}\nCareful.\n\\end{warnbox}\n"
+        );
+    }
+
+    #[test]
+    fn footnotes_move_to_their_references() {
+        let doc = "See [1]_ and [#]_ or [#]_.\n\n.. [1] First.\n.. [#] Auto one.\n.. [#] Auto two.\n";
+        let blocks = parse(doc).unwrap();
+        assert_eq!(
+            LatexRenderer::new().render(&blocks),
+            "See \\footnote{First.} and \\footnote{Auto one.} or \\footnote{Auto two.}.\n"
+        );
+    }
+
+    #[test]
+    fn documents_have_a_preamble() {
+        let blocks = parse("Hello.").unwrap();
+        let out = LatexRenderer::new()
+            .document(true)
+            .document_class("report")
+            .render(&blocks);
+        assert!(out.starts_with("\\documentclass{report}\n\\usepackage[utf8]{inputenc}\n"));
+        assert!(out.contains("\\newenvironment{rstadmonition}"));
+        assert!(out.ends_with("\\begin{document}\n\nHello.\n\n\\end{document}\n"));
+    }
+}
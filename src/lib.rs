@@ -1,7 +1,11 @@
 //! Recursive descent reStructuredText parser that targets a lightweight AST.
 //!
 //! The crate exposes helpers to parse raw docstrings into [`Block`] nodes via [`parse`],
-//! and render them as HTML with [`html_of`].
+//! and render them as HTML with [`html_of`], or with an [`HtmlRenderer`] for control over the
+//! markup. [`to_rst`] writes blocks back out as reStructuredText, and [`format()`] uses it to
+//! reformat reST source. [`text_of`] renders plain text for terminals.
+//! [`events::Parser`] yields the same document as a stream of events, a top-level block at a time.
+//! [`Document`] keeps the parse of an editable text and re-parses only what an edit touches.
 //!
 //! When the `markdown` feature is enabled, you can also normalize docstrings into
 //! Markdown using [`markdown_of`].
@@ -9,19 +13,64 @@
 //! The internal parser is intentionally small and resilient enough to handle the
 //! eclectic docstring styles used in the Python ecosystem.
 
+mod ansi;
 mod ast;
+pub mod docstring;
+mod document;
+mod docutils;
 pub mod error;
-pub use ast::{Block, Field, Inline, ListKind};
+pub mod events;
+mod format;
+pub mod highlight;
+mod html;
+mod latex;
+mod man;
+mod options;
+pub mod render;
+pub mod resolver;
+mod rst;
+pub mod tags;
+mod text;
+mod textutil;
+pub mod visit;
+
+use std::borrow::Cow;
+
+pub use ansi::AnsiRenderer;
+pub use ast::{
+    Block, Field, Inline, Inventory, InventoryEntry, ListKind, OptionItem, ParamKind, PyObject, PyObjectKind, PyParam,
+    PySignature, PySignatureError, TableCell, assign_heading_ids, make_id, parse_py_signature,
+};
+pub use docstring::{
+    Deprecation, DocParam, DocRaise, DocReturn, DocSection, Docstring, DocstringStyle, SeeAlso, parse_docstring,
+    write_docstring,
+};
+pub use document::Document;
+pub use docutils::DocutilsRenderer;
 pub use error::ParseError;
+pub use format::{FormatOptions, format};
+#[cfg(feature = "highlight")]
+pub use highlight::BuiltinHighlighter;
+pub use highlight::Highlighter;
+pub use html::HtmlRenderer;
+pub use latex::LatexRenderer;
+pub use man::ManRenderer;
+pub use options::ParserOptions;
+pub use render::{RawMode, RenderOptions};
+pub use resolver::{FileResolver, FsResolver};
+pub use rst::to_rst;
+pub use tags::{TagExpr, Tags, apply_conditionals};
+pub use text::TextRenderer;
 
 #[derive(Debug, Clone, Copy)]
 struct Line<'a> {
-    _num: usize,
+    num: usize,
     raw: &'a str,
 }
 
 #[derive(Debug)]
 struct Lines<'a> {
+    input: &'a str,
     all: Vec<Line<'a>>,
     i: usize,
 }
@@ -31,9 +80,9 @@ impl<'a> Lines<'a> {
         let all = input
             .lines()
             .enumerate()
-            .map(|(i, raw)| Line { _num: i + 1, raw })
+            .map(|(i, raw)| Line { num: i + 1, raw })
             .collect();
-        Self { all, i: 0 }
+        Self { input, all, i: 0 }
     }
 
     fn peek(&self) -> Option<&Line<'a>> {
@@ -59,6 +108,38 @@ impl<'a> Lines<'a> {
     fn is_eof(&self) -> bool {
         self.i >= self.all.len()
     }
+
+    /// Index of the next line.
+    fn position(&self) -> usize {
+        self.i
+    }
+
+    fn seek(&mut self, i: usize) {
+        self.i = i.min(self.all.len());
+    }
+
+    /// The lines from `start` up to the next one, each followed by a newline: a slice of the input
+    /// unless it has `\r\n` line endings or lacks the final newline.
+    fn text_since(&self, start: usize) -> Cow<'a, str> {
+        let lines = &self.all[start..self.i];
+        let Some(first) = lines.first() else { return Cow::Borrowed("") };
+        let offset = |raw: &str| raw.as_ptr() as usize - self.input.as_ptr() as usize;
+        let end = self.all.get(self.i).map_or(self.input.len(), |next| offset(next.raw));
+        let text = &self.input[offset(first.raw)..end];
+        if text.ends_with('\n') && !text.contains('\r') {
+            Cow::Borrowed(text)
+        } else {
+            Cow::Owned(lines.iter().flat_map(|line| [line.raw, "\n"]).collect())
+        }
+    }
+}
+
+/// `text` without trailing whitespace, still borrowed if it was.
+fn trim_end(text: Cow<'_, str>) -> Cow<'_, str> {
+    match text {
+        Cow::Borrowed(text) => Cow::Borrowed(text.trim_end()),
+        Cow::Owned(text) => Cow::Owned(text.trim_end().to_string()),
+    }
 }
 
 fn is_blank(s: &str) -> bool {
@@ -69,7 +150,7 @@ fn leading_indent(s: &str) -> usize {
     s.chars().take_while(|c| c.is_whitespace()).count()
 }
 
-fn colon_heading_text(current: &Line<'_>, next: Option<&Line<'_>>) -> Option<String> {
+fn colon_heading_text<'a>(current: &Line<'a>, next: Option<&Line<'_>>) -> Option<&'a str> {
     let trimmed = current.raw.trim();
     if trimmed.starts_with("..") {
         return None;
@@ -88,15 +169,15 @@ fn colon_heading_text(current: &Line<'_>, next: Option<&Line<'_>>) -> Option<Str
         return None;
     }
     match next {
-        Some(next_line) if is_blank(next_line.raw) => Some(without_colon.to_string()),
+        Some(next_line) if is_blank(next_line.raw) => Some(without_colon),
         Some(next_line) => {
             if leading_indent(next_line.raw) > leading_indent(current.raw) {
-                Some(without_colon.to_string())
+                Some(without_colon)
             } else {
                 None
             }
         }
-        None => Some(without_colon.to_string()),
+        None => Some(without_colon),
     }
 }
 
@@ -118,15 +199,15 @@ fn strip_indent_preserve(s: &str, indent: usize) -> &str {
     ""
 }
 
+/// Section title adornment characters in level order: `=` marks level 1, `-` level 2 and so on.
+pub(crate) const ADORNMENTS: [char; 5] = ['=', '-', '~', '^', '"'];
+
+/// The title level a line made of one repeated [adornment](ADORNMENTS) character marks.
 fn underline_level(s: &str) -> Option<u8> {
     let t = s.trim();
-    if !t.is_empty() && t.chars().all(|c| c == '=') {
-        Some(1)
-    } else if !t.is_empty() && t.chars().all(|c| c == '-') {
-        Some(2)
-    } else {
-        None
-    }
+    let c = t.chars().next()?;
+    let level = ADORNMENTS.iter().position(|&a| a == c)?;
+    t.chars().all(|d| d == c).then_some(level as u8 + 1)
 }
 
 #[cfg(feature = "markdown")]
@@ -177,28 +258,26 @@ fn skip_blank_lines(ls: &mut Lines<'_>) {
 }
 
 /// Try to parse a code fence block (```)
-fn try_parse_code_fence(ls: &mut Lines<'_>) -> Option<Block> {
+fn try_parse_code_fence<'a>(ls: &mut Lines<'a>, opts: &ParserOptions) -> Option<Block<'a>> {
     let l = ls.peek()?;
-    if l.raw.trim() != "```" {
+    if !opts.code_fences_enabled() || l.raw.trim() != "```" {
         return None;
     }
 
     ls.next();
-    let mut buf = String::new();
-    while let Some(inner) = ls.next() {
-        if inner.raw.trim() == "```" {
-            break;
-        }
-        buf.push_str(inner.raw);
-        buf.push('\n');
+    let start = ls.position();
+    while ls.peek().is_some_and(|inner| inner.raw.trim() != "```") {
+        ls.next();
     }
-    Some(Block::CodeBlock(buf))
+    let code = ls.text_since(start);
+    ls.next();
+    Some(Block::CodeBlock(code))
 }
 
 /// Try to parse a quote block (>)
-fn try_parse_quote(ls: &mut Lines<'_>) -> Result<Option<Block>, ParseError> {
+fn try_parse_quote<'a>(ls: &mut Lines<'a>, opts: &ParserOptions) -> Result<Option<Block<'a>>, ParseError> {
     let l = ls.peek();
-    if !l.map(|l| l.raw.trim_start().starts_with('>')).unwrap_or(false) {
+    if !opts.markdown_quotes_enabled() || !l.map(|l| l.raw.trim_start().starts_with('>')).unwrap_or(false) {
         return Ok(None);
     }
 
@@ -213,59 +292,80 @@ fn try_parse_quote(ls: &mut Lines<'_>) -> Result<Option<Block>, ParseError> {
             break;
         }
     }
-    let inner = parse(&quote)?;
+    let inner = parse_nested(&quote, opts)?;
     Ok(Some(Block::Quote(inner)))
 }
 
 /// Try to parse a colon-style heading (Heading:)
-fn try_parse_colon_heading(ls: &mut Lines<'_>) -> Option<Block> {
+fn try_parse_colon_heading<'a>(ls: &mut Lines<'a>, opts: &ParserOptions) -> Option<Block<'a>> {
+    if !opts.colon_headings_enabled() {
+        return None;
+    }
     let line = ls.peek()?;
     let title = colon_heading_text(line, ls.peek_next())?;
     ls.next();
-    Some(Block::Heading { level: 2, inlines: ast::parse_inlines(&title) })
+    Some(Block::Heading { level: 2, inlines: ast::parse_inlines(title, opts), id: String::new() })
+}
+
+/// Try to parse a section title with an overline matching its underline
+fn try_parse_overlined_heading<'a>(ls: &mut Lines<'a>, opts: &ParserOptions) -> Option<Block<'a>> {
+    let over = ls.peek()?;
+    let level = underline_level(over.raw)?;
+    let (title, under) = (ls.all.get(ls.i + 1)?, ls.all.get(ls.i + 2)?);
+    if is_blank(title.raw) || underline_level(title.raw).is_some() || under.raw.trim() != over.raw.trim() {
+        return None;
+    }
+    ls.i += 3;
+    let inlines = ast::parse_inlines(title.raw.trim(), opts);
+    Some(Block::Heading { level, inlines, id: String::new() })
 }
 
-/// Try to parse a setext-style heading (underlined with = or -)
-fn try_parse_setext_heading(ls: &mut Lines<'_>) -> Option<Block> {
+/// Try to parse a setext-style heading (underlined with one of the [adornments](ADORNMENTS))
+fn try_parse_setext_heading<'a>(ls: &mut Lines<'a>, opts: &ParserOptions) -> Option<Block<'a>> {
     let title = ls.next()?;
     let ul = ls.peek()?;
     let level = underline_level(ul.raw)?;
     ls.next();
-    let inlines = ast::parse_inlines(title.raw.trim());
-    Some(Block::Heading { level, inlines })
+    let inlines = ast::parse_inlines(title.raw.trim(), opts);
+    Some(Block::Heading { level, inlines, id: String::new() })
 }
 
 /// Try to parse a literal block (::)
-fn try_parse_literal_block(ls: &mut Lines<'_>) -> Option<Block> {
+fn try_parse_literal_block<'a>(ls: &mut Lines<'a>) -> Option<Block<'a>> {
     let line = ls.peek()?;
     if line.raw.trim() != "::" {
         return None;
     }
 
     ls.next();
+    Some(parse_literal_body(ls))
+}
 
+/// Read the indented body of a literal block, after the line that introduced it.
+fn parse_literal_body<'a>(ls: &mut Lines<'a>) -> Block<'a> {
     let base_indent = if let Some(next_line) = ls.peek() {
         if is_blank(next_line.raw) {
             ls.next();
             if let Some(content_line) = ls.peek() {
                 leading_indent(content_line.raw)
             } else {
-                return Some(Block::LiteralBlock(String::new()));
+                return Block::LiteralBlock(Cow::Borrowed(""));
             }
         } else {
             leading_indent(next_line.raw)
         }
     } else {
-        return Some(Block::LiteralBlock(String::new()));
+        return Block::LiteralBlock(Cow::Borrowed(""));
     };
 
     let mut buf = String::new();
     while let Some(l) = ls.peek() {
         if is_blank(l.raw) {
-            if let Some(next) = ls.peek_next() {
-                if !is_blank(next.raw) && leading_indent(next.raw) < base_indent {
-                    break;
-                }
+            if let Some(next) = ls.peek_next()
+                && !is_blank(next.raw)
+                && leading_indent(next.raw) < base_indent
+            {
+                break;
             }
             buf.push('\n');
             ls.next();
@@ -278,46 +378,61 @@ fn try_parse_literal_block(ls: &mut Lines<'_>) -> Option<Block> {
         }
     }
 
-    Some(Block::LiteralBlock(buf.trim_end().to_string()))
+    Block::LiteralBlock(Cow::Owned(buf.trim_end().to_string()))
 }
 
-/// Try to parse a comment (.. without ::)
-fn try_parse_comment(ls: &mut Lines<'_>) -> Result<Option<Block>, ParseError> {
-    let line = ls.peek().ok_or(ParseError::Eof)?;
-    let trimmed = line.raw.trim_start();
-
-    if !trimmed.starts_with(".. ") {
-        return Ok(None);
+/// Try to parse an internal hyperlink target (`.. _name:`); one with a URL stays a comment
+fn try_parse_target<'a>(ls: &mut Lines<'a>) -> Option<Block<'a>> {
+    let line = ls.peek()?;
+    let rest = line.raw.trim().strip_prefix(".. _")?.strip_suffix(':')?;
+    let name = match rest.strip_prefix('`') {
+        Some(quoted) => quoted.strip_suffix('`')?,
+        None if rest.contains(':') => return None,
+        None => rest,
+    };
+    if name.trim().is_empty() || name == "_" {
+        return None;
     }
-
-    let after_dots = &trimmed[3..];
-
-    if after_dots.contains("::") {
-        return Ok(None);
+    if ls
+        .peek_next()
+        .is_some_and(|next| !is_blank(next.raw) && leading_indent(next.raw) > leading_indent(line.raw))
+    {
+        return None;
     }
-
-    let base_indent = leading_indent(line.raw);
+    let name = name.to_string();
     ls.next();
+    Some(Block::Target { name })
+}
 
+/// Read the body of an explicit markup block (comment, footnote) whose first line is indented by
+/// `base_indent` and continues with `first`; the rest of the body is indented like its first indented line.
+fn explicit_body(ls: &mut Lines<'_>, base_indent: usize, first: &str) -> String {
     let mut content_text = String::new();
-    if !after_dots.trim().is_empty() {
-        content_text.push_str(after_dots.trim());
+    if !first.trim().is_empty() {
+        content_text.push_str(first.trim());
     }
 
-    if let Some(next) = ls.peek() {
-        if is_blank(next.raw) {
-            ls.next();
-        }
+    if content_text.is_empty()
+        && let Some(next) = ls.peek()
+        && is_blank(next.raw)
+    {
+        ls.next();
     }
 
-    let content_indent = base_indent + 1;
+    let content_indent = [ls.peek(), ls.peek_next()]
+        .into_iter()
+        .flatten()
+        .find(|l| !is_blank(l.raw))
+        .filter(|l| leading_indent(l.raw) > base_indent)
+        .map_or(base_indent + 1, |l| leading_indent(l.raw));
 
     while let Some(l) = ls.peek() {
         if is_blank(l.raw) {
-            if let Some(next) = ls.peek_next() {
-                if !is_blank(next.raw) && leading_indent(next.raw) <= base_indent {
-                    break;
-                }
+            if let Some(next) = ls.peek_next()
+                && !is_blank(next.raw)
+                && leading_indent(next.raw) <= base_indent
+            {
+                break;
             }
             if !content_text.is_empty() {
                 content_text.push('\n');
@@ -333,14 +448,62 @@ fn try_parse_comment(ls: &mut Lines<'_>) -> Result<Option<Block>, ParseError> {
             break;
         }
     }
+    content_text
+}
+
+/// Try to parse a footnote (`.. [label] text`); other bracketed labels, such as citations, stay comments
+fn try_parse_footnote<'a>(ls: &mut Lines<'a>, opts: &ParserOptions) -> Result<Option<Block<'a>>, ParseError> {
+    let Some(line) = ls.peek() else { return Ok(None) };
+    let Some((label, after)) = line
+        .raw
+        .trim_start()
+        .strip_prefix(".. [")
+        .and_then(|rest| rest.split_once(']'))
+    else {
+        return Ok(None);
+    };
+    if !ast::is_footnote_label(label) || !(after.is_empty() || after.starts_with(char::is_whitespace)) {
+        return Ok(None);
+    }
+    let label = label.to_string();
+    let base_indent = leading_indent(line.raw);
+    let after = after.to_string();
+    ls.next();
+    let content_text = explicit_body(ls, base_indent, &after);
+    let content = if content_text.trim().is_empty() { Vec::new() } else { parse_nested(&content_text, opts)? };
+    Ok(Some(Block::Footnote { label, content }))
+}
+
+/// Try to parse a comment (.. without ::), whose body is indented like its first indented line
+fn try_parse_comment<'a>(ls: &mut Lines<'a>, opts: &ParserOptions) -> Result<Option<Block<'a>>, ParseError> {
+    let line = ls.peek().ok_or(ParseError::Eof)?;
+    let trimmed = line.raw.trim_start();
+
+    if !trimmed.starts_with(".. ") && trimmed.trim_end() != ".." {
+        return Ok(None);
+    }
 
-    let content = if content_text.trim().is_empty() { Vec::new() } else { parse(&content_text)? };
+    let after_dots = trimmed.get(3..).unwrap_or("");
+
+    if after_dots.contains("::") {
+        return Ok(None);
+    }
+
+    let base_indent = leading_indent(line.raw);
+    ls.next();
+    // An empty comment followed by a blank line ends the construct before it, keeping none of the
+    // indented text after it.
+    if after_dots.trim().is_empty() && ls.peek().is_none_or(|next| is_blank(next.raw)) {
+        return Ok(Some(Block::Comment(Vec::new())));
+    }
+    let content_text = explicit_body(ls, base_indent, after_dots);
+    let content = if content_text.trim().is_empty() { Vec::new() } else { parse_nested(&content_text, opts)? };
 
     Ok(Some(Block::Comment(content)))
 }
 
 /// Try to parse a directive (.. name:: argument)
-fn try_parse_directive(ls: &mut Lines<'_>) -> Result<Option<Block>, ParseError> {
+fn try_parse_directive<'a>(ls: &mut Lines<'a>, opts: &ParserOptions) -> Result<Option<Block<'a>>, ParseError> {
     let line = ls.peek().ok_or(ParseError::Eof)?;
     let trimmed = line.raw.trim_start();
 
@@ -361,24 +524,30 @@ fn try_parse_directive(ls: &mut Lines<'_>) -> Result<Option<Block>, ParseError>
 
     let argument = after_dots[double_colon_idx + 2..].trim().to_string();
 
+    let line_num = line.num;
     let base_indent = leading_indent(line.raw);
+    let start = ls.position();
     ls.next();
 
-    if let Some(next) = ls.peek() {
-        if is_blank(next.raw) {
-            ls.next();
-        }
+    if let Some(next) = ls.peek()
+        && is_blank(next.raw)
+    {
+        ls.next();
     }
 
     let mut content_text = String::new();
-    let content_indent = base_indent + 4;
+    let content_indent = ls
+        .peek()
+        .filter(|l| !is_blank(l.raw) && leading_indent(l.raw) > base_indent)
+        .map_or(base_indent + 4, |l| leading_indent(l.raw));
 
     while let Some(l) = ls.peek() {
         if is_blank(l.raw) {
-            if let Some(next) = ls.peek_next() {
-                if !is_blank(next.raw) && leading_indent(next.raw) < content_indent {
-                    break;
-                }
+            if let Some(next) = ls.peek_next()
+                && !is_blank(next.raw)
+                && leading_indent(next.raw) < content_indent
+            {
+                break;
             }
             content_text.push('\n');
             ls.next();
@@ -391,135 +560,270 @@ fn try_parse_directive(ls: &mut Lines<'_>) -> Result<Option<Block>, ParseError>
         }
     }
 
+    if name == "raw" {
+        let block = build_raw_block(line_num, &argument, &content_text, opts)?;
+        return Ok(Some(
+            block.unwrap_or_else(|| Block::LiteralBlock(trim_end(ls.text_since(start)))),
+        ));
+    }
+
+    // A signature this parser does not understand (`FOO = 1`, `Foo[T]`) keeps the plain directive.
+    if let Some(kind) = ast::PyObjectKind::from_directive(name) {
+        let (options, body) = split_directive_options(&content_text);
+        let content = if body.trim().is_empty() { Vec::new() } else { parse_nested(body, opts)? };
+        if let Ok(obj) = ast::build_py_object(kind, &argument, options, content) {
+            return Ok(Some(Block::PyObject(Box::new(obj))));
+        }
+    }
+
     let content = if content_text.trim().is_empty() {
         Vec::new()
     } else if name == "code-block" || name == "code" {
-        vec![Block::LiteralBlock(content_text.trim_end().to_string())]
+        vec![Block::LiteralBlock(Cow::Owned(content_text.trim_end().to_string()))]
     } else {
-        parse(&content_text)?
+        parse_nested(&content_text, opts)?
     };
 
     Ok(Some(Block::Directive { name: name.to_string(), argument, content }))
 }
 
+/// Split the leading `:name: value` option lines off a directive body.
+///
+/// Options end at the first blank line or the first line that is not an option; the rest of the body is
+/// returned unchanged.
+fn split_directive_options(body: &str) -> (Vec<(String, String)>, &str) {
+    let mut options = Vec::new();
+    let mut rest = body;
+    loop {
+        let (line, remainder) = rest.split_once('\n').unwrap_or((rest, ""));
+        let Some((name, value)) = line.strip_prefix(':').and_then(|t| t.split_once(':')) else {
+            break;
+        };
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            break;
+        }
+        options.push((name.to_string(), value.trim().to_string()));
+        rest = remainder;
+    }
+    (options, rest)
+}
+
+/// Build a [`Block::Raw`] from a `raw` directive, reading `:file:` through the configured resolver.
+///
+/// Returns `None` for a directive that cannot be honoured (no output format, `:file:` alongside
+/// content, or `:file:` without a resolver); the caller keeps its source as a literal block. Only a
+/// configured resolver failing to read the file is reported as an error.
+fn build_raw_block(
+    line: usize, argument: &str, body: &str, opts: &ParserOptions,
+) -> Result<Option<Block<'static>>, ParseError> {
+    let formats: Vec<String> = argument.split_whitespace().map(|f| f.to_ascii_lowercase()).collect();
+    if formats.is_empty() {
+        return Ok(None);
+    }
+
+    let (options, inline_content) = split_directive_options(body);
+    let content = match options.iter().find(|(name, _)| name == "file") {
+        Some((_, path)) => {
+            let Some(resolver) = opts.resolver().filter(|_| inline_content.trim().is_empty()) else {
+                return Ok(None);
+            };
+            resolver
+                .read(path)
+                .map_err(|source| ParseError::File { line, path: path.clone(), source })?
+        }
+        None => inline_content.trim_matches('\n').to_string(),
+    };
+
+    Ok(Some(Block::Raw { formats, content }))
+}
+
 /// Check if a line starts a new block (not a paragraph continuation)
-fn starts_new_block(line: &str) -> bool {
-    is_blank(line) || ast::list_kind(line).is_some() || line.trim() == "```" || line.trim_start().starts_with('>')
+fn starts_new_block(line: &str, opts: &ParserOptions) -> bool {
+    is_blank(line)
+        || ast::list_kind(line).is_some()
+        || (opts.code_fences_enabled() && line.trim() == "```")
+        || (opts.markdown_quotes_enabled() && line.trim_start().starts_with('>'))
 }
 
 /// Parse remaining content as a paragraph
-fn parse_paragraph(ls: &mut Lines<'_>) -> Option<Block> {
-    let mut buf = String::new();
-    while let Some(l) = ls.peek() {
-        if starts_new_block(l.raw) {
-            break;
+///
+/// A paragraph ending in `::` followed by a blank line and an indented block introduces a literal block,
+/// as in `Example::`. The marker becomes a single `:`, or disappears when it stands apart (`Example ::`).
+fn parse_paragraph<'a>(ls: &mut Lines<'a>, opts: &ParserOptions) -> Vec<Block<'a>> {
+    let indent = ls.peek().map_or(0, |l| leading_indent(l.raw));
+    let start = ls.position();
+    while ls.peek().is_some_and(|l| !starts_new_block(l.raw, opts)) {
+        ls.next();
+    }
+    let text = trim_end(ls.text_since(start));
+    if text.is_empty() {
+        return Vec::new();
+    }
+
+    let literal_follows = ls.peek().is_some_and(|l| is_blank(l.raw))
+        && ls
+            .peek_next()
+            .is_some_and(|l| !is_blank(l.raw) && leading_indent(l.raw) > indent);
+    match text.strip_suffix("::") {
+        Some(head) if literal_follows => {
+            let mut blocks = Vec::new();
+            if !head.trim().is_empty() {
+                let head = if !head.ends_with(char::is_whitespace) {
+                    Cow::Owned(format!("{head}:"))
+                } else if let Cow::Borrowed(text) = text {
+                    Cow::Borrowed(text[..head.len()].trim_end())
+                } else {
+                    Cow::Owned(head.trim_end().to_string())
+                };
+                blocks.push(Block::Paragraph(ast::parse_inlines_cow(head, opts)));
+            }
+            blocks.push(parse_literal_body(ls));
+            blocks
         }
-        buf.push_str(ls.next().unwrap().raw);
-        buf.push('\n');
+        _ => vec![Block::Paragraph(ast::parse_inlines_cow(text, opts))],
     }
-    let text = buf.trim_end();
-    if text.is_empty() { None } else { Some(Block::Paragraph(ast::parse_inlines(text))) }
 }
 
 /// Parse raw reStructuredText-like input into a vector of [`Block`] nodes.
 ///
 /// The parser walks the input top-to-bottom, attempting the most specific block constructs first
 /// (code fences, block quotes, lists, field lists, definition lists, headings) before falling back to paragraphs.
-/// When the stream cannot be consumed because of malformed markup, a [`ParseError`] is returned to the caller.
-pub fn parse(input: &str) -> Result<Vec<Block>, ParseError> {
+/// Malformed markup, such as a broken table, is kept as a literal block and parsing carries on; a
+/// [`ParseError`] is returned when input cannot be read at all, e.g. a file resolver fails.
+pub fn parse(input: &str) -> Result<Vec<Block<'_>>, ParseError> {
+    parse_with(input, &ParserOptions::default())
+}
+
+/// Parse input like [`parse`], using the supplied [`ParserOptions`].
+///
+/// Use [`ParserOptions::strict`] for plain reST files so docstring conveniences such as `Args:`
+/// headings and Markdown fences are not applied to them.
+pub fn parse_with<'a>(input: &'a str, opts: &ParserOptions) -> Result<Vec<Block<'a>>, ParseError> {
+    let mut blocks = parse_blocks(input, opts)?;
+    ast::resolve_py_names(&mut blocks);
+    ast::assign_heading_ids(&mut blocks);
+    Ok(match opts.active_tags() {
+        Some(tags) => apply_conditionals(blocks, tags),
+        None => blocks,
+    })
+}
+
+/// Block-level parsing loop shared by the top level and every nested body.
+fn parse_blocks<'a>(input: &'a str, opts: &ParserOptions) -> Result<Vec<Block<'a>>, ParseError> {
     let mut ls = Lines::new(input);
     let mut blocks = Vec::new();
-
     while !ls.is_eof() {
-        skip_blank_lines(&mut ls);
-        if ls.is_eof() {
-            break;
-        }
+        blocks.extend(parse_next(&mut ls, opts)?);
+    }
+    Ok(blocks)
+}
 
-        if let Some(block) = try_parse_code_fence(&mut ls) {
-            blocks.push(block);
-            continue;
-        }
+/// Parse a body assembled from the input, such as dedented directive content, into blocks that own
+/// their text.
+fn parse_nested(text: &str, opts: &ParserOptions) -> Result<Vec<Block<'static>>, ParseError> {
+    parse_blocks(text, opts).map(ast::into_owned)
+}
 
-        if let Some(block) = try_parse_quote(&mut ls)? {
-            blocks.push(block);
-            continue;
-        }
+/// Skip blank lines and parse the construct that follows, which may yield several blocks (or none
+/// at the end of input).
+fn parse_next<'a>(ls: &mut Lines<'a>, opts: &ParserOptions) -> Result<Vec<Block<'a>>, ParseError> {
+    skip_blank_lines(ls);
+    if ls.is_eof() {
+        return Ok(Vec::new());
+    }
 
-        if let Some(block) = ast::try_parse_list(&mut ls) {
-            blocks.push(block);
-            continue;
-        }
+    if let Some(block) = try_parse_code_fence(ls, opts) {
+        return Ok(vec![block]);
+    }
 
-        if let Some(block) = ast::try_parse_grid_table(&mut ls) {
-            blocks.push(block);
-            continue;
-        }
+    if let Some(block) = try_parse_overlined_heading(ls, opts) {
+        return Ok(vec![block]);
+    }
 
-        if let Some(block) = ast::try_parse_simple_table(&mut ls) {
-            blocks.push(block);
-            continue;
-        }
+    if let Some(block) = try_parse_quote(ls, opts)? {
+        return Ok(vec![block]);
+    }
 
-        if let Some(block) = try_parse_comment(&mut ls)? {
-            blocks.push(block);
-            continue;
-        }
+    if let Some(block) = ast::try_parse_list(ls, opts) {
+        return Ok(vec![block]);
+    }
 
-        if let Some(block) = try_parse_directive(&mut ls)? {
-            blocks.push(block);
-            continue;
-        }
+    if let Some(block) = ast::try_parse_option_list(ls, opts)? {
+        return Ok(vec![block]);
+    }
 
-        if let Some(field_block) = ast::parse_field_entries(&mut ls)? {
-            blocks.push(field_block);
-            continue;
-        }
+    if let Some(block) = ast::try_parse_grid_table(ls, opts)? {
+        return Ok(vec![block]);
+    }
 
-        if let Some(def_blocks) = ast::parse_definition_entries(&mut ls)? {
-            blocks.extend(def_blocks);
-            continue;
-        }
+    if let Some(block) = ast::try_parse_simple_table(ls, opts)? {
+        return Ok(vec![block]);
+    }
 
-        if let Some(block) = try_parse_colon_heading(&mut ls) {
-            blocks.push(block);
-            continue;
-        }
+    if let Some(block) = try_parse_target(ls) {
+        return Ok(vec![block]);
+    }
 
-        if let Some(block) = try_parse_setext_heading(&mut ls) {
-            blocks.push(block);
-            continue;
-        } else {
-            ls.backtrack();
-        }
+    if let Some(block) = try_parse_footnote(ls, opts)? {
+        return Ok(vec![block]);
+    }
 
-        if let Some(block) = try_parse_literal_block(&mut ls) {
-            blocks.push(block);
-            continue;
-        }
+    if let Some(block) = try_parse_comment(ls, opts)? {
+        return Ok(vec![block]);
+    }
 
-        if let Some(block) = parse_paragraph(&mut ls) {
-            blocks.push(block);
-        }
+    if let Some(block) = try_parse_directive(ls, opts)? {
+        return Ok(vec![block]);
     }
 
-    Ok(blocks)
+    if let Some(field_block) = ast::parse_field_entries(ls, opts)? {
+        return Ok(vec![field_block]);
+    }
+
+    if let Some(def_blocks) = ast::parse_definition_entries(ls, opts)? {
+        return Ok(def_blocks);
+    }
+
+    if let Some(block) = try_parse_colon_heading(ls, opts) {
+        return Ok(vec![block]);
+    }
+
+    if let Some(block) = try_parse_setext_heading(ls, opts) {
+        return Ok(vec![block]);
+    }
+    ls.backtrack();
+
+    if let Some(block) = try_parse_literal_block(ls) {
+        return Ok(vec![block]);
+    }
+
+    Ok(parse_paragraph(ls, opts))
 }
 
 /// Render the provided docstring to HTML by parsing it and concatenating the
-/// HTML representation of each [`Block`].
+/// HTML representation of each [`Block`], using the default [`HtmlRenderer`].
 ///
-/// ## Panics
-///
-/// Panics if [`parse`] returns an error. Use [`parse`] directly when you need
-/// to surface parsing failures to your caller.
+/// Input that [`parse`] rejects is rendered as a single literal block. Use [`parse`]
+/// directly when you need to surface parsing failures to your caller.
 pub fn html_of(input: &str) -> String {
-    parse(input)
-        .unwrap()
-        .into_iter()
-        .map(|b| b.to_string())
-        .collect::<Vec<_>>()
-        .join("\n")
+    html_with(input, &RenderOptions::default())
+}
+
+/// Render the provided input to HTML like [`html_of`], applying the given [`RenderOptions`].
+pub fn html_with(input: &str, opts: &RenderOptions) -> String {
+    render::render_html(&parse_or_literal(input), opts)
+}
+
+/// Render the provided input as plain text wrapped to `width` columns, using [`TextRenderer`].
+///
+/// Input that [`parse`] rejects is rendered as a single literal block.
+pub fn text_of(input: &str, width: usize) -> String {
+    TextRenderer::new().width(width).render(&parse_or_literal(input))
+}
+
+/// Parse `input`, falling back to one literal block holding the whole input when parsing fails.
+fn parse_or_literal(input: &str) -> Vec<Block<'_>> {
+    parse(input).unwrap_or_else(|_| vec![Block::LiteralBlock(Cow::Borrowed(input))])
 }
 
 /// Convert docstrings that mix Google/Numpy/Sphinx conventions into Markdown.
@@ -549,7 +853,7 @@ A paragraph with *emphasis*, **strong**, and `code`.
 "#;
 
         let html = html_of(doc);
-        assert!(html.contains("<h1>Title</h1>"));
+        assert!(html.contains("<h1 id=\"title\">Title</h1>"));
         assert!(html.contains("<em>emphasis</em>"));
         assert!(html.contains("<strong>strong</strong>"));
         assert!(html.contains("<code>code</code>"));
@@ -562,17 +866,19 @@ A paragraph with *emphasis*, **strong**, and `code`.
 
         assert_eq!(ast.len(), 2);
         match &ast[0] {
-            Block::Heading { level, inlines } => {
+            Block::Heading { level, inlines, id } => {
                 assert_eq!(*level, 1);
                 assert_eq!(inlines[0], Inline::Text("Heading 1".into()));
+                assert_eq!(id, "heading-1");
             }
             _ => panic!("expected heading"),
         }
 
         match &ast[1] {
-            Block::Heading { level, inlines } => {
+            Block::Heading { level, inlines, id } => {
                 assert_eq!(*level, 2);
                 assert_eq!(inlines[0], Inline::Text("Heading 2".into()));
+                assert_eq!(id, "heading-2");
             }
             _ => panic!("expected heading"),
         }
@@ -653,7 +959,7 @@ A paragraph with *emphasis*, **strong**, and `code`.
     #[test]
     fn parses_emphasis_and_strong() {
         let line = "A *word* and a **strong** one";
-        let inl = ast::parse_inlines(line);
+        let inl = ast::parse_inlines(line, &ParserOptions::default());
         let html = ast::join_inlines(&inl);
         assert!(html.contains("<em>word</em>"));
         assert!(html.contains("<strong>strong</strong>"));
@@ -662,35 +968,35 @@ A paragraph with *emphasis*, **strong**, and `code`.
     #[test]
     fn parses_inline_code() {
         let line = "Inline `code` works";
-        let html = ast::join_inlines(&ast::parse_inlines(line));
+        let html = ast::join_inlines(&ast::parse_inlines(line, &ParserOptions::default()));
         assert!(html.contains("<code>code</code>"));
     }
 
     #[test]
     fn parses_double_backtick_code() {
         let line = "Use ``inline`` literals";
-        let html = ast::join_inlines(&ast::parse_inlines(line));
+        let html = ast::join_inlines(&ast::parse_inlines(line, &ParserOptions::default()));
         assert!(html.contains("<code>inline</code>"));
     }
 
     #[test]
     fn parses_inline_link() {
         let line = "`example <https://example.com>`_";
-        let html = ast::join_inlines(&ast::parse_inlines(line));
+        let html = ast::join_inlines(&ast::parse_inlines(line, &ParserOptions::default()));
         assert!(html.contains("<a href=\"https://example.com\">example</a>"));
     }
 
     #[test]
     fn inline_link_requires_reference_suffix() {
         let line = "`example <https://example.com>`";
-        let inl = ast::parse_inlines(line);
+        let inl = ast::parse_inlines(line, &ParserOptions::default());
         assert_eq!(inl, vec![Inline::Code("example <https://example.com>".into())]);
     }
 
     #[test]
     fn inline_link_mixed_with_text() {
         let line = "Read `docs <https://example.com>`_ now.";
-        let inl = ast::parse_inlines(line);
+        let inl = ast::parse_inlines(line, &ParserOptions::default());
         assert_eq!(
             inl,
             vec![
@@ -704,7 +1010,7 @@ A paragraph with *emphasis*, **strong**, and `code`.
     #[test]
     fn parses_mixed_inline_styles() {
         let line = "**bold** *em* `code` and `link <x>`_";
-        let html = ast::join_inlines(&ast::parse_inlines(line));
+        let html = ast::join_inlines(&ast::parse_inlines(line, &ParserOptions::default()));
         assert!(html.contains("<strong>bold</strong>"));
         assert!(html.contains("<em>em</em>"));
         assert!(html.contains("<code>code</code>"));
@@ -714,7 +1020,7 @@ A paragraph with *emphasis*, **strong**, and `code`.
     #[test]
     fn unmatched_markup_falls_back_to_text() {
         let line = "An *unfinished emphasis";
-        let inl = ast::parse_inlines(line);
+        let inl = ast::parse_inlines(line, &ParserOptions::default());
         assert_eq!(inl, vec![Inline::Text("An *unfinished emphasis".into())]);
     }
 
@@ -722,7 +1028,7 @@ A paragraph with *emphasis*, **strong**, and `code`.
     fn html_of_renders_expected_html() {
         let doc = "Heading\n=======\n\nBody text.";
         let rendered = html_of(doc);
-        assert_eq!(rendered.trim(), "<h1>Heading</h1>\n<p>Body text.</p>");
+        assert_eq!(rendered.trim(), "<h1 id=\"heading\">Heading</h1>\n<p>Body text.</p>");
     }
 
     #[test]
@@ -777,7 +1083,7 @@ A paragraph with *emphasis*, **strong**, and `code`.
         let ast = parse(doc).unwrap();
 
         match &ast[0] {
-            Block::Heading { level, inlines } => {
+            Block::Heading { level, inlines, .. } => {
                 assert_eq!(*level, 2);
                 assert_eq!(inlines[0], Inline::Text("Args".into()));
             }
@@ -845,7 +1151,7 @@ code
 ```
 "#;
         let html = html_of(doc);
-        assert!(html.contains("<h1>Title</h1>"));
+        assert!(html.contains("<h1 id=\"title\">Title</h1>"));
         assert!(html.contains("<ul>"));
         assert!(html.contains("<blockquote>"));
         assert!(html.contains("<pre><code>"));
@@ -870,7 +1176,7 @@ code
     #[test]
     fn parses_nested_strong_with_emphasis() {
         let line = "**bold *italic* bold**";
-        let inl = ast::parse_inlines(line);
+        let inl = ast::parse_inlines(line, &ParserOptions::default());
         assert_eq!(inl.len(), 1);
         match &inl[0] {
             Inline::Strong(children) => {
@@ -892,7 +1198,7 @@ code
     #[test]
     fn parses_nested_emphasis_with_strong() {
         let line = "*em **strong** em*";
-        let inl = ast::parse_inlines(line);
+        let inl = ast::parse_inlines(line, &ParserOptions::default());
         assert_eq!(inl.len(), 1);
         match &inl[0] {
             Inline::Em(children) => {
@@ -914,14 +1220,14 @@ code
     #[test]
     fn renders_nested_inline_markup_to_html() {
         let line = "**bold *italic* text**";
-        let html = ast::join_inlines(&ast::parse_inlines(line));
+        let html = ast::join_inlines(&ast::parse_inlines(line, &ParserOptions::default()));
         assert_eq!(html, "<strong>bold <em>italic</em> text</strong>");
     }
 
     #[test]
     fn parses_link_with_nested_markup() {
         let line = "`**bold** link <https://example.com>`_";
-        let inl = ast::parse_inlines(line);
+        let inl = ast::parse_inlines(line, &ParserOptions::default());
         assert_eq!(inl.len(), 1);
         match &inl[0] {
             Inline::Link { text, url } => {
@@ -943,14 +1249,14 @@ code
     #[test]
     fn nested_markup_does_not_break_code_blocks() {
         let line = "Use ``**not bold**`` for literals";
-        let inl = ast::parse_inlines(line);
+        let inl = ast::parse_inlines(line, &ParserOptions::default());
         assert!(matches!(&inl[1], Inline::Code(s) if s == "**not bold**"));
     }
 
     #[test]
     fn multiple_levels_of_nesting() {
         let line = "**strong with *emphasis* inside** and *emphasis with **strong** inside*";
-        let html = ast::join_inlines(&ast::parse_inlines(line));
+        let html = ast::join_inlines(&ast::parse_inlines(line, &ParserOptions::default()));
         assert!(html.contains("<strong>strong with <em>emphasis</em> inside</strong>"));
         assert!(html.contains("<em>emphasis with <strong>strong</strong> inside</em>"));
     }
@@ -1144,8 +1450,8 @@ val3  val4
         let ast = parse(doc).unwrap();
         assert_eq!(ast.len(), 1);
         match &ast[0] {
-            Block::Table { headers, rows } => {
-                assert_eq!(headers.len(), 2);
+            Block::Table { headers, rows, .. } => {
+                assert_eq!(headers[0].len(), 2);
                 assert_eq!(rows.len(), 2);
                 assert_eq!(rows[0].len(), 2);
                 assert_eq!(rows[1].len(), 2);
@@ -1175,11 +1481,11 @@ bar        `str`
 "#;
         let ast = parse(doc).unwrap();
         match &ast[0] {
-            Block::Table { headers, rows } => {
-                assert_eq!(headers.len(), 2);
-                assert!(matches!(&headers[0][0], Inline::Strong(_)));
-                assert!(matches!(&headers[1][0], Inline::Em(_)));
-                assert!(matches!(&rows[0][1][0], Inline::Code(_)));
+            Block::Table { headers, rows, .. } => {
+                assert_eq!(headers[0].len(), 2);
+                assert!(matches!(&headers[0][0].as_inlines().unwrap()[0], Inline::Strong(_)));
+                assert!(matches!(&headers[0][1].as_inlines().unwrap()[0], Inline::Em(_)));
+                assert!(matches!(&rows[0][1].as_inlines().unwrap()[0], Inline::Code(_)));
             }
             _ => panic!("expected Table"),
         }
@@ -1202,8 +1508,8 @@ A     B     C
 "#;
         let ast = parse(doc).unwrap();
         match &ast[0] {
-            Block::Table { headers, rows } => {
-                assert_eq!(headers.len(), 3);
+            Block::Table { headers, rows, .. } => {
+                assert_eq!(headers[0].len(), 3);
                 assert_eq!(rows.len(), 2);
                 assert_eq!(rows[0].len(), 3);
             }
@@ -1218,16 +1524,17 @@ A     B     C
 A     B
 ====  ====
 x
-      y
+y     z
 ====  ====
 "#;
         let ast = parse(doc).unwrap();
         match &ast[0] {
-            Block::Table { headers, rows } => {
-                assert_eq!(headers.len(), 2);
+            Block::Table { headers, rows, .. } => {
+                assert_eq!(headers[0].len(), 2);
                 assert_eq!(rows.len(), 2);
-                assert!(!rows[0][0].is_empty());
-                assert!(!rows[1][1].is_empty());
+                assert!(!rows[0][0].content.is_empty());
+                assert!(rows[0][1].content.is_empty());
+                assert!(!rows[1][1].content.is_empty());
             }
             _ => panic!("expected Table"),
         }
@@ -1267,8 +1574,8 @@ Paragraph after table.
         let ast = parse(doc).unwrap();
         assert_eq!(ast.len(), 1);
         match &ast[0] {
-            Block::Table { headers, rows } => {
-                assert_eq!(headers.len(), 2);
+            Block::Table { headers, rows, .. } => {
+                assert_eq!(headers[0].len(), 2);
                 assert_eq!(rows.len(), 2);
                 assert_eq!(rows[0].len(), 2);
                 assert_eq!(rows[1].len(), 2);
@@ -1297,10 +1604,10 @@ Paragraph after table.
 "#;
         let ast = parse(doc).unwrap();
         match &ast[0] {
-            Block::Table { headers, rows } => {
-                assert_eq!(headers.len(), 2);
-                assert!(matches!(&headers[0][0], Inline::Strong(_)));
-                assert!(matches!(&headers[1][0], Inline::Em(_)));
+            Block::Table { headers, rows, .. } => {
+                assert_eq!(headers[0].len(), 2);
+                assert!(matches!(&headers[0][0].as_inlines().unwrap()[0], Inline::Strong(_)));
+                assert!(matches!(&headers[0][1].as_inlines().unwrap()[0], Inline::Em(_)));
                 assert_eq!(rows.len(), 2);
             }
             _ => panic!("expected Table"),
@@ -1324,9 +1631,9 @@ Paragraph after table.
 "#;
         let ast = parse(doc).unwrap();
         match &ast[0] {
-            Block::Table { headers, rows } => {
-                assert_eq!(headers.len(), 2);
-                let header0_text = ast::join_inlines(&headers[0]);
+            Block::Table { headers, rows, .. } => {
+                assert_eq!(headers[0].len(), 2);
+                let header0_text = ast::join_inlines(headers[0][0].as_inlines().unwrap());
                 assert!(header0_text.contains("A"));
                 assert!(header0_text.contains("long"));
                 assert_eq!(rows.len(), 1);
@@ -1348,8 +1655,8 @@ Paragraph after table.
 "#;
         let ast = parse(doc).unwrap();
         match &ast[0] {
-            Block::Table { headers, rows } => {
-                assert_eq!(headers.len(), 3);
+            Block::Table { headers, rows, .. } => {
+                assert_eq!(headers[0].len(), 3);
                 assert_eq!(rows.len(), 2);
                 assert_eq!(rows[0].len(), 3);
                 assert_eq!(rows[1].len(), 3);
@@ -1638,4 +1945,1172 @@ More text.
             _ => panic!("expected FieldList"),
         }
     }
+
+    #[test]
+    fn raw_html_passes_through_verbatim() {
+        let doc = ".. raw:: html\n\n   <div class=\"x\"><b>*not emphasis*</b></div>\n";
+        let ast = parse(doc).unwrap();
+        match &ast[0] {
+            Block::Raw { formats, content } => {
+                assert_eq!(formats, &vec!["html".to_string()]);
+                assert_eq!(content, "<div class=\"x\"><b>*not emphasis*</b></div>");
+            }
+            _ => panic!("expected Raw"),
+        }
+        let passthrough = RenderOptions::new().raw(RawMode::Passthrough);
+        assert_eq!(
+            html_with(doc, &passthrough),
+            "<div class=\"x\"><b>*not emphasis*</b></div>"
+        );
+        assert_eq!(html_of(doc), "");
+    }
+
+    #[test]
+    fn raw_for_other_format_is_omitted_from_html() {
+        let doc = "Before.\n\n.. raw:: latex\n\n   \\newpage\n\nAfter.";
+        let html = html_of(doc);
+        assert!(!html.contains("newpage"));
+        assert!(html.contains("<p>After.</p>"));
+    }
+
+    #[test]
+    fn raw_file_option_reads_through_resolver() {
+        let doc = ".. raw:: html\n   :file: snippets/banner.html\n";
+        let opts = ParserOptions::new().file_resolver(|path: &str| Ok(format!("<!-- {path} -->")));
+        let ast = parse_with(doc, &opts).unwrap();
+        assert_eq!(
+            ast,
+            vec![Block::Raw { formats: vec!["html".into()], content: "<!-- snippets/banner.html -->".into() }]
+        );
+    }
+
+    #[test]
+    fn raw_file_option_without_resolver_stays_literal() {
+        let doc = "Intro.\n\n.. raw:: html\n   :file: /etc/passwd\n\nAfter.\n";
+        let ast = parse(doc).unwrap();
+        assert_eq!(
+            ast[1],
+            Block::LiteralBlock(".. raw:: html\n   :file: /etc/passwd".into())
+        );
+        assert_eq!(ast[2], Block::Paragraph(vec![Inline::Text("After.".into())]));
+    }
+
+    #[test]
+    fn raw_file_option_reports_resolver_failures() {
+        let doc = "Intro.\n\n.. raw:: html\n   :file: missing.html\n";
+        let opts = ParserOptions::new()
+            .file_resolver(|_: &str| Err(std::io::Error::new(std::io::ErrorKind::NotFound, "missing")));
+        match parse_with(doc, &opts) {
+            Err(ParseError::File { line, path, .. }) => {
+                assert_eq!(line, 3);
+                assert_eq!(path, "missing.html");
+            }
+            other => panic!("expected file error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn raw_without_format_stays_literal_and_renders() {
+        let doc = ".. raw::\n\n   <script>x</script>\n\nAfter.\n";
+        let ast = parse(doc).unwrap();
+        assert_eq!(ast[0], Block::LiteralBlock(".. raw::\n\n   <script>x</script>".into()));
+        let html = html_of(doc);
+        assert!(html.contains("&lt;script&gt;") && html.contains("<p>After.</p>"));
+    }
+
+    #[test]
+    fn fs_resolver_rejects_paths_outside_root() {
+        let resolver = FsResolver::new(".");
+        let err = resolver.read("../secret.txt").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+        assert!(resolver.read("/etc/passwd").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fs_resolver_rejects_symlinks_out_of_root() {
+        let dir = std::env::temp_dir().join(format!("parserst-resolver-{}", std::process::id()));
+        let root = dir.join("root");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(dir.join("secret.txt"), "secret").unwrap();
+        std::fs::write(root.join("inside.txt"), "inside").unwrap();
+        std::os::unix::fs::symlink(dir.join("secret.txt"), root.join("link.txt")).unwrap();
+
+        let resolver = FsResolver::new(&root);
+        assert_eq!(resolver.read("inside.txt").unwrap(), "inside");
+        let err = resolver.read("link.txt").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn safe_mode_drops_or_escapes_raw_content() {
+        let doc = ".. note::\n\n   .. raw:: html\n\n      <script>alert(1)</script>\n";
+
+        let dropped = html_with(doc, &RenderOptions::new().safe_mode(true));
+        assert!(!dropped.contains("script"));
+
+        let escaped = html_with(doc, &RenderOptions::new().raw(RawMode::Escape));
+        assert!(escaped.contains("&lt;script&gt;"));
+        assert!(!escaped.contains("<script>"));
+
+        assert!(!html_of(doc).contains("script"));
+        let trusted = html_with(doc, &RenderOptions::new().safe_mode(false));
+        assert!(trusted.contains("<script>alert(1)</script>"));
+    }
+
+    #[test]
+    fn block_display_drops_raw_content() {
+        let raw = parse(".. raw:: html\n\n   <script>alert(1)</script>\n").unwrap();
+        assert_eq!(raw[0].to_string(), "");
+        let nested = parse(".. note::\n\n   .. raw:: html\n\n      <script>alert(1)</script>\n").unwrap();
+        assert!(!nested[0].to_string().contains("script"));
+    }
+
+    #[test]
+    fn text_and_attributes_are_escaped() {
+        let html = html_of("Hi <script>alert('x')</script> & bye.\n\n`go <https://e.com/?q=\"a\"&b>`_\n");
+        assert_eq!(
+            html,
+            "<p>Hi &lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt; &amp; bye.</p>\n\
+             <p><a href=\"https://e.com/?q=&quot;a&quot;&amp;b\">go</a></p>"
+        );
+
+        let html = html_of(".. image:: x.png\" onerror=\"alert(1)\n");
+        assert_eq!(html, "<img src=\"x.png&quot; onerror=&quot;alert(1)\" alt=\"\">");
+    }
+
+    #[test]
+    fn unsafe_url_schemes_are_neutralised() {
+        for url in [
+            "javascript:alert(1)",
+            "JavaScript:alert(1)",
+            " java\tscript:alert(1)",
+            "data:text/html,<b>x</b>",
+        ] {
+            let html = HtmlRenderer::new().render(&[Block::Paragraph(vec![Inline::Link {
+                text: vec![Inline::Text("click".into())],
+                url: url.into(),
+            }])]);
+            assert_eq!(html, "<p>click</p>", "{url}");
+        }
+        assert_eq!(html_of("`x <javascript:alert(1)>`_"), "<p>x</p>");
+        assert_eq!(
+            html_of(".. image:: data:image/png;base64,AAAA\n\nAfter."),
+            "\n<p>After.</p>"
+        );
+
+        for url in [
+            "https://example.com",
+            "mailto:a@b.c",
+            "docs/page.html",
+            "#frag",
+            "/a:b",
+            "?q=1:2",
+        ] {
+            assert!(HtmlRenderer::new().allows_url(url), "{url}");
+        }
+    }
+
+    #[test]
+    fn url_scheme_allowlist_is_configurable() {
+        let blocks = parse("`a <https://e.com>`_ `b <irc://e.com>`_").unwrap();
+        let html = HtmlRenderer::new().url_schemes(["irc"]).render(&blocks);
+        assert_eq!(html, "<p>a <a href=\"irc://e.com\">b</a></p>");
+    }
+
+    #[test]
+    fn directive_body_indentation_follows_first_content_line() {
+        let doc = ".. note::\n\n   Three-space body.\n\nOutside.";
+        let ast = parse(doc).unwrap();
+        assert_eq!(ast.len(), 2);
+        match &ast[0] {
+            Block::Directive { content, .. } => assert_eq!(content.len(), 1),
+            _ => panic!("expected Directive"),
+        }
+    }
+
+    #[test]
+    fn only_directive_is_kept_in_unfiltered_ast() {
+        let doc = ".. only:: html and not internal\n\n   Public HTML text.\n";
+        let ast = parse(doc).unwrap();
+        assert!(matches!(&ast[0], Block::Directive { name, argument, .. }
+            if name == "only" && argument == "html and not internal"));
+    }
+
+    #[test]
+    fn only_directive_filtered_by_parser_tags() {
+        let doc =
+            "Intro.\n\n.. only:: html and not internal\n\n   Public text.\n\n.. only:: internal\n\n   Secret text.\n";
+
+        let public = parse_with(doc, &ParserOptions::new().tags(["html"].into_iter().collect())).unwrap();
+        assert_eq!(public.len(), 2);
+        assert!(
+            ast::join_inlines(match &public[1] {
+                Block::Paragraph(inlines) => inlines,
+                _ => panic!("expected spliced paragraph"),
+            })
+            .contains("Public text")
+        );
+
+        let internal = parse_with(
+            doc,
+            &ParserOptions::new().tags(["html", "internal"].into_iter().collect()),
+        )
+        .unwrap();
+        let html: String = internal.iter().map(|b| b.to_string()).collect();
+        assert!(!html.contains("Public text"));
+        assert!(html.contains("Secret text"));
+    }
+
+    #[test]
+    fn conditionals_resolve_at_render_time() {
+        let doc = ".. note::\n\n   .. ifconfig:: pdf\n\n      Print only.\n\n   Always shown.\n";
+        let html = html_with(doc, &RenderOptions::new().tags(["html"].into_iter().collect()));
+        assert!(!html.contains("Print only"));
+        assert!(html.contains("Always shown"));
+
+        let unfiltered = apply_conditionals(parse(doc).unwrap(), &Tags::new());
+        assert_eq!(
+            unfiltered,
+            apply_conditionals(unfiltered.clone(), &["html"].into_iter().collect())
+        );
+    }
+
+    #[test]
+    fn unparsable_conditions_are_kept_and_evaluate_false() {
+        let doc = "Text.\n\n.. ifconfig:: release.startswith('1.')\n\n   Body.\n\n.. only:: html and\n\n   More.\n";
+        let ast = parse(doc).unwrap();
+        assert!(matches!(&ast[1], Block::Directive { argument, .. } if argument == "release.startswith('1.')"));
+        assert!(matches!(&ast[2], Block::Directive { argument, .. } if argument == "html and"));
+
+        let filtered = apply_conditionals(ast, &["html"].into_iter().collect());
+        assert_eq!(filtered, vec![Block::Paragraph(vec![Inline::Text("Text.".into())])]);
+    }
+
+    #[test]
+    fn parses_python_domain_objects_with_qualified_names() {
+        let doc = r#"
+.. py:module:: shapes
+
+.. py:class:: Point(x: float, y: float)
+
+   A point.
+
+   .. py:method:: distance(other: Point) -> float
+      :async:
+
+      Distance to another point.
+
+   .. py:attribute:: x
+      :type: float
+
+.. py:function:: origin() -> Point
+"#;
+        let ast = parse(doc).unwrap();
+        let class = match &ast[1] {
+            Block::PyObject(obj) => obj,
+            other => panic!("expected PyObject, got {other:?}"),
+        };
+        assert_eq!(class.kind, PyObjectKind::Class);
+        assert_eq!(class.fullname, "shapes.Point");
+        match &class.content[1] {
+            Block::PyObject(method) => {
+                assert_eq!(method.fullname, "shapes.Point.distance");
+                assert_eq!(method.option("async"), Some(""));
+                assert_eq!(method.signature.return_annotation.as_deref(), Some("float"));
+            }
+            other => panic!("expected method, got {other:?}"),
+        }
+
+        let inventory = Inventory::from_blocks(&ast);
+        let names: Vec<&str> = inventory.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "shapes",
+                "shapes.Point",
+                "shapes.Point.distance",
+                "shapes.Point.x",
+                "shapes.origin"
+            ]
+        );
+        assert_eq!(inventory.find("distance").map(|e| e.kind), Some(PyObjectKind::Method));
+        assert_eq!(
+            inventory.get("shapes").map(|e| e.anchor.as_str()),
+            Some("module-shapes")
+        );
+    }
+
+    #[test]
+    fn renders_python_objects_like_sphinx() {
+        let doc = ".. py:function:: greet(name: str, excited=False) -> str\n\n   Say hello.\n";
+        let html = html_of(doc);
+        assert!(html.contains("<dl class=\"py function\">"));
+        assert!(html.contains("<dt class=\"sig sig-object py\" id=\"greet\">"));
+        assert!(html.contains("<span class=\"sig-name descname\">greet</span>"));
+        assert!(html.contains("<span class=\"n\">name</span><span class=\"p\">:</span> <span class=\"n\">str</span>"));
+        assert!(html.contains("<span class=\"default_value\">False</span>"));
+        assert!(html.contains("<span class=\"sig-return-typehint\">str</span>"));
+        assert!(html.contains("<dd><p>Say hello.</p></dd>"));
+    }
+
+    #[test]
+    fn unsupported_python_signatures_stay_plain_directives() {
+        let doc = "Intro.\n\n.. py:data:: FOO = 1\n\n   The answer.\n\n.. py:class:: Foo[T]\n\n.. py:function:: broken(a, b\n\nAfter.\n";
+        let ast = parse(doc).unwrap();
+        assert_eq!(ast.len(), 5);
+        assert_eq!(
+            ast[1],
+            Block::Directive {
+                name: "py:data".into(),
+                argument: "FOO = 1".into(),
+                content: vec![Block::Paragraph(vec![Inline::Text("The answer.".into())])],
+            }
+        );
+        assert!(
+            matches!(&ast[2], Block::Directive { name, argument, .. } if name == "py:class" && argument == "Foo[T]")
+        );
+        assert!(matches!(&ast[3], Block::Directive { name, .. } if name == "py:function"));
+    }
+
+    #[test]
+    fn grid_table_cells_span_columns_and_rows() {
+        let doc = r#"
++-------+-------+-------+
+| Wide header   | C     |
++=======+=======+=======+
+| tall  | b     | c     |
+|       +-------+-------+
+|       | spans two     |
++-------+---------------+
+"#;
+        let ast = parse(doc).unwrap();
+        let Block::Table { headers, rows, .. } = &ast[0] else {
+            panic!("expected Table");
+        };
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0].len(), 2);
+        assert_eq!((headers[0][0].colspan, headers[0][0].rowspan), (2, 1));
+        assert_eq!(ast::join_inlines(headers[0][0].as_inlines().unwrap()), "Wide header");
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!((rows[0][0].colspan, rows[0][0].rowspan), (1, 2));
+        assert_eq!(rows[0].len(), 3);
+        assert_eq!(rows[1].len(), 1);
+        assert_eq!((rows[1][0].colspan, rows[1][0].rowspan), (2, 1));
+        assert_eq!(ast::join_inlines(rows[1][0].as_inlines().unwrap()), "spans two");
+
+        let html = html_of(doc);
+        assert!(html.contains("<th colspan=\"2\">Wide header</th>"));
+        assert!(html.contains("<td rowspan=\"2\">tall</td>"));
+        assert!(html.contains("<td colspan=\"2\">spans two</td>"));
+    }
+
+    #[test]
+    fn grid_table_keeps_multiple_header_rows() {
+        let doc = r#"
++-----+-----+
+| H1  | H2  |
++-----+-----+
+| sub | sub |
++=====+=====+
+| a   | b   |
++-----+-----+
+"#;
+        let ast = parse(doc).unwrap();
+        let Block::Table { headers, rows, .. } = &ast[0] else {
+            panic!("expected Table");
+        };
+        assert_eq!(headers.len(), 2);
+        assert_eq!(rows.len(), 1);
+        let html = html_of(doc);
+        assert!(html.contains("<thead><tr><th>H1</th><th>H2</th></tr><tr><th>sub</th><th>sub</th></tr></thead>"));
+    }
+
+    #[test]
+    fn malformed_grid_tables_stay_literal() {
+        let misaligned = "Intro.\n\n+-----+-----+\n| a   | b   |\n| c   | d    |\n+-----+-----+\n\nAfter.\n";
+        let ast = parse(misaligned).unwrap();
+        assert_eq!(
+            ast[1],
+            Block::LiteralBlock("+-----+-----+\n| a   | b   |\n| c   | d    |\n+-----+-----+".into())
+        );
+        assert_eq!(ast[2], Block::Paragraph(vec![Inline::Text("After.".into())]));
+
+        let unclosed = "+-----+-----+\n| a   | b   |\n+-----+     |\n| c     d   |\n+-----+-----+\n";
+        assert_eq!(
+            parse(unclosed).unwrap(),
+            vec![Block::LiteralBlock(unclosed.trim_end().into())]
+        );
+
+        let no_bottom = "+-----+\n| a   |\n| b   |\n";
+        assert_eq!(
+            parse(no_bottom).unwrap(),
+            vec![Block::LiteralBlock(no_bottom.trim_end().into())]
+        );
+    }
+
+    #[test]
+    fn strict_parsing_reports_malformed_grid_tables() {
+        let misaligned = "Intro.\n\n+-----+-----+\n| a   | b   |\n| c   | d    |\n+-----+-----+\n";
+        let err = parse_with(misaligned, &ParserOptions::strict()).unwrap_err();
+        assert!(
+            matches!(&err, ParseError::Invalid { line: 5, msg } if msg.contains("right border")),
+            "{err}"
+        );
+
+        let unclosed = "+-----+-----+\n| a   | b   |\n+-----+     |\n| c     d   |\n+-----+-----+\n";
+        let err = parse_with(unclosed, &ParserOptions::strict()).unwrap_err();
+        assert!(
+            matches!(&err, ParseError::Invalid { line: 3, msg } if msg.contains("never closes")),
+            "{err}"
+        );
+
+        let lenient = ParserOptions::strict().lenient_tables(true);
+        assert_eq!(
+            parse_with(unclosed, &lenient).unwrap(),
+            vec![Block::LiteralBlock(unclosed.trim_end().into())]
+        );
+    }
+
+    #[test]
+    fn grid_table_cells_hold_block_content() {
+        let doc = r#"
++------------+----------------------+
+| Option     | Notes                |
++============+======================+
+| ``-v``     | First paragraph.     |
+|            |                      |
+|            | - item one           |
+|            | - item two           |
++------------+----------------------+
+| ``-x``     | ::                   |
+|            |                      |
+|            |     run --fast       |
++------------+----------------------+
+"#;
+        let ast = parse(doc).unwrap();
+        let Block::Table { rows, .. } = &ast[0] else {
+            panic!("expected Table");
+        };
+        let notes = &rows[0][1].content;
+        assert_eq!(notes.len(), 2);
+        assert!(matches!(&notes[0], Block::Paragraph(_)));
+        assert!(matches!(&notes[1], Block::List { items, .. } if items.len() == 2));
+        assert!(
+            rows[1][1]
+                .content
+                .iter()
+                .any(|b| matches!(b, Block::LiteralBlock(code) if code == "run --fast"))
+        );
+
+        let html = html_of(doc);
+        assert!(html.contains("<td><code>-v</code></td>"));
+        assert!(html.contains("<td><p>First paragraph.</p><ul><li>item one</li><li>item two</li></ul></td>"));
+    }
+
+    #[test]
+    fn empty_table_cells_have_no_blocks() {
+        let doc = "+-----+-----+\n| a   |     |\n+-----+-----+\n";
+        let ast = parse(doc).unwrap();
+        let Block::Table { rows, .. } = &ast[0] else {
+            panic!("expected Table");
+        };
+        assert!(rows[0][1].content.is_empty());
+        assert!(html_of(doc).contains("<td>a</td><td></td>"));
+    }
+
+    #[test]
+    fn simple_table_rows_continue_when_first_column_is_blank() {
+        let doc = "=====  ==========\nTerm   Definition\n=====  ==========\nfoo    first line\n       second line\n\n       - a list\nbar    short\n=====  ==========\n";
+        let ast = parse(doc).unwrap();
+        let Block::Table { headers, rows, .. } = &ast[0] else {
+            panic!("expected Table");
+        };
+        assert_eq!(headers.len(), 1);
+        assert_eq!(rows.len(), 2);
+        assert!(matches!(
+            rows[0][1].content.as_slice(),
+            [Block::Paragraph(_), Block::List { .. }]
+        ));
+        assert!(html_of(doc).contains("second line</p><ul><li>a list</li></ul></td>"));
+    }
+
+    #[test]
+    fn simple_table_without_header() {
+        let doc = "===  ===\na    b\nc    d\n===  ===\n\nAfter.\n";
+        let ast = parse(doc).unwrap();
+        assert_eq!(ast.len(), 2);
+        let Block::Table { headers, rows, .. } = &ast[0] else {
+            panic!("expected Table");
+        };
+        assert!(headers.is_empty());
+        assert_eq!(rows.len(), 2);
+        assert!(!html_of(doc).contains("<thead>"));
+    }
+
+    #[test]
+    fn simple_table_span_underlines_and_multiple_header_rows() {
+        let doc = "=====  =====  ======\n   Inputs     Output\n------------  ------\n  A      B    A or B\n=====  =====  ======\nFalse  False  False\nTrue   False  True\n=====  =====  ======\n";
+        let ast = parse(doc).unwrap();
+        let Block::Table { headers, rows, .. } = &ast[0] else {
+            panic!("expected Table");
+        };
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers[0].iter().map(|c| c.colspan).collect::<Vec<_>>(), [2, 1]);
+        assert_eq!(headers[1].len(), 3);
+        assert_eq!(rows.len(), 2);
+        assert!(html_of(doc).contains("<th colspan=\"2\">Inputs</th><th>Output</th>"));
+    }
+
+    #[test]
+    fn simple_table_last_column_is_unbounded() {
+        let doc = "===  ===\na    b\nc    runs past the border\n===  ===\n";
+        let ast = parse(doc).unwrap();
+        assert!(html_of(doc).contains("<td>runs past the border</td>"));
+        assert!(matches!(&ast[0], Block::Table { rows, .. } if rows.len() == 2));
+    }
+
+    #[test]
+    fn malformed_simple_tables_stay_literal() {
+        let doc = "=====  =====\nA      B\n=====  =====\nabcdefg  h\n=====  =====\n";
+        assert_eq!(parse(doc).unwrap(), vec![Block::LiteralBlock(doc.trim_end().into())]);
+
+        let misaligned = "=====  =====\nA      B\n---  -------\nx      y\n=====  =====\n";
+        assert_eq!(
+            parse(misaligned).unwrap(),
+            vec![Block::LiteralBlock(misaligned.trim_end().into())]
+        );
+
+        let after = Block::Paragraph(vec![Inline::Text("After.".into())]);
+        let short_border = "=====  =====\nA      B\n====  =====\n\nAfter.\n";
+        assert_eq!(
+            parse(short_border).unwrap(),
+            vec![
+                Block::LiteralBlock("=====  =====\nA      B\n====  =====".into()),
+                after.clone()
+            ]
+        );
+        let no_bottom = "=====  =====\nA      B\n\nAfter.\n";
+        assert_eq!(
+            parse(no_bottom).unwrap(),
+            vec![Block::LiteralBlock("=====  =====\nA      B".into()), after]
+        );
+    }
+
+    #[test]
+    fn strict_parsing_reports_malformed_simple_tables() {
+        let outside = "Intro.\n\n=====  =====\nA      B\n=====  =====\nabcdefg  h\n=====  =====\n";
+        let err = parse_with(outside, &ParserOptions::strict()).unwrap_err();
+        assert!(
+            matches!(&err, ParseError::Invalid { line: 6, msg } if msg.contains("outside its column boundaries")),
+            "{err}"
+        );
+
+        let misaligned = "=====  =====\nA      B\n---  -------\nx      y\n=====  =====\n";
+        let err = parse_with(misaligned, &ParserOptions::strict()).unwrap_err();
+        assert!(
+            matches!(&err, ParseError::Invalid { line: 3, msg } if msg.contains("not aligned with the table columns")),
+            "{err}"
+        );
+
+        let lenient = ParserOptions::strict().lenient_tables(true);
+        assert_eq!(
+            parse_with(misaligned, &lenient).unwrap(),
+            vec![Block::LiteralBlock(misaligned.trim_end().into())]
+        );
+    }
+
+    #[test]
+    fn single_column_border_is_not_a_simple_table() {
+        let ast = parse("Title\n=====\n\nText.\n").unwrap();
+        assert!(matches!(&ast[0], Block::Heading { .. }));
+    }
+
+    #[test]
+    fn tables_measure_columns_in_display_width() {
+        let simple = "======  ======\n名前    説明\n======  ======\nfoo     日本語\nbar     abc\n======  ======\n";
+        let ast = parse(simple).unwrap();
+        let Block::Table { headers, rows, .. } = &ast[0] else {
+            panic!("expected Table");
+        };
+        assert_eq!(headers[0].len(), 2);
+        assert_eq!(rows.len(), 2);
+        assert!(html_of(simple).contains("<th>名前</th><th>説明</th>"));
+        assert!(html_of(simple).contains("<td>foo</td><td>日本語</td>"));
+
+        let grid = "+------+------+\n| 中文 | é́    |\n+======+======+\n| 表格 | ok   |\n+------+------+\n";
+        let html = html_of(grid);
+        assert!(html.contains("<th>中文</th><th>é́</th>"));
+        assert!(html.contains("<td>表格</td><td>ok</td>"));
+    }
+
+    #[test]
+    fn wide_text_crossing_a_column_margin_is_malformed() {
+        let doc = "=====  =====\nA      B\n=====  =====\n日本語  x\n=====  =====\n";
+        assert_eq!(parse(doc).unwrap(), vec![Block::LiteralBlock(doc.trim_end().into())]);
+    }
+
+    #[test]
+    fn strict_profile_disables_docstring_extensions() {
+        let doc = "Args:\n    x: the value\n\n```\ncode\n```\n\n> quoted\n";
+        let docstring = parse(doc).unwrap();
+        assert!(matches!(&docstring[0], Block::Heading { .. }));
+        assert!(docstring.iter().any(|b| matches!(b, Block::CodeBlock(_))));
+        assert!(docstring.iter().any(|b| matches!(b, Block::Quote(_))));
+
+        let strict = parse_with(doc, &ParserOptions::strict()).unwrap();
+        assert!(
+            !strict
+                .iter()
+                .any(|b| matches!(b, Block::Heading { .. } | Block::CodeBlock(_) | Block::Quote(_)))
+        );
+    }
+
+    #[test]
+    fn extensions_toggle_individually() {
+        let opts = ParserOptions::strict().code_fences(true);
+        let ast = parse_with("```\nlet x = 1;\n```\n\n> not a quote\n", &opts).unwrap();
+        assert_eq!(ast[0], Block::CodeBlock("let x = 1;\n".into()));
+        assert!(matches!(&ast[1], Block::Paragraph(_)));
+
+        let opts = ParserOptions::new().colon_headings(false);
+        assert!(matches!(
+            &parse_with("Returns:\n\nThe value.\n", &opts).unwrap()[0],
+            Block::Paragraph(_)
+        ));
+    }
+
+    #[test]
+    fn strict_profile_keeps_inner_markup_literal() {
+        let strict = parse_with("**bold *em***", &ParserOptions::strict()).unwrap();
+        assert_eq!(
+            strict[0],
+            Block::Paragraph(vec![
+                Inline::Strong(vec![Inline::Text("bold *em".into())]),
+                Inline::Text("*".into())
+            ])
+        );
+
+        let link = parse_with("`**x** <https://e.com>`_", &ParserOptions::strict()).unwrap();
+        assert_eq!(
+            link[0],
+            Block::Paragraph(vec![Inline::Link {
+                text: vec![Inline::Text("**x**".into())],
+                url: "https://e.com".into()
+            }])
+        );
+    }
+
+    /// Parse `doc`, write it back out and check the second parse matches the first. Markdown code
+    /// fences and quotes are written as their reST counterparts, so with those only the written form
+    /// has to be stable.
+    fn assert_round_trip(doc: &str) -> String {
+        let ast = parse(doc).unwrap();
+        let rst = to_rst(&ast);
+        let reparsed = parse(&rst).unwrap();
+        struct Markdown(bool);
+        impl visit::Visitor for Markdown {
+            fn visit_block(&mut self, block: &Block) {
+                self.0 |= matches!(block, Block::CodeBlock(_) | Block::Quote(_));
+                visit::walk_block(self, block);
+            }
+        }
+        let mut markdown = Markdown(false);
+        visit::Visitor::visit_blocks(&mut markdown, &ast);
+        if markdown.0 {
+            assert_eq!(to_rst(&reparsed), rst, "parsed as:\n{reparsed:?}");
+        } else {
+            assert_eq!(reparsed, ast, "written as:\n{rst}");
+        }
+        rst
+    }
+
+    #[test]
+    fn to_rst_round_trips_every_block_kind() {
+        let doc = r#"
+Guide
+=====
+
+Intro with *em*, **strong**, ``code`` and `a link <https://example.com>`_.
+
+Usage
+-----
+
+- first
+- second
+
+3. three
+4. four
+
+```
+fenced code
+```
+
+> quoted text
+
+::
+
+    literal
+      indented
+
+.. note::
+
+   Admonition body.
+
+   - nested list
+
+.. code-block:: python
+
+   def f():
+       return 1
+
+.. a comment
+   on two lines
+
+.. raw:: html
+
+   <b>raw</b>
+
+:param x: The value,
+   wrapped.
+
+   Second paragraph.
+:returns: Nothing.
+
+Args:
+    foo (int): The foo.
+"#;
+        let rst = assert_round_trip(doc);
+        assert!(rst.contains("Guide\n=====\n"));
+        assert!(rst.contains("1. three\n2. four"));
+        assert!(rst.contains(":param x: The value,\n   wrapped.\n\n   Second paragraph.\n:returns: Nothing."));
+        assert!(rst.contains("**foo** (*int*): The foo."));
+    }
+
+    #[test]
+    fn to_rst_writes_markdown_blocks_as_rest() {
+        let doc = "```\nx = 1\n\n  y\n```\n\n> quoted *text*\n>\n> more\n\n.. note:: Body.\n\n> after\n";
+        let rst = assert_round_trip(doc);
+        assert_eq!(
+            rst,
+            ".. code-block::\n\n   x = 1\n\n     y\n\n..\n\n   quoted *text*\n\n   more\n\n.. note:: Body.\n\n..\n\n   after\n"
+        );
+        let strict = parse_with(&rst, &ParserOptions::strict()).unwrap();
+        assert_eq!(
+            strict[0],
+            Block::Directive {
+                name: "code-block".into(),
+                argument: String::new(),
+                content: vec![Block::LiteralBlock("x = 1\n\n  y".into())]
+            }
+        );
+        assert_eq!(strict[1], Block::Comment(Vec::new()));
+        assert_eq!(
+            strict.last(),
+            Some(&Block::Paragraph(vec![Inline::Text("   after".into())]))
+        );
+    }
+
+    #[test]
+    fn to_rst_keeps_whitespace_in_text() {
+        assert_eq!(assert_round_trip("   a\n\n   b\n"), "   a\n\n   b\n");
+        assert_eq!(assert_round_trip("   [#]_ ref\n"), "   [#]_ ref\n");
+        assert_eq!(
+            assert_round_trip("two  spaces\n   and indent\n"),
+            "two  spaces\n   and indent\n"
+        );
+        assert_round_trip("   Indented::\n\n       literal\n");
+    }
+
+    #[test]
+    fn to_rst_round_trips_generated_documents() {
+        const FRAGMENTS: &[&str] = &[
+            "Plain text.",
+            "two  spaces  here",
+            "a\n   b",
+            "[#]_ ref and [1]_",
+            "*em* **strong** ``code`` `link <http://x>`_",
+            ":literal:`a\\`\\`b` and `a \\< b <http://x>`_",
+            "2 * 3 `x` \\y",
+            "Title\n=====",
+            "Sub\n---",
+            "- one\n- two",
+            "1. a\n2. b",
+            "- item\n  continued",
+            "::\n\n    literal",
+            "Example::\n\n    code",
+            ".. note::\n\n   Body.\n\n   - nested",
+            ".. [1] Note.",
+            ".. comment",
+            ":param x: Val.\n:returns: R.",
+            ":field: one\n   two\n\n   Three.",
+            "-v  Verbose.",
+            "--out=FILE\n      Write\n      here.",
+            "=====  =====\nA      B\n=====  =====\nx      y\n=====  =====",
+            "+---+---+\n| a | b |\n+===+===+\n| c | d |\n+---+---+",
+            ".. _target:",
+            "> quoted",
+            ".. raw:: html\n\n   <b>",
+            "```\ncode\n```",
+            ".. py:function:: f(a, b=1) -> int\n\n   Does f.",
+            ".. code-block:: python\n\n   x  =  1",
+        ];
+        // A small linear congruential generator keeps the documents reproducible.
+        let mut state: u64 = 7;
+        let mut pick = |n: usize| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) as usize % n
+        };
+        for _ in 0..2000 {
+            let count = 1 + pick(5);
+            let doc: Vec<String> = (0..count)
+                .map(|_| {
+                    let fragment = FRAGMENTS[pick(FRAGMENTS.len())];
+                    let plain = fragment.starts_with(char::is_alphabetic) && !fragment.contains("::");
+                    let pad = if plain { " ".repeat(pick(4)) } else { String::new() };
+                    format!("{pad}{fragment}")
+                })
+                .collect();
+            assert_round_trip(&(doc.join("\n\n") + "\n"));
+        }
+    }
+
+    #[test]
+    fn to_rst_writes_targets() {
+        let rst = assert_round_trip(".. _intro:\n\nIntro\n=====\n\n.. _`a: b`:\n\nText.\n");
+        assert_eq!(rst, ".. _intro:\n\nIntro\n=====\n\n.. _`a: b`:\n\nText.\n");
+    }
+
+    #[test]
+    fn footnotes_parse_and_round_trip() {
+        let blocks = parse(
+            "See [1]_ and [#note]_; [CIT]_ and x[2]_y stay text.\n\n.. [1] First\n   line.\n\n.. [CIT] A citation.\n",
+        )
+        .unwrap();
+        assert_eq!(
+            blocks[0],
+            Block::Paragraph(vec![
+                Inline::Text("See ".into()),
+                Inline::FootnoteRef("1".into()),
+                Inline::Text(" and ".into()),
+                Inline::FootnoteRef("#note".into()),
+                Inline::Text("; [CIT]_ and x[2]_y stay text.".into()),
+            ])
+        );
+        assert_eq!(
+            blocks[1],
+            Block::Footnote {
+                label: "1".into(),
+                content: vec![Block::Paragraph(vec![Inline::Text("First\nline.".into())])]
+            }
+        );
+        assert!(matches!(blocks[2], Block::Comment(_)));
+        assert_eq!(
+            blocks[1].to_string(),
+            "<aside class=\"footnote\" id=\"footnote-1\"><span class=\"label\">[1]</span><p>First\nline.</p></aside>"
+        );
+
+        let rst = assert_round_trip(".. [#] A note with [1]_.\n\n   More.\n\nLiteral \\[1]_ here.\n");
+        assert_eq!(
+            rst,
+            ".. [#]\n   A note with [1]_.\n\n   More.\n\nLiteral \\[1]_ here.\n"
+        );
+    }
+
+    #[test]
+    fn to_rst_writes_option_lists() {
+        let rst = assert_round_trip("-v, --verbose  Be loud.\n--out=FILE\n      Write\n      here.\n\n      More.\n");
+        assert_eq!(
+            rst,
+            "-v, --verbose  Be loud.\n--out=FILE\n   Write\n   here.\n\n   More.\n"
+        );
+    }
+
+    #[test]
+    fn to_rst_sizes_adornments_to_display_width() {
+        let ast = vec![Block::Heading { level: 2, inlines: vec![Inline::Text("表格".into())], id: String::new() }];
+        assert_eq!(to_rst(&ast), "表格\n----\n");
+    }
+
+    #[test]
+    fn to_rst_escapes_literal_markup_characters() {
+        let ast = vec![Block::Paragraph(vec![
+            Inline::Text("2 * 3 and `x` in C:\\dir".into()),
+            Inline::Em(vec![Inline::Text("a*b".into())]),
+        ])];
+        let rst = to_rst(&ast);
+        assert_eq!(rst, "2 \\* 3 and \\`x\\` in C:\\\\dir*a\\*b*\n");
+        assert_eq!(parse(&rst).unwrap(), ast);
+        assert_eq!(parse_with(&rst, &ParserOptions::strict()).unwrap(), ast);
+    }
+
+    #[test]
+    fn to_rst_escapes_code_and_link_text() {
+        let ast = vec![Block::Paragraph(vec![
+            Inline::Code("a``b".into()),
+            Inline::Text(" ".into()),
+            Inline::Code("\\*tick`".into()),
+            Inline::Text(" ".into()),
+            Inline::Link { text: vec![Inline::Text("a < b".into())], url: "https://e.com".into() },
+        ])];
+        let rst = to_rst(&ast);
+        assert_eq!(
+            rst,
+            ":literal:`a\\`\\`b` :literal:`\\\\*tick\\`` `a \\< b <https://e.com>`_\n"
+        );
+        assert_eq!(parse(&rst).unwrap(), ast);
+        assert_eq!(parse_with(&rst, &ParserOptions::strict()).unwrap(), ast);
+    }
+
+    #[test]
+    fn to_rst_regenerates_simple_tables() {
+        let doc = "=====  =====\nA      B\n=====  =====\nlong   *x*\ny      \n=====  =====\n";
+        let rst = assert_round_trip(doc);
+        assert_eq!(
+            rst,
+            "=====  =====\nA      B\n=====  =====\nlong   *x*\ny\n=====  =====\n"
+        );
+    }
+
+    #[test]
+    fn to_rst_draws_grid_tables_with_spans() {
+        let doc = r#"
++-------+-------+-------+
+| Wide header   | C     |
++=======+=======+=======+
+| tall  | b     | c     |
+|       +-------+-------+
+|       | spans two     |
++-------+-------+-------+
+| - a   | 日本  | x     |
+| - b   |       |       |
++-------+-------+-------+
+"#;
+        let rst = assert_round_trip(doc);
+        assert_eq!(
+            rst,
+            doc.trim_start()
+                .replacen("+-------+-------+-------+", "+---------------+-------+", 1)
+        );
+    }
+
+    #[test]
+    fn to_rst_writes_python_objects() {
+        let doc = r#"
+.. py:module:: pkg
+
+.. py:class:: Base
+
+   :noindex:
+
+   .. py:method:: run(self, a: int, /, b=2, *args, c, **kw) -> bool
+      :async:
+
+      Runs it.
+
+.. py:function:: helper(*, flag: bool = False)
+"#;
+        let rst = assert_round_trip(doc);
+        assert!(rst.contains(".. py:method:: run(self, a: int, /, b=2, *args, c, **kw) -> bool\n      :async:\n"));
+        assert!(rst.contains(".. py:function:: helper(*, flag: bool = False)"));
+    }
+
+    #[test]
+    fn paragraph_ending_in_double_colon_introduces_literal_block() {
+        let ast = parse("Example::\n\n    x = 1\n\nSpaced ::\n\n    y\n").unwrap();
+        assert_eq!(
+            ast,
+            vec![
+                Block::Paragraph(vec![Inline::Text("Example:".into())]),
+                Block::LiteralBlock("x = 1".into()),
+                Block::Paragraph(vec![Inline::Text("Spaced".into())]),
+                Block::LiteralBlock("y".into()),
+            ]
+        );
+        assert_eq!(to_rst(&ast), "Example::\n\n    x = 1\n\nSpaced ::\n\n    y\n");
+    }
+
+    #[test]
+    fn list_items_continue_on_indented_lines() {
+        let ast = parse("- first\n  continued\n- second\n").unwrap();
+        assert_eq!(
+            ast[0],
+            Block::List {
+                kind: ListKind::Unordered,
+                items: vec![
+                    vec![Inline::Text("first\ncontinued".into())],
+                    vec![Inline::Text("second".into())]
+                ],
+            }
+        );
+    }
+
+    /// Blocks of `src` written on single lines with tables fitted to their content, so formatted
+    /// text compares equal.
+    fn unwrapped(src: &str) -> String {
+        let blocks = format::unformatted(parse_with(src, &ParserOptions::strict()).unwrap());
+        rst::write_blocks(&blocks, Some(usize::MAX))
+    }
+
+    #[test]
+    fn format_rewraps_without_breaking_markup() {
+        let src = "A paragraph with **strong words** and `a link <https://example.com/x>`_ that\nis long.\n\n- an item that is long enough to wrap\n";
+        let out = format(src, &FormatOptions::new().width(24)).unwrap();
+        assert_eq!(
+            out,
+            "A paragraph with\n**strong words** and\n`a link <https://example.com/x>`_\nthat is long.\n\n\
+             - an item that is long\n  enough to wrap\n"
+        );
+        assert_eq!(unwrapped(&out), unwrapped(src));
+    }
+
+    #[test]
+    fn format_keeps_literal_and_code_blocks() {
+        let src = "Example::\n\n    x  =  1\n      y\n\n.. code-block:: python\n\n     def  f():\n         pass\n";
+        let out = format(src, &FormatOptions::new().width(10)).unwrap();
+        assert_eq!(
+            out,
+            "Example::\n\n    x  =  1\n      y\n\n.. code-block:: python\n\n   def  f():\n       pass\n"
+        );
+    }
+
+    #[test]
+    fn format_normalises_structure() {
+        let src = r#"
+Title
+=========
+
+Sub
+---------------
+
+7. seven
+9. nine
+
+.. note::
+
+        Indented   too far.
+
+========  =====
+a         b
+long      c
+========  =====
+"#;
+        let out = format(src, &FormatOptions::new()).unwrap();
+        assert_eq!(
+            out,
+            "Title\n=====\n\nSub\n---\n\n1. seven\n2. nine\n\n.. note::\n\n   Indented   too far.\n\n\
+             ====  =\na     b\nlong  c\n====  =\n"
+        );
+        assert_eq!(format(&out, &FormatOptions::new()).unwrap(), out);
+        assert_eq!(unwrapped(&out), unwrapped(src));
+    }
+
+    /// Sources mixing constructs `format` rewrites with ones the tree doesn't hold losslessly.
+    const FORMAT_CORPUS: &[&str] = &[
+        "Title\n==\n\nSome   text that\nwraps.\n\n3. a\n7. b\n",
+        "See :ref:`the   target` and :func:`len`\nfor more.\n",
+        "Read `Python`_ or `the docs <https://docs.python.org>`__\nfirst.\n\n.. _Python: https://python.org\n",
+        "=======\n Title\n=======\n\nSub\n~~~\n\nBody  text\nhere.\n",
+        "#. one\n#. two\n\nterm\n    Its   definition.\n\n- a\n  - nested\n- b\n",
+        ".. note::\n   :class: aside\n\n   Noted   here.\n\n.. image:: a.png\n   :alt: An image\n",
+        "*unclosed emphasis\nTitle\n=====\n",
+        "> quoted\n\n   -v, --verbose  Be loud.\n\n--opt=VALUE  Option.\n",
+        "Para\n- item one\n- item   two\n\n+---+----+\n| a | bb |\n+---+----+\n\n=====  ===\nx      y\n=====  ===\n",
+        "A paragraph::\n\n    literal   text\n\n:field: value\n    more\n",
+        "Words with |sub| and [1]_ refs, *emphasis   here* and ``code``.\n\n.. [1] A   note.\n\n.. |sub| replace:: text\n",
+    ];
+
+    fn format_corpus() -> impl Iterator<Item = String> {
+        let pairs = FORMAT_CORPUS.windows(2).map(|pair| pair.join("\n"));
+        FORMAT_CORPUS.iter().map(|src| src.to_string()).chain(pairs)
+    }
+
+    #[test]
+    fn format_is_idempotent_and_keeps_the_parse() {
+        for width in [16, 79] {
+            let opts = FormatOptions::new().width(width);
+            let strict = ParserOptions::strict();
+            for src in format_corpus() {
+                let out = format(&src, &opts).unwrap();
+                assert_eq!(format(&out, &opts).unwrap(), out, "{src:?}");
+                let parsed = |text| {
+                    format::unformatted(parse_with(text, &strict).unwrap())
+                        .into_iter()
+                        .map(Block::into_owned)
+                };
+                assert!(parsed(&out).eq(parsed(&src)), "{src:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn format_copies_what_the_tree_loses() {
+        let opts = FormatOptions::new();
+        for src in [FORMAT_CORPUS[1], FORMAT_CORPUS[2], FORMAT_CORPUS[5], FORMAT_CORPUS[6]] {
+            assert_eq!(format(src, &opts).unwrap(), src);
+        }
+        assert_eq!(format(FORMAT_CORPUS[4], &opts).unwrap(), FORMAT_CORPUS[4]);
+    }
+
+    #[test]
+    fn format_adorns_titles_in_the_house_style() {
+        let src = "=======\n Title\n=======\n\nSub\n~~~~~~~~\n\nBody  text\nhere.\n";
+        assert_eq!(
+            format(src, &FormatOptions::new()).unwrap(),
+            "Title\n=====\n\nSub\n~~~\n\nBody text here.\n"
+        );
+        assert_eq!(
+            format(src, &FormatOptions::new().overlined(1)).unwrap(),
+            "=====\nTitle\n=====\n\nSub\n~~~\n\nBody text here.\n"
+        );
+        let blocks = parse(src).unwrap();
+        assert!(matches!(&blocks[0], Block::Heading { level: 1, .. }));
+        assert!(matches!(&blocks[1], Block::Heading { level: 3, .. }));
+    }
+
+    #[test]
+    fn blocks_borrow_unchanged_text_from_the_input() {
+        const DOC: &str = "Title\n=====\n\nSome *text* with ``code`` and `a link <https://a.b>`_\nover two lines.\n\n\
+                           Escaped \\*star\\*.\n\n- item\n\n```\nlet x = 1;\n```\n\n.. note:: Nested.\n";
+        let input = DOC.to_string();
+        let blocks = parse(&input).unwrap();
+
+        let Block::Paragraph(inlines) = &blocks[1] else { panic!("expected a paragraph") };
+        for inline in inlines {
+            match inline {
+                Inline::Text(text) | Inline::Code(text) | Inline::Link { url: text, .. } => {
+                    assert!(matches!(text, Cow::Borrowed(_)), "{text:?} was copied")
+                }
+                _ => {}
+            }
+        }
+        assert!(matches!(&inlines[..], [.., Inline::Text(last)] if last == "\nover two lines."));
+        let [heading, _, escaped, list, code, note] = &blocks[..] else {
+            panic!("unexpected blocks {blocks:?}")
+        };
+        assert!(
+            matches!(heading, Block::Heading { inlines, .. } if matches!(&inlines[..], [Inline::Text(Cow::Borrowed("Title"))]))
+        );
+        assert!(matches!(escaped, Block::Paragraph(inlines) if matches!(&inlines[..], [Inline::Text(Cow::Owned(_))])));
+        assert!(
+            matches!(list, Block::List { items, .. } if matches!(&items[0][..], [Inline::Text(Cow::Borrowed("item"))]))
+        );
+        assert!(matches!(code, Block::CodeBlock(Cow::Borrowed("let x = 1;\n"))));
+        assert!(matches!(note, Block::Directive { .. }));
+
+        let owned: Vec<Block<'static>> = blocks.into_iter().map(Block::into_owned).collect();
+        drop(input);
+        assert_eq!(owned, parse(DOC).unwrap());
+    }
+
+    #[test]
+    fn crlf_input_parses_like_lf() {
+        let lf = "Title\n=====\n\nTwo\nlines.\n\n```\ncode\n```\n\nEnd::\n\n    literal\n";
+        let crlf = lf.replace('\n', "\r\n");
+        assert_eq!(parse(&crlf).unwrap(), parse(lf).unwrap());
+    }
 }
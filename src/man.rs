@@ -0,0 +1,463 @@
+//! Man page (roff) output.
+
+use crate::ast::capitalize;
+use crate::html::ADMONITIONS;
+use crate::render::{RenderOptions, prepare};
+use crate::text::Style;
+use crate::{Block, Field, Inline, ListKind, OptionItem, PyObject, PyObjectKind};
+
+/// Renders a document as a man page, using the `man` macro package.
+///
+/// A leading title heading names the page in `.TH`, and a subtitle heading directly below it becomes
+/// the `NAME` section (`title \- subtitle`). A field list right after them supplies the rest of the
+/// `.TH` line from `:Manual section:`, `:Date:`, `:Version:` and `:Manual group:`, unless set on the
+/// renderer. The top remaining heading level becomes `.SH` and deeper ones `.SS`.
+///
+/// Option lists and field lists become `.TP` entries, code and tables are set unfilled with
+/// `.nf`/`.fi`, and emphasis and strong text use `\fI` and `\fB`. Backslashes and lines that would
+/// start with a control character are escaped.
+///
+/// ```
+/// use parserst::{ManRenderer, parse};
+///
+/// let doc = "tool\n====\n\ndo things\n---------\n\n:Manual section: 1\n\nOptions\n=======\n\n-v, --verbose  Be *loud*.\n";
+/// assert_eq!(
+///     ManRenderer::new().render(&parse(doc).unwrap()),
+///     ".TH \"TOOL\" \"1\" \"\" \"\" \"\"\n.SH NAME\ntool \\- do things\n.SH \"Options\"\n\
+///      .TP\n\\fB\\-v\\fP, \\fB\\-\\-verbose\\fP\nBe \\fIloud\\fP.\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ManRenderer {
+    title: Option<String>,
+    section: Option<String>,
+    date: Option<String>,
+    source: Option<String>,
+    manual: Option<String>,
+    options: RenderOptions,
+}
+
+impl ManRenderer {
+    /// Takes the `.TH` fields from the document.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Page title, instead of the document's title heading.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Manual section (`1` for commands, `5` for file formats, ...), instead of `:Manual section:`.
+    pub fn section(mut self, section: impl Into<String>) -> Self {
+        self.section = Some(section.into());
+        self
+    }
+
+    /// Date shown in the footer, instead of `:Date:`.
+    pub fn date(mut self, date: impl Into<String>) -> Self {
+        self.date = Some(date.into());
+        self
+    }
+
+    /// Source of the page, usually the program and its version, instead of `:Version:`.
+    pub fn source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Manual name shown in the header, instead of `:Manual group:`.
+    pub fn manual(mut self, manual: impl Into<String>) -> Self {
+        self.manual = Some(manual.into());
+        self
+    }
+
+    /// Apply `options` (raw content handling, conditional directives) when rendering a document.
+    pub fn options(mut self, options: RenderOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Render a complete man page.
+    pub fn render(&self, blocks: &[Block]) -> String {
+        let blocks = prepare(blocks, &self.options);
+        let mut rest: &[Block] = &blocks;
+
+        let mut title = None;
+        let mut subtitle = None;
+        if let [Block::Heading { level, inlines, .. }, after @ ..] = rest {
+            title = Some(plain(inlines));
+            rest = after;
+            if let [Block::Heading { level: sub, inlines, .. }, after @ ..] = rest
+                && sub > level
+            {
+                subtitle = Some(plain(inlines));
+                rest = after;
+            }
+        }
+
+        let info = |key: &str| docinfo(rest, key);
+        let section = self.section.clone().or_else(|| info("manual section"));
+        let date = self.date.clone().or_else(|| info("date"));
+        let source = self.source.clone().or_else(|| info("version"));
+        let manual = self.manual.clone().or_else(|| info("manual group"));
+        if let [Block::FieldList { fields }, after @ ..] = rest
+            && fields.iter().any(|field| DOCINFO.contains(&field_name(field).as_str()))
+        {
+            rest = after;
+        }
+
+        let title = self.title.clone().or(title).unwrap_or_default();
+        let mut out = vec![format!(
+            ".TH {} {} {} {} {}",
+            quote(&title.to_uppercase()),
+            quote(section.as_deref().unwrap_or("1")),
+            quote(date.as_deref().unwrap_or_default()),
+            quote(source.as_deref().unwrap_or_default()),
+            quote(manual.as_deref().unwrap_or_default())
+        )];
+        if let Some(subtitle) = subtitle {
+            out.push(format!(".SH NAME\n{} \\- {}", escape(&title), escape(&subtitle)));
+        }
+
+        let top = rest
+            .iter()
+            .filter_map(|block| match block {
+                Block::Heading { level, .. } => Some(*level),
+                _ => None,
+            })
+            .min()
+            .unwrap_or(1);
+        let writer = Out { top };
+        out.extend(rest.iter().filter_map(|block| writer.block(block, false)));
+        let mut out = out.join("\n");
+        out.push('\n');
+        out
+    }
+}
+
+/// Field names read from the field list after the title.
+const DOCINFO: &[&str] = &["manual section", "date", "version", "manual group"];
+
+fn field_name(field: &Field) -> String {
+    let name = if field.argument.is_empty() {
+        field.name.clone()
+    } else {
+        format!("{} {}", field.name, field.argument)
+    };
+    name.to_lowercase()
+}
+
+/// The value of the `key` field in a field list at the start of `blocks`.
+fn docinfo(blocks: &[Block], key: &str) -> Option<String> {
+    let Some(Block::FieldList { fields }) = blocks.first() else { return None };
+    fields.iter().find(|field| field_name(field) == key).map(|field| {
+        let body: Vec<String> = field
+            .body
+            .iter()
+            .filter_map(|block| match block {
+                Block::Paragraph(inlines) => Some(plain(inlines)),
+                _ => None,
+            })
+            .collect();
+        body.join(" ")
+    })
+}
+
+struct Out {
+    /// Heading level written as `.SH`; deeper headings are `.SS`.
+    top: u8,
+}
+
+impl Out {
+    fn blocks(&self, blocks: &[Block], indented: bool) -> Vec<String> {
+        blocks.iter().filter_map(|block| self.block(block, indented)).collect()
+    }
+
+    /// The requests for `block`, or `None` when it has no man page form. Inside a `.TP` or `.IP`
+    /// entry (`indented`), paragraphs start with `.IP` so they keep the entry's indentation.
+    fn block(&self, block: &Block, indented: bool) -> Option<String> {
+        let paragraph = if indented { ".IP" } else { ".PP" };
+        Some(match block {
+            Block::Heading { level, inlines, .. } => {
+                let request = if *level <= self.top { ".SH" } else { ".SS" };
+                format!("{request} {}", quote(&plain(inlines)))
+            }
+            Block::Paragraph(inlines) => format!("{paragraph}\n{}", text(inlines)),
+            Block::List { kind, items } => items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    let marker = match kind {
+                        ListKind::Unordered => "\\(bu 2".to_string(),
+                        ListKind::Ordered => format!("{}. 4", i + 1),
+                    };
+                    format!(".IP {marker}\n{}", text(item))
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Block::CodeBlock(code) | Block::LiteralBlock(code) => unfilled(code, indented),
+            Block::Quote(children) => format!(".RS 4\n{}\n.RE", self.blocks(children, false).join("\n")),
+            Block::Directive { name, argument, content } => self.directive(name, argument, content, indented)?,
+            Block::Table { .. } => unfilled(
+                &Style::default().blocks(std::slice::from_ref(block), usize::MAX),
+                indented,
+            ),
+            Block::Comment(_) | Block::Target { .. } => return None,
+            Block::FieldList { fields } => fields
+                .iter()
+                .map(|field| {
+                    let tag = if field.argument.is_empty() {
+                        escape(&field.name)
+                    } else {
+                        format!("{} {}", escape(&field.name), escape(&field.argument))
+                    };
+                    format!(".TP\n\\fB{tag}\\fP\n{}", self.entry(&field.body))
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Block::OptionList { items } => items
+                .iter()
+                .map(|item| self.option_item(item))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Block::Raw { formats, content } if formats.iter().any(|format| format == "man") => content.clone(),
+            Block::Raw { .. } => return None,
+            Block::PyObject(obj) => self.py_object(obj)?,
+            Block::Footnote { label, content } => {
+                format!(".IP {} 4\n{}", quote(&format!("[{label}]")), self.entry(content))
+            }
+        })
+    }
+
+    fn directive(&self, name: &str, argument: &str, content: &[Block], indented: bool) -> Option<String> {
+        Some(match name {
+            "only" | "ifconfig" => self.blocks(content, indented).join("\n"),
+            "highlight" | "image" => return None,
+            "code-block" | "code" => {
+                let code: Vec<&str> = content
+                    .iter()
+                    .filter_map(|block| match block {
+                        Block::LiteralBlock(code) => Some(code.as_ref()),
+                        _ => None,
+                    })
+                    .collect();
+                unfilled(&code.join("\n\n"), indented)
+            }
+            _ => {
+                let paragraph = if indented { ".IP" } else { ".PP" };
+                let mut head = format!("{paragraph}\n\\fB{}:\\fP", escape(&capitalize(name)));
+                if !argument.is_empty() {
+                    head.push(' ');
+                    head.push_str(&escape(argument));
+                }
+                let body = self.blocks(content, false);
+                if body.is_empty() {
+                    head
+                } else if ADMONITIONS.contains(&name) || !indented {
+                    format!("{head}\n.RS 4\n{}\n.RE", body.join("\n"))
+                } else {
+                    format!("{head}\n{}", self.blocks(content, true).join("\n"))
+                }
+            }
+        })
+    }
+
+    /// `.TP` with the options in bold and their arguments in italics.
+    fn option_item(&self, item: &OptionItem) -> String {
+        let options: Vec<String> = item
+            .options
+            .iter()
+            .map(|option| match option.find([' ', '=']) {
+                Some(i) => format!(
+                    "\\fB{}\\fP{}\\fI{}\\fP",
+                    escape(&option[..i]).replace('-', "\\-"),
+                    &option[i..=i],
+                    escape(&option[i + 1..])
+                ),
+                None => format!("\\fB{}\\fP", escape(option).replace('-', "\\-")),
+            })
+            .collect();
+        format!(".TP\n{}\n{}", options.join(", "), self.entry(&item.body))
+    }
+
+    fn py_object(&self, obj: &PyObject) -> Option<String> {
+        match obj.kind {
+            PyObjectKind::CurrentModule => None,
+            PyObjectKind::Module => Some(self.blocks(&obj.content, false).join("\n")),
+            _ => Some(format!(
+                ".TP\n\\fB{}\\fP\n{}",
+                escape(&obj.signature.to_string()),
+                self.entry(&obj.content)
+            )),
+        }
+    }
+
+    /// The body of a `.TP` or `.IP` entry: a leading paragraph follows the tag directly.
+    fn entry(&self, body: &[Block]) -> String {
+        let (first, rest) = match body.split_first() {
+            Some((Block::Paragraph(inlines), rest)) => (Some(text(inlines)), rest),
+            _ => (None, body),
+        };
+        first
+            .into_iter()
+            .chain(self.blocks(rest, true))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// `code` set line for line in a constant-width font.
+fn unfilled(code: &str, indented: bool) -> String {
+    let lines: Vec<String> = code.lines().map(|line| guard(&escape(line))).collect();
+    let space = if indented { ".IP" } else { ".sp" };
+    format!("{space}\n.nf\n.ft C\n{}\n.ft P\n.fi", lines.join("\n"))
+}
+
+/// Filled text for `inlines`: one output line per source line, with no leading spaces.
+fn text(inlines: &[Inline]) -> String {
+    let text: String = inlines.iter().map(inline).collect();
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(guard)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn inline(inline: &Inline) -> String {
+    match inline {
+        Inline::Text(t) => escape(t),
+        Inline::Em(children) => format!("\\fI{}\\fP", children.iter().map(self::inline).collect::<String>()),
+        Inline::Strong(children) => format!("\\fB{}\\fP", children.iter().map(self::inline).collect::<String>()),
+        Inline::Code(t) => format!("\\fB{}\\fP", escape(t)),
+        Inline::Link { text, url } => {
+            let label: String = text.iter().map(self::inline).collect();
+            if plain(text).trim() == url { escape(url) } else { format!("{label} <{}>", escape(url)) }
+        }
+        Inline::FootnoteRef(label) => format!("[{}]", escape(label)),
+    }
+}
+
+/// Text of `inlines` without markup.
+fn plain(inlines: &[Inline]) -> String {
+    Style::default().words(inlines, &[]).join(" ")
+}
+
+/// Escape backslashes, which start roff escapes.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\e")
+}
+
+/// Keep a line that starts with `.` or `'` from being read as a request.
+fn guard(line: &str) -> String {
+    if line.starts_with(['.', '\'']) { format!("\\&{line}") } else { line.to_string() }
+}
+
+/// A request argument in double quotes.
+fn quote(s: &str) -> String {
+    format!("\"{}\"", escape(s).replace('"', "\\(dq"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn renders_a_man_page() {
+        let doc = "\
+mytool
+======
+
+frobnicate files
+----------------
+
+:Manual section: 8
+:Date: 2024-01-01
+:Version: mytool 1.2
+:Manual group: System Manager's Manual
+
+SYNOPSIS
+========
+
+**mytool** [*options*] FILE
+
+Options
+-------
+
+--output=FILE  Write to FILE.
+-q             Quiet.
+
+Example
+-------
+
+::
+
+   mytool -q x
+
+- one
+- two
+";
+        assert_eq!(
+            ManRenderer::new().render(&parse(doc).unwrap()),
+            "\
+.TH \"MYTOOL\" \"8\" \"2024-01-01\" \"mytool 1.2\" \"System Manager's Manual\"
+.SH NAME
+mytool \\- frobnicate files
+.SH \"SYNOPSIS\"
+.PP
+\\fBmytool\\fP [\\fIoptions\\fP] FILE
+.SS \"Options\"
+.TP
+\\fB\\-\\-output\\fP=\\fIFILE\\fP
+Write to FILE.
+.TP
+\\fB\\-q\\fP
+Quiet.
+.SS \"Example\"
+.sp
+.nf
+.ft C
+mytool -q x
+.ft P
+.fi
+.IP \\(bu 2
+one
+.IP \\(bu 2
+two
+"
+        );
+    }
+
+    #[test]
+    fn escapes_backslashes_and_control_lines() {
+        let blocks = parse("Path C:\\\\dir and\n.start or\n'quote.\n\n::\n\n   .hidden\n").unwrap();
+        let page = ManRenderer::new().title("x").section("1").render(&blocks);
+        assert_eq!(
+            page,
+            ".TH \"X\" \"1\" \"\" \"\" \"\"\n.PP\nPath C:\\edir and\n\\&.start or\n\\&'quote.\n\
+             .sp\n.nf\n.ft C\n\\&.hidden\n.ft P\n.fi\n"
+        );
+    }
+
+    #[test]
+    fn renderer_settings_override_the_document() {
+        let doc = "tool\n====\n\n:Manual section: 1\n:Date: old\n\nText.\n";
+        let page = ManRenderer::new()
+            .section("5")
+            .date("2025-02-02")
+            .render(&parse(doc).unwrap());
+        assert!(page.starts_with(".TH \"TOOL\" \"5\" \"2025-02-02\" \"\" \"\"\n.PP\nText.\n"));
+    }
+
+    #[test]
+    fn entries_keep_later_paragraphs_indented() {
+        let doc = "-v  Verbose.\n\n    More detail.\n";
+        assert_eq!(
+            ManRenderer::new().title("t").render(&parse(doc).unwrap()),
+            ".TH \"T\" \"1\" \"\" \"\" \"\"\n.TP\n\\fB\\-v\\fP\nVerbose.\n.IP\nMore detail.\n"
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Configuration for [`parse_with`](crate::parse_with).
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::resolver::FileResolver;
+use crate::tags::Tags;
+
+/// Options that control how input is parsed.
+///
+/// Two profiles are available. [`ParserOptions::docstring`], the default, accepts the non-reST
+/// conveniences common in Python docstrings: Markdown code fences, `>` quotes, `Args:`-style headings,
+/// nested inline markup and malformed tables kept as literal text. [`ParserOptions::strict`] turns all
+/// of them off so ordinary reST files are read the way docutils reads them. Each extension can also be toggled on its own.
+///
+/// Built with chained setters:
+///
+/// ```
+/// use parserst::{FsResolver, ParserOptions};
+///
+/// let opts = ParserOptions::new().file_resolver(FsResolver::new("docs"));
+/// assert!(opts.resolver().is_some());
+///
+/// let opts = ParserOptions::strict().code_fences(true);
+/// assert!(opts.code_fences_enabled() && !opts.colon_headings_enabled());
+/// ```
+#[derive(Clone)]
+pub struct ParserOptions {
+    file_resolver: Option<Arc<dyn FileResolver>>,
+    tags: Option<Tags>,
+    code_fences: bool,
+    markdown_quotes: bool,
+    colon_headings: bool,
+    nested_inline_markup: bool,
+    lenient_tables: bool,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self::docstring()
+    }
+}
+
+impl ParserOptions {
+    /// The default profile, same as [`ParserOptions::docstring`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Profile for Python docstrings, with every non-reST extension enabled.
+    pub fn docstring() -> Self {
+        Self {
+            file_resolver: None,
+            tags: None,
+            code_fences: true,
+            markdown_quotes: true,
+            colon_headings: true,
+            nested_inline_markup: true,
+            lenient_tables: true,
+        }
+    }
+
+    /// Profile that follows the reST specification, with every extension disabled.
+    pub fn strict() -> Self {
+        Self {
+            code_fences: false,
+            markdown_quotes: false,
+            colon_headings: false,
+            nested_inline_markup: false,
+            lenient_tables: false,
+            ..Self::docstring()
+        }
+    }
+
+    /// Accept Markdown ```` ``` ```` fences as code blocks.
+    pub fn code_fences(mut self, enabled: bool) -> Self {
+        self.code_fences = enabled;
+        self
+    }
+
+    /// Accept Markdown `>` prefixed lines as block quotes.
+    pub fn markdown_quotes(mut self, enabled: bool) -> Self {
+        self.markdown_quotes = enabled;
+        self
+    }
+
+    /// Treat Google-style section lines such as `Args:` as headings.
+    pub fn colon_headings(mut self, enabled: bool) -> Self {
+        self.colon_headings = enabled;
+        self
+    }
+
+    /// Parse inline markup inside other inline markup, e.g. `**bold *and em***`.
+    ///
+    /// reST does not nest inline markup; with this disabled the inner text is kept literally.
+    pub fn nested_inline_markup(mut self, enabled: bool) -> Self {
+        self.nested_inline_markup = enabled;
+        self
+    }
+
+    /// Keep a malformed table as a literal block holding its source instead of failing with
+    /// [`ParseError::Invalid`](crate::ParseError::Invalid) at the line where it goes wrong.
+    pub fn lenient_tables(mut self, enabled: bool) -> Self {
+        self.lenient_tables = enabled;
+        self
+    }
+
+    /// Allow directives such as `raw` to read files through `resolver`.
+    pub fn file_resolver(mut self, resolver: impl FileResolver + 'static) -> Self {
+        self.file_resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Resolve `only`/`ifconfig` directives against `tags` once parsing finishes.
+    ///
+    /// Leave unset to keep conditional directives in the tree, e.g. to serialize the unfiltered AST and
+    /// run [`apply_conditionals`](crate::apply_conditionals) later.
+    pub fn tags(mut self, tags: Tags) -> Self {
+        self.tags = Some(tags);
+        self
+    }
+
+    /// The configured file resolver, if file access has been enabled.
+    pub fn resolver(&self) -> Option<&dyn FileResolver> {
+        self.file_resolver.as_deref()
+    }
+
+    pub fn active_tags(&self) -> Option<&Tags> {
+        self.tags.as_ref()
+    }
+
+    pub fn code_fences_enabled(&self) -> bool {
+        self.code_fences
+    }
+
+    pub fn markdown_quotes_enabled(&self) -> bool {
+        self.markdown_quotes
+    }
+
+    pub fn colon_headings_enabled(&self) -> bool {
+        self.colon_headings
+    }
+
+    pub fn nested_inline_markup_enabled(&self) -> bool {
+        self.nested_inline_markup
+    }
+
+    pub fn lenient_tables_enabled(&self) -> bool {
+        self.lenient_tables
+    }
+}
+
+impl fmt::Debug for ParserOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParserOptions")
+            .field("file_resolver", &self.file_resolver.is_some())
+            .field("tags", &self.tags)
+            .field("code_fences", &self.code_fences)
+            .field("markdown_quotes", &self.markdown_quotes)
+            .field("colon_headings", &self.colon_headings)
+            .field("nested_inline_markup", &self.nested_inline_markup)
+            .field("lenient_tables", &self.lenient_tables)
+            .finish()
+    }
+}
@@ -0,0 +1,101 @@
+//! Renderer-level options shared by every output format.
+
+use std::borrow::Cow;
+
+use crate::tags::{Tags, apply_conditionals};
+use crate::visit::Fold;
+use crate::{Block, HtmlRenderer};
+
+/// How renderers treat [`Block::Raw`] content.
+///
+/// Raw content is dropped unless [`Passthrough`](Self::Passthrough) is asked for, so rendering
+/// untrusted input never emits markup it supplied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RawMode {
+    /// Emit raw content verbatim when one of its formats matches the renderer. Only for trusted input.
+    Passthrough,
+    /// Show raw content as an escaped literal block instead of live markup.
+    Escape,
+    /// Leave raw content out of the output entirely.
+    #[default]
+    Drop,
+}
+
+/// Options applied at render time, independent of how the document was parsed.
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptions {
+    raw: RawMode,
+    tags: Option<Tags>,
+}
+
+impl RenderOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Choose how [`Block::Raw`] nodes are rendered.
+    pub fn raw(mut self, mode: RawMode) -> Self {
+        self.raw = mode;
+        self
+    }
+
+    /// Safe mode for untrusted input, on by default: raw content is dropped. Turning it off passes
+    /// raw content through, like [`RawMode::Passthrough`].
+    pub fn safe_mode(self, enabled: bool) -> Self {
+        self.raw(if enabled { RawMode::Drop } else { RawMode::Passthrough })
+    }
+
+    /// Resolve `only`/`ifconfig` directives against `tags` before rendering.
+    pub fn tags(mut self, tags: Tags) -> Self {
+        self.tags = Some(tags);
+        self
+    }
+
+    pub fn raw_mode(&self) -> RawMode {
+        self.raw
+    }
+
+    pub fn active_tags(&self) -> Option<&Tags> {
+        self.tags.as_ref()
+    }
+}
+
+/// Render parsed blocks to HTML, honoring `opts`.
+pub fn render_html(blocks: &[Block], opts: &RenderOptions) -> String {
+    HtmlRenderer::new().options(opts.clone()).render(blocks)
+}
+
+/// Resolve conditionals and rewrite raw content as `opts` asks, borrowing `blocks` when nothing changes.
+pub(crate) fn prepare<'a, 'b>(blocks: &'a [Block<'b>], opts: &RenderOptions) -> Cow<'a, [Block<'b>]> {
+    let mut blocks = Cow::Borrowed(blocks);
+    if let Some(tags) = &opts.tags {
+        blocks = Cow::Owned(apply_conditionals(blocks.into_owned(), tags));
+    }
+    if opts.raw != RawMode::Passthrough {
+        blocks = Cow::Owned(apply_raw_mode(&blocks, opts.raw));
+    }
+    blocks
+}
+
+/// Rewrite every [`Block::Raw`] in the tree according to `mode`.
+fn apply_raw_mode<'a>(blocks: &[Block<'a>], mode: RawMode) -> Vec<Block<'a>> {
+    RawRewrite(mode).fold_blocks(blocks.to_vec())
+}
+
+struct RawRewrite(RawMode);
+
+impl<'a> Fold<'a> for RawRewrite {
+    fn fold_blocks(&mut self, blocks: Vec<Block<'a>>) -> Vec<Block<'a>> {
+        blocks
+            .into_iter()
+            .filter_map(|block| match block {
+                Block::Raw { formats, content } => match self.0 {
+                    RawMode::Passthrough => Some(Block::Raw { formats, content }),
+                    RawMode::Escape => Some(Block::LiteralBlock(content.into())),
+                    RawMode::Drop => None,
+                },
+                other => Some(self.fold_block(other)),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,63 @@
+//! File access for directives that pull content from disk (e.g. `.. raw:: html` with `:file:`).
+//!
+//! The parser never touches the filesystem on its own. Callers opt in by handing a [`FileResolver`]
+//! to [`ParserOptions::file_resolver`](crate::ParserOptions::file_resolver); without one, a directive
+//! that references a file is kept as a literal block. A configured resolver that fails to read a file
+//! makes the parse fail with [`ParseError::File`](crate::ParseError::File).
+
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Resolves a path named inside a document to its text content.
+pub trait FileResolver: Send + Sync {
+    /// Read the file referenced by `path`, exactly as written in the document.
+    fn read(&self, path: &str) -> io::Result<String>;
+}
+
+impl<F> FileResolver for F
+where
+    F: Fn(&str) -> io::Result<String> + Send + Sync,
+{
+    fn read(&self, path: &str) -> io::Result<String> {
+        self(path)
+    }
+}
+
+/// Reads files relative to a root directory.
+///
+/// Absolute paths, paths that climb out of the root with `..` and paths that resolve outside it
+/// through a symlink are rejected, so documents can only reach files below `root`.
+#[derive(Debug, Clone)]
+pub struct FsResolver {
+    root: PathBuf,
+}
+
+impl FsResolver {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl FileResolver for FsResolver {
+    fn read(&self, path: &str) -> io::Result<String> {
+        let relative = Path::new(path);
+        let outside = || {
+            io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("`{path}` is outside the resolver root"),
+            )
+        };
+        let escapes = relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+        if escapes {
+            return Err(outside());
+        }
+        let root = self.root.canonicalize()?;
+        let full = root.join(relative).canonicalize()?;
+        if !full.starts_with(&root) {
+            return Err(outside());
+        }
+        std::fs::read_to_string(full)
+    }
+}
@@ -0,0 +1,386 @@
+//! Serialization of parsed blocks back to reStructuredText source.
+
+use crate::textutil::{Slot, display_width, place_cells, write_grid_table};
+use crate::{ADORNMENTS, Block, Field, Inline, ListKind, OptionItem, PyObject, TableCell};
+
+/// Indentation used for directive and comment bodies.
+pub(crate) const BODY_INDENT: usize = 3;
+
+/// Write `blocks` as canonical reStructuredText.
+///
+/// Headings are underlined with their level's adornment (`=`, `-`, `~`, `^` then `"`) sized to
+/// their title, lists get `-` and renumbered `N.` markers, tables are redrawn as simple tables
+/// when every cell is a single line and as grid tables otherwise, with columns at least as wide as
+/// they were in the source, and literal `\`, `*` and `` ` `` characters in text are escaped, as is
+/// a `[` that would start a footnote reference. Text keeps its line breaks and spacing, including
+/// the indentation of an indented paragraph. Parsing the output gives back the same blocks, except
+/// that Markdown code fences and `>` quotes, which reST lacks, are written as a `code-block`
+/// directive and an indented block quote.
+///
+/// # Examples
+///
+/// ```
+/// use parserst::{parse, to_rst};
+///
+/// let blocks = parse("Title\n=====\n\n- one\n- *two*\n").unwrap();
+/// let rst = to_rst(&blocks);
+/// assert_eq!(rst, "Title\n=====\n\n- one\n- *two*\n");
+/// assert_eq!(parse(&rst).unwrap(), blocks);
+/// ```
+pub fn to_rst(blocks: &[Block]) -> String {
+    let mut out = write_blocks(blocks, None);
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+/// Write `blocks` as reST, separated by blank lines.
+///
+/// With a `width`, paragraphs and list items are refilled to fit; otherwise their text is written
+/// unchanged, whitespace included. A paragraph followed by a literal block introduces it with a
+/// trailing `::` unless the paragraph is indented, and a block quote following a construct with an
+/// indented body is set apart from it by an empty comment.
+pub(crate) fn write_blocks(blocks: &[Block], width: Option<usize>) -> String {
+    let mut parts = Vec::new();
+    let mut prev = None;
+    let mut iter = blocks.iter().peekable();
+    while let Some(block) = iter.next() {
+        if matches!(block, Block::Quote(_)) && prev.is_some_and(takes_indented_body) {
+            parts.push("..".to_string());
+        }
+        prev = Some(block);
+        match (block, iter.peek()) {
+            (Block::Paragraph(_), Some(Block::LiteralBlock(text))) if !text.is_empty() => {
+                let para = write_block(block, width);
+                // `  para ::` over an indented block would read as a definition, so keep the `::` apart.
+                if para.starts_with(char::is_whitespace) {
+                    parts.push(para);
+                    continue;
+                }
+                let marker = if para.ends_with(':') && !para.ends_with(" :") { ":" } else { " ::" };
+                parts.push(format!("{para}{marker}\n\n{}", indent(text, 4)));
+                iter.next();
+            }
+            _ => parts.push(write_block(block, width)),
+        }
+    }
+    parts.join("\n\n")
+}
+
+fn write_block(block: &Block, width: Option<usize>) -> String {
+    let inner = width.map(|w| w.saturating_sub(BODY_INDENT));
+    match block {
+        Block::Heading { level, inlines, .. } => {
+            let title = write_inlines(inlines);
+            let adornment = ADORNMENTS[usize::from((*level).clamp(1, 5)) - 1];
+            let len = title.lines().map(display_width).max().unwrap_or(0);
+            format!("{title}\n{}", adornment.to_string().repeat(len.max(1)))
+        }
+        Block::Paragraph(inlines) => match width {
+            Some(_) => fill(&inline_words(inlines), width, "", "").join("\n"),
+            None => write_inlines(inlines),
+        },
+        Block::List { kind, items } => items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let marker = match kind {
+                    ListKind::Unordered => "- ".to_string(),
+                    ListKind::Ordered => format!("{}. ", i + 1),
+                };
+                let hang = " ".repeat(marker.len());
+                match width {
+                    Some(_) => fill(&inline_words(item), width, &marker, &hang).join("\n"),
+                    None => format!("{marker}{}", write_inlines(item).replace('\n', &format!("\n{hang}"))),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Block::CodeBlock(code) => with_body(directive_head("code-block", ""), code, BODY_INDENT),
+        Block::Quote(children) => indent(&write_blocks(children, inner), BODY_INDENT),
+        Block::LiteralBlock(text) => format!("::\n\n{}", indent(text, 4)),
+        Block::Directive { name, argument, content } => {
+            let body = match content.as_slice() {
+                [Block::LiteralBlock(code)] if name == "code-block" || name == "code" => code.to_string(),
+                _ => write_blocks(content, inner),
+            };
+            with_body(directive_head(name, argument), &body, BODY_INDENT)
+        }
+        Block::Table { headers, rows, widths } => write_table(headers, rows, widths),
+        Block::Comment(children) => write_explicit("..", children, inner),
+        Block::Footnote { label, content } => write_explicit(&format!(".. [{label}]"), content, inner),
+        Block::FieldList { fields } => fields
+            .iter()
+            .map(|f| write_field(f, width))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Block::OptionList { items } => items
+            .iter()
+            .map(|item| write_option_item(item, width))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Block::Raw { formats, content } => with_body(directive_head("raw", &formats.join(" ")), content, BODY_INDENT),
+        Block::Target { name } if name.contains(':') => format!(".. _`{name}`:"),
+        Block::Target { name } => format!(".. _{name}:"),
+        Block::PyObject(obj) => write_py_object(obj, inner),
+    }
+}
+
+/// Whether an indented block written after `block` would be read as part of it.
+fn takes_indented_body(block: &Block) -> bool {
+    matches!(
+        block,
+        Block::CodeBlock(_)
+            | Block::Directive { .. }
+            | Block::Comment(_)
+            | Block::Footnote { .. }
+            | Block::FieldList { .. }
+            | Block::OptionList { .. }
+            | Block::Raw { .. }
+            | Block::PyObject(_)
+    )
+}
+
+fn directive_head(name: &str, argument: &str) -> String {
+    if argument.is_empty() { format!(".. {name}::") } else { format!(".. {name}:: {argument}") }
+}
+
+/// A comment or footnote holding a single paragraph starts on the `head` line; anything else goes in
+/// an indented body.
+fn write_explicit(head: &str, children: &[Block], width: Option<usize>) -> String {
+    let body = write_blocks(children, width);
+    match children {
+        [Block::Paragraph(_)] if !body.contains("::") => {
+            let (first, rest) = body.split_once('\n').unwrap_or((&body, ""));
+            let rest = indent(rest, BODY_INDENT);
+            if rest.is_empty() { format!("{head} {first}") } else { format!("{head} {first}\n{rest}") }
+        }
+        _ if body.is_empty() => head.to_string(),
+        _ => format!("{head}\n{}", indent(&body, BODY_INDENT)),
+    }
+}
+
+/// A single-paragraph description follows its options after two spaces; anything else goes in an
+/// indented body below them.
+fn write_option_item(item: &OptionItem, width: Option<usize>) -> String {
+    let options = item.options.join(", ");
+    match item.body.as_slice() {
+        [Block::Paragraph(inlines)] => fill(
+            &inline_words(inlines),
+            width,
+            &format!("{options}  "),
+            &" ".repeat(BODY_INDENT),
+        )
+        .join("\n"),
+        body => {
+            let body = write_blocks(body, width.map(|w| w.saturating_sub(BODY_INDENT)));
+            format!("{options}\n{}", indent(&body, BODY_INDENT))
+        }
+    }
+}
+
+/// A field's body starts on its `:name:` line and continues indented below it.
+fn write_field(field: &Field, width: Option<usize>) -> String {
+    let head = if field.argument.is_empty() {
+        format!(":{}:", field.name)
+    } else {
+        format!(":{} {}:", field.name, field.argument)
+    };
+    let body = write_blocks(&field.body, width.map(|w| w.saturating_sub(BODY_INDENT)));
+    match body.split_once('\n') {
+        _ if body.is_empty() => head,
+        Some((first, rest)) => format!("{head} {first}\n{}", indent(rest, BODY_INDENT)),
+        None => format!("{head} {body}"),
+    }
+}
+
+/// Options go directly under the directive line, the content after a blank line.
+fn write_py_object(obj: &PyObject, width: Option<usize>) -> String {
+    let mut out = directive_head(&format!("py:{}", obj.kind.name()), &obj.signature.to_string());
+    for (name, value) in &obj.options {
+        out.push('\n');
+        out.push_str(format!("{}:{name}: {value}", " ".repeat(BODY_INDENT)).trim_end());
+    }
+    with_body(out, &write_blocks(&obj.content, width), BODY_INDENT)
+}
+
+/// Draw a table with its columns at least as wide as they were in the source.
+fn write_table(headers: &[Vec<TableCell>], rows: &[Vec<TableCell>], widths: &[usize]) -> String {
+    let all: Vec<&Vec<TableCell>> = headers.iter().chain(rows).collect();
+    let (slots, columns) = place_cells(&all, |blocks| write_blocks(blocks, None));
+    write_simple_table(&slots, columns, all.len(), headers.len(), widths)
+        .unwrap_or_else(|| write_grid_table(&slots, columns, all.len(), headers.len(), widths, false))
+}
+
+/// Draw a simple table, or `None` when a cell spans, holds more than one line or would be read as a
+/// continuation row because its first column is empty.
+fn write_simple_table(
+    slots: &[Slot], columns: usize, rows: usize, headers: usize, min_widths: &[usize],
+) -> Option<String> {
+    let fits = columns >= 2
+        && (headers == 0 || headers < rows)
+        && slots.iter().all(|slot| {
+            let first_ok = slot.col > 0
+                || slot
+                    .lines
+                    .first()
+                    .is_some_and(|l| !l.chars().all(|c| c == '-' || c == '=' || c == ' '));
+            slot.rowspan == 1 && slot.colspan == 1 && slot.lines.len() <= 1 && first_ok
+        });
+    if !fits {
+        return None;
+    }
+
+    let mut widths = vec![1; columns];
+    if min_widths.len() == columns {
+        widths
+            .iter_mut()
+            .zip(min_widths)
+            .for_each(|(width, &min)| *width = min.max(1));
+    }
+    for slot in slots {
+        widths[slot.col] = widths[slot.col].max(display_width(slot.text()));
+    }
+    let border = widths.iter().map(|&w| "=".repeat(w)).collect::<Vec<_>>().join("  ");
+
+    let mut lines = vec![border.clone()];
+    for (row, cells) in slots.chunk_by(|a, b| a.row == b.row).enumerate() {
+        if row == headers && headers > 0 {
+            lines.push(border.clone());
+        }
+        let line = cells
+            .iter()
+            .map(|slot| {
+                let t = slot.text();
+                format!("{t}{}", " ".repeat(widths[slot.col] - display_width(t)))
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        lines.push(line.trim_end().to_string());
+    }
+    lines.push(border);
+    Some(lines.join("\n"))
+}
+
+/// `head` followed by `body` indented by `n` columns, after a blank line.
+fn with_body(head: String, body: &str, n: usize) -> String {
+    if body.trim().is_empty() { head } else { format!("{head}\n\n{}", indent(body, n)) }
+}
+
+/// Indent every non-empty line of `text` by `n` spaces.
+pub(crate) fn indent(text: &str, n: usize) -> String {
+    let pad = " ".repeat(n);
+    text.lines()
+        .map(|l| if l.trim().is_empty() { String::new() } else { format!("{pad}{l}") })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Write inline nodes as reST markup.
+pub(crate) fn write_inlines(inlines: &[Inline]) -> String {
+    inlines.iter().map(write_inline).collect()
+}
+
+fn write_inline(inline: &Inline) -> String {
+    match inline {
+        Inline::Text(t) => escape(t),
+        Inline::Em(children) => format!("*{}*", write_inlines(children)),
+        Inline::Strong(children) => format!("**{}**", write_inlines(children)),
+        // ``a``b`` would end at the first double backtick, so such code goes in the `:literal:` role.
+        Inline::Code(code) if code.contains("``") || code.ends_with('`') => {
+            format!(":literal:`{}`", code.replace('\\', "\\\\").replace('`', "\\`"))
+        }
+        Inline::Code(code) => format!("``{code}``"),
+        Inline::Link { text, url } => {
+            let label: String = text
+                .iter()
+                .map(|inline| match inline {
+                    Inline::Text(t) => escape(t).replace('<', "\\<"),
+                    _ => write_inline(inline),
+                })
+                .collect();
+            format!("`{label} <{url}>`_")
+        }
+        Inline::FootnoteRef(label) => format!("[{label}]_"),
+    }
+}
+
+/// Backslash-escape the characters that would otherwise start inline markup.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for (i, c) in text.char_indices() {
+        let footnote_ref = c == '['
+            && text[i + 1..]
+                .split_once("]_")
+                .is_some_and(|(label, _)| crate::ast::is_footnote_label(label));
+        if matches!(c, '\\' | '*' | '`') || footnote_ref {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Split inline content into words for filling.
+///
+/// Markup spans are never split, so refilling cannot break them across lines. Line breaks in text
+/// are kept as `"\n"` words so an unfilled paragraph keeps its original lines.
+pub(crate) fn inline_words(inlines: &[Inline]) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    for inline in inlines {
+        let Inline::Text(text) = inline else {
+            current.push_str(&write_inline(inline));
+            continue;
+        };
+        for (i, line) in escape(text).split('\n').enumerate() {
+            if i > 0 {
+                if !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                }
+                words.push("\n".to_string());
+            }
+            let mut pieces = line.split(' ').peekable();
+            while let Some(piece) = pieces.next() {
+                current.push_str(piece);
+                if pieces.peek().is_some() && !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                }
+            }
+        }
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Lay out `words` as lines no wider than `width`, starting the first line with `first` and the rest
+/// with `rest`. Without a width, only the `"\n"` words break lines.
+pub(crate) fn fill(words: &[String], width: Option<usize>, first: &str, rest: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = first.to_string();
+    let mut empty = true;
+    for word in words {
+        if word == "\n" {
+            if width.is_none() && !empty {
+                lines.push(std::mem::replace(&mut line, rest.to_string()));
+                empty = true;
+            }
+            continue;
+        }
+        let fits = width.is_none_or(|w| display_width(&line) + 1 + display_width(word) <= w);
+        if !empty && !fits {
+            lines.push(std::mem::replace(&mut line, rest.to_string()));
+            empty = true;
+        }
+        if !empty {
+            line.push(' ');
+        }
+        line.push_str(word);
+        empty = false;
+    }
+    lines.push(line);
+    lines
+}
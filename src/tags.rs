@@ -0,0 +1,248 @@
+//! Tag sets and the boolean tag expressions used by the `only` and `ifconfig` directives.
+//!
+//! Conditional content stays in the parsed tree as ordinary [`Block::Directive`] nodes so it can be
+//! serialized untouched. [`apply_conditionals`] is the transform that resolves it against a [`Tags`]
+//! set, either on demand or automatically through [`ParserOptions::tags`](crate::ParserOptions::tags)
+//! and [`RenderOptions::tags`](crate::RenderOptions::tags).
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::Block;
+use crate::visit::Fold;
+
+/// Directives whose argument is a tag expression deciding whether their content is kept.
+pub const CONDITIONAL_DIRECTIVES: [&str; 2] = ["only", "ifconfig"];
+
+/// The set of tags that are active for a build (e.g. `html`, `internal`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tags(BTreeSet<String>);
+
+impl Tags {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, tag: impl Into<String>) -> bool {
+        self.0.insert(tag.into())
+    }
+
+    pub fn remove(&mut self, tag: &str) -> bool {
+        self.0.remove(tag)
+    }
+
+    pub fn contains(&self, tag: &str) -> bool {
+        self.0.contains(tag)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for Tags {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        Self(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+/// A parsed boolean expression over tag names, e.g. `html and not (internal or draft)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagExpr {
+    Tag(String),
+    Not(Box<TagExpr>),
+    And(Box<TagExpr>, Box<TagExpr>),
+    Or(Box<TagExpr>, Box<TagExpr>),
+}
+
+/// Error returned when a tag expression cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagExprError(String);
+
+impl fmt::Display for TagExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid tag expression: {}", self.0)
+    }
+}
+
+impl std::error::Error for TagExprError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token<'a> {
+    Open,
+    Close,
+    Word(&'a str),
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token<'_>>, TagExprError> {
+    let mut tokens = Vec::new();
+    let mut rest = input;
+    loop {
+        rest = rest.trim_start();
+        let Some(ch) = rest.chars().next() else {
+            return Ok(tokens);
+        };
+        match ch {
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            c if is_tag_char(c) => {
+                let end = rest.find(|c: char| !is_tag_char(c)).unwrap_or(rest.len());
+                tokens.push(Token::Word(&rest[..end]));
+                rest = &rest[end..];
+                continue;
+            }
+            other => return Err(TagExprError(format!("unexpected character `{other}`"))),
+        }
+        rest = &rest[1..];
+    }
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '.'
+}
+
+/// Recursive descent over `or` > `and` > `not` > atom.
+struct ExprParser<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+}
+
+impl<'a> ExprParser<'a> {
+    fn peek(&self) -> Option<&Token<'a>> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        if self.peek() == Some(&Token::Word(word)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<TagExpr, TagExprError> {
+        let mut lhs = self.and()?;
+        while self.eat_word("or") {
+            lhs = TagExpr::Or(Box::new(lhs), Box::new(self.and()?));
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<TagExpr, TagExprError> {
+        let mut lhs = self.not()?;
+        while self.eat_word("and") {
+            lhs = TagExpr::And(Box::new(lhs), Box::new(self.not()?));
+        }
+        Ok(lhs)
+    }
+
+    fn not(&mut self) -> Result<TagExpr, TagExprError> {
+        if self.eat_word("not") {
+            return Ok(TagExpr::Not(Box::new(self.not()?)));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<TagExpr, TagExprError> {
+        match self.tokens.get(self.pos).cloned() {
+            Some(Token::Open) => {
+                self.pos += 1;
+                let inner = self.or()?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err(TagExprError("missing `)`".into()));
+                }
+                self.pos += 1;
+                Ok(inner)
+            }
+            Some(Token::Word(word)) if !matches!(word, "and" | "or" | "not") => {
+                self.pos += 1;
+                Ok(TagExpr::Tag(word.to_string()))
+            }
+            Some(Token::Word(word)) => Err(TagExprError(format!("expected a tag, found `{word}`"))),
+            Some(Token::Close) => Err(TagExprError("unexpected `)`".into())),
+            None => Err(TagExprError("unexpected end of expression".into())),
+        }
+    }
+}
+
+impl TagExpr {
+    /// Parse an expression made of tag names, `and`, `or`, `not` and parentheses.
+    pub fn parse(input: &str) -> Result<Self, TagExprError> {
+        let mut parser = ExprParser { tokens: tokenize(input)?, pos: 0 };
+        let expr = parser.or()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(Token::Close) => Err(TagExprError("unexpected `)`".into())),
+            Some(_) => Err(TagExprError("expected `and` or `or` between tags".into())),
+        }
+    }
+
+    pub fn eval(&self, tags: &Tags) -> bool {
+        match self {
+            TagExpr::Tag(name) => tags.contains(name),
+            TagExpr::Not(inner) => !inner.eval(tags),
+            TagExpr::And(lhs, rhs) => lhs.eval(tags) && rhs.eval(tags),
+            TagExpr::Or(lhs, rhs) => lhs.eval(tags) || rhs.eval(tags),
+        }
+    }
+}
+
+/// Resolve every `only`/`ifconfig` directive in `blocks` against `tags`.
+///
+/// Directives whose expression holds are replaced by their content; the rest are removed along with
+/// their content. Expressions that fail to parse are treated as false.
+pub fn apply_conditionals<'a>(blocks: Vec<Block<'a>>, tags: &Tags) -> Vec<Block<'a>> {
+    Conditionals(tags).fold_blocks(blocks)
+}
+
+struct Conditionals<'a>(&'a Tags);
+
+impl<'a> Fold<'a> for Conditionals<'_> {
+    fn fold_blocks(&mut self, blocks: Vec<Block<'a>>) -> Vec<Block<'a>> {
+        let mut out = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            match block {
+                Block::Directive { name, argument, content } if CONDITIONAL_DIRECTIVES.contains(&name.as_str()) => {
+                    if TagExpr::parse(&argument).is_ok_and(|expr| expr.eval(self.0)) {
+                        out.extend(self.fold_blocks(content));
+                    }
+                }
+                other => out.push(self.fold_block(other)),
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_precedence_and_parentheses() {
+        let expr = TagExpr::parse("html and not internal or pdf").unwrap();
+        assert_eq!(
+            expr,
+            TagExpr::Or(
+                Box::new(TagExpr::And(
+                    Box::new(TagExpr::Tag("html".into())),
+                    Box::new(TagExpr::Not(Box::new(TagExpr::Tag("internal".into())))),
+                )),
+                Box::new(TagExpr::Tag("pdf".into())),
+            )
+        );
+
+        let grouped = TagExpr::parse("html and (internal or pdf)").unwrap();
+        let tags: Tags = ["html", "pdf"].into_iter().collect();
+        assert!(grouped.eval(&tags));
+        assert!(!grouped.eval(&["html"].into_iter().collect()));
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        for input in ["", "html and", "(html", "html)", "html pdf", "not", "html & pdf"] {
+            assert!(TagExpr::parse(input).is_err(), "{input:?} should not parse");
+        }
+    }
+}
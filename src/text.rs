@@ -0,0 +1,512 @@
+//! Plain-text output for terminals and editor hovers.
+
+use crate::ast::capitalize;
+use crate::events::{Event, prepared_blocks};
+use crate::html::ADMONITIONS;
+use crate::render::{RenderOptions, prepare};
+use crate::rst::{fill, indent};
+use crate::textutil::{display_width, place_cells, write_grid_table};
+use crate::{Block, Field, Inline, ListKind, ParseError, PyObject, PyObjectKind, TableCell};
+
+/// Indentation of quotes, code and directive bodies.
+const INDENT: usize = 4;
+
+/// Renders blocks as readable plain text.
+///
+/// Markup is removed, headings are underlined, list items keep their markers, tables are drawn as
+/// ASCII grids and links read `text (url)`. Paragraphs and list items wrap to the configured width;
+/// code keeps its lines and is indented instead.
+///
+/// ```
+/// use parserst::{TextRenderer, parse};
+///
+/// let blocks = parse("Usage\n=====\n\nSee `the docs <https://example.com>`_ for *all* options.\n").unwrap();
+/// let text = TextRenderer::new().width(30).render(&blocks);
+/// assert_eq!(text, "Usage\n=====\n\nSee the docs\n(https://example.com) for all\noptions.\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct TextRenderer {
+    width: usize,
+    options: RenderOptions,
+}
+
+impl Default for TextRenderer {
+    fn default() -> Self {
+        Self { width: 79, options: RenderOptions::default() }
+    }
+}
+
+impl TextRenderer {
+    /// Defaults to wrapping at 79 columns.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wrap paragraphs and list items to `width` columns; `usize::MAX` keeps each on one line.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Apply `options` (raw content handling, conditional directives) when rendering a document.
+    pub fn options(mut self, options: RenderOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Render a document, separating blocks with blank lines.
+    pub fn render(&self, blocks: &[Block]) -> String {
+        let mut out = Style::default().blocks(&prepare(blocks, &self.options), self.width);
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Render a document from an [event stream](crate::events), a top-level block at a time.
+    ///
+    /// Each item is the output for the next block; together they are what [`render`](Self::render)
+    /// gives for the blocks the events describe. Errors in the stream are passed through.
+    pub fn render_events<'r, 'a: 'r, I>(&'r self, events: I) -> impl Iterator<Item = Result<String, ParseError>> + 'r
+    where
+        I: IntoIterator<Item = Result<Event<'a>, ParseError>>,
+        I::IntoIter: 'r,
+    {
+        let mut first = true;
+        prepared_blocks(events, &self.options).filter_map(move |item| {
+            let (block, _) = match item {
+                Ok(item) => item,
+                Err(err) => return Some(Err(err)),
+            };
+            let text = Style::default()
+                .block(&block, self.width)
+                .filter(|text| !text.is_empty())?;
+            let separator = if first { "" } else { "\n" };
+            first = false;
+            Some(Ok(format!("{separator}{text}\n")))
+        })
+    }
+
+    /// Render inline content as a single unwrapped line.
+    pub fn render_inlines(&self, inlines: &[Inline]) -> String {
+        Style::default().words(inlines, &[]).join(" ")
+    }
+}
+
+/// Terminal styling layered on the plain-text layout; the default writes plain text.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Style {
+    /// Emit SGR sequences: bold and underlined headings, italics, coloured code and admonitions.
+    pub(crate) color: bool,
+    /// Emit link text as OSC 8 hyperlinks instead of `text (url)`.
+    pub(crate) hyperlinks: bool,
+    /// Draw tables and admonitions with box-drawing characters.
+    pub(crate) boxes: bool,
+}
+
+impl Style {
+    pub(crate) fn blocks(self, blocks: &[Block], width: usize) -> String {
+        blocks
+            .iter()
+            .filter_map(|block| self.block(block, width))
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// The text of `block`, or `None` for blocks that have no plain-text form.
+    fn block(self, block: &Block, width: usize) -> Option<String> {
+        let inner = width.saturating_sub(INDENT);
+        Some(match block {
+            Block::Heading { level, inlines, .. } if self.color => {
+                let sgr: &[&str] = if *level <= 1 { &["1", "4"] } else { &["1"] };
+                self.words(inlines, sgr).join(" ")
+            }
+            Block::Heading { level, inlines, .. } => {
+                let title = self.words(inlines, &[]).join(" ");
+                let adornment = if *level <= 1 { "=" } else { "-" };
+                format!("{title}\n{}", adornment.repeat(display_width(&title).max(1)))
+            }
+            Block::Paragraph(inlines) => self.wrap(inlines, width, "", ""),
+            Block::List { kind, items } => items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    let marker = match kind {
+                        ListKind::Unordered => "- ".to_string(),
+                        ListKind::Ordered => format!("{}. ", i + 1),
+                    };
+                    self.wrap(item, width, &marker, &" ".repeat(marker.len()))
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Block::CodeBlock(code) | Block::LiteralBlock(code) => indent(&self.code(code), INDENT),
+            Block::Quote(children) => indent(&self.blocks(children, inner), INDENT),
+            Block::Directive { name, argument, content } => match name.as_str() {
+                "only" | "ifconfig" => self.blocks(content, width),
+                "highlight" => return None,
+                "image" => format!("[image: {argument}]"),
+                "code-block" | "code" => indent(&self.code_block(content, inner), INDENT),
+                name if self.boxes && ADMONITIONS.contains(&name) => self.admonition(name, argument, content, width),
+                _ => {
+                    let head = match argument.as_str() {
+                        "" => format!("{}:", capitalize(name)),
+                        argument => format!("{}: {argument}", capitalize(name)),
+                    };
+                    with_body(self.paint(&head, "1"), &self.blocks(content, inner))
+                }
+            },
+            Block::Table { headers, rows, .. } => self.table(headers, rows),
+            Block::Comment(_) | Block::Target { .. } => return None,
+            Block::FieldList { fields } => fields
+                .iter()
+                .map(|field| self.field(field, width))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Block::OptionList { items } => items
+                .iter()
+                .map(|item| {
+                    with_body(
+                        self.paint(&item.options.join(", "), "1"),
+                        &self.blocks(&item.body, inner),
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Block::Raw { formats, content } if formats.iter().any(|format| format == "text") => content.clone(),
+            Block::Raw { .. } => return None,
+            Block::PyObject(obj) => self.py_object(obj, width)?,
+            Block::Footnote { label, content } => self.hanging(format!("[{label}]"), content, width),
+        })
+    }
+
+    /// `text` wrapped in the SGR sequence `sgr` when colour is on.
+    fn paint(self, text: &str, sgr: &str) -> String {
+        if self.color && !text.is_empty() { format!("\x1b[{sgr}m{text}\x1b[0m") } else { text.to_string() }
+    }
+
+    /// Code kept line for line, dimmed when colour is on.
+    fn code(self, code: &str) -> String {
+        code.lines()
+            .map(|line| self.paint(line, "2"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// The text of a `code-block`'s content, kept line for line.
+    fn code_block(self, content: &[Block], width: usize) -> String {
+        content
+            .iter()
+            .map(|block| match block {
+                Block::LiteralBlock(code) => self.code(code),
+                other => self.blocks(std::slice::from_ref(other), width),
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// An admonition's body in a box whose border is coloured by the admonition's kind.
+    fn admonition(self, name: &str, argument: &str, content: &[Block], width: usize) -> String {
+        let inner = width.saturating_sub(4);
+        let words: Vec<String> = argument.split_whitespace().map(str::to_string).collect();
+        let body = match (words.is_empty(), self.blocks(content, inner)) {
+            (true, body) => body,
+            (false, body) if body.is_empty() => fill(&words, Some(inner), "", "").join("\n"),
+            (false, body) => format!("{}\n\n{body}", fill(&words, Some(inner), "", "").join("\n")),
+        };
+        let color = match name {
+            "danger" | "error" => "31",
+            "warning" | "caution" | "attention" => "33",
+            "tip" | "hint" => "32",
+            "important" => "35",
+            _ => "34",
+        };
+        let title = capitalize(name);
+        let lines: Vec<&str> = body.lines().collect();
+        let inner = lines
+            .iter()
+            .map(|line| display_width(line))
+            .max()
+            .unwrap_or(0)
+            .max(title.len() + 2);
+
+        let mut out = vec![format!(
+            "{}{}{}",
+            self.paint("╭─ ", color),
+            self.paint(&title, &format!("1;{color}")),
+            self.paint(&format!(" {}╮", "─".repeat(inner - title.len() - 1)), color)
+        )];
+        for line in lines {
+            let pad = " ".repeat(inner - display_width(line));
+            out.push(format!(
+                "{} {line}{pad} {}",
+                self.paint("│", color),
+                self.paint("│", color)
+            ));
+        }
+        out.push(self.paint(&format!("╰{}╯", "─".repeat(inner + 2)), color));
+        out.join("\n")
+    }
+
+    /// A field's body starts on its name line and continues indented below it.
+    fn field(self, field: &Field, width: usize) -> String {
+        let head = if field.argument.is_empty() {
+            format!("{}:", field.name)
+        } else {
+            format!("{} {}:", field.name, field.argument)
+        };
+        self.hanging(self.paint(&head, "1"), &field.body, width)
+    }
+
+    /// `head` followed by `body`, whose first paragraph starts on the head line.
+    fn hanging(self, head: String, body: &[Block], width: usize) -> String {
+        match body.split_first() {
+            Some((Block::Paragraph(inlines), rest)) => {
+                let first = self.wrap(inlines, width, &format!("{head} "), &" ".repeat(INDENT));
+                match self.blocks(rest, width.saturating_sub(INDENT)) {
+                    rest if rest.is_empty() => first,
+                    rest => format!("{first}\n\n{}", indent(&rest, INDENT)),
+                }
+            }
+            _ => with_body(head, &self.blocks(body, width.saturating_sub(INDENT))),
+        }
+    }
+
+    /// The object's signature with its description indented below it.
+    fn py_object(self, obj: &PyObject, width: usize) -> Option<String> {
+        let body = self.blocks(&obj.content, width.saturating_sub(INDENT));
+        match obj.kind {
+            PyObjectKind::CurrentModule => None,
+            PyObjectKind::Module => Some(body),
+            _ => Some(with_body(self.paint(&obj.signature.to_string(), "1"), &body)),
+        }
+    }
+
+    fn table(self, headers: &[Vec<TableCell>], rows: &[Vec<TableCell>]) -> String {
+        let all: Vec<&Vec<TableCell>> = headers.iter().chain(rows).collect();
+        let (slots, columns) = place_cells(&all, |blocks| self.blocks(blocks, usize::MAX));
+        write_grid_table(&slots, columns, all.len(), headers.len(), &[], self.boxes)
+    }
+
+    /// Fill the words of `inlines` to `width`, starting with `first` and indenting later lines by `rest`.
+    fn wrap(self, inlines: &[Inline], width: usize, first: &str, rest: &str) -> String {
+        fill(&self.words(inlines, &[]), Some(width), first, rest).join("\n")
+    }
+
+    /// The whitespace-separated words of `inlines` with markup removed, each styled on its own so
+    /// that no style carries over a line break. `sgr` applies to every word.
+    pub(crate) fn words(self, inlines: &[Inline], sgr: &[&'static str]) -> Vec<String> {
+        let mut words = Words { style: self, done: Vec::new(), current: String::new() };
+        words.inlines(inlines, &mut sgr.to_vec(), None);
+        words.finish()
+    }
+}
+
+/// Collects styled words while walking inline content.
+struct Words {
+    style: Style,
+    done: Vec<String>,
+    current: String,
+}
+
+impl Words {
+    fn inlines(&mut self, inlines: &[Inline], sgr: &mut Vec<&'static str>, link: Option<&str>) {
+        for inline in inlines {
+            match inline {
+                Inline::Text(t) => self.text(t, sgr, link),
+                Inline::FootnoteRef(label) => self.text(&format!("[{label}]"), sgr, link),
+                Inline::Code(t) => self.styled(t, "36", sgr, link),
+                Inline::Em(children) => {
+                    sgr.push("3");
+                    self.inlines(children, sgr, link);
+                    sgr.pop();
+                }
+                Inline::Strong(children) => {
+                    sgr.push("1");
+                    self.inlines(children, sgr, link);
+                    sgr.pop();
+                }
+                Inline::Link { text, url } if self.style.hyperlinks => {
+                    sgr.push("4");
+                    self.inlines(text, sgr, Some(url));
+                    sgr.pop();
+                }
+                Inline::Link { text, url } => {
+                    let mut plain = String::new();
+                    push_text(&mut plain, text);
+                    self.inlines(text, sgr, link);
+                    if plain.trim() != url {
+                        self.text(&format!(" ({url})"), sgr, link);
+                    }
+                }
+            }
+        }
+    }
+
+    fn styled(&mut self, text: &str, code: &'static str, sgr: &mut Vec<&'static str>, link: Option<&str>) {
+        sgr.push(code);
+        self.text(text, sgr, link);
+        sgr.pop();
+    }
+
+    /// Split `text` into words, continuing the current word if `text` doesn't start with a space.
+    fn text(&mut self, text: &str, sgr: &[&str], link: Option<&str>) {
+        for (i, piece) in text.split(char::is_whitespace).enumerate() {
+            if i > 0 && !self.current.is_empty() {
+                self.done.push(std::mem::take(&mut self.current));
+            }
+            if piece.is_empty() {
+                continue;
+            }
+            let mut piece = piece.to_string();
+            if self.style.color && !sgr.is_empty() {
+                piece = format!("\x1b[{}m{piece}\x1b[0m", sgr.join(";"));
+            }
+            if let Some(url) = link.filter(|_| self.style.hyperlinks) {
+                piece = format!("\x1b]8;;{url}\x1b\\{piece}\x1b]8;;\x1b\\");
+            }
+            self.current.push_str(&piece);
+        }
+    }
+
+    fn finish(mut self) -> Vec<String> {
+        if !self.current.is_empty() {
+            self.done.push(self.current);
+        }
+        self.done
+    }
+}
+
+/// `head` followed by `body` indented below it.
+fn with_body(head: String, body: &str) -> String {
+    if body.is_empty() { head } else { format!("{head}\n{}", indent(body, INDENT)) }
+}
+
+fn push_text(out: &mut String, inlines: &[Inline]) {
+    for inline in inlines {
+        match inline {
+            Inline::Text(t) | Inline::Code(t) => out.push_str(t),
+            Inline::Em(children) | Inline::Strong(children) => push_text(out, children),
+            Inline::FootnoteRef(label) => out.push_str(&format!("[{label}]")),
+            Inline::Link { text, url } => {
+                let start = out.len();
+                push_text(out, text);
+                if out[start..].trim() != url {
+                    out.push_str(&format!(" ({url})"));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, text_of};
+
+    #[test]
+    fn renders_every_block_kind() {
+        let doc = "\
+Guide
+=====
+
+Intro with ``code``, **bold** and https://example.com links.
+
+Install
+-------
+
+1. first step
+2. second step that is long enough to wrap onto another line
+
+- bullet
+
+.. note::
+
+   Take care.
+
+   Second paragraph.
+
+::
+
+   $ make
+     all
+
+> quoted text
+
+.. code-block:: sh
+
+   echo hi
+
+.. image:: logo.png
+
+.. a comment
+
+:param x: The value.
+:returns: Nothing.
+";
+        assert_eq!(
+            text_of(doc, 40),
+            "\
+Guide
+=====
+
+Intro with code, bold and
+https://example.com links.
+
+Install
+-------
+
+1. first step
+2. second step that is long enough to
+   wrap onto another line
+
+- bullet
+
+Note:
+    Take care.
+
+    Second paragraph.
+
+    $ make
+      all
+
+    quoted text
+
+    echo hi
+
+[image: logo.png]
+
+param x: The value.
+returns: Nothing.
+"
+        );
+    }
+
+    #[test]
+    fn tables_are_drawn_as_ascii_grids() {
+        let doc = "===== =====\nA     B\n===== =====\n*x*   ``y``\n===== =====\n";
+        assert_eq!(
+            text_of(doc, 79),
+            "+---+---+\n| A | B |\n+===+===+\n| x | y |\n+---+---+\n"
+        );
+    }
+
+    #[test]
+    fn links_show_their_url_once() {
+        let blocks = parse("`docs <https://a.b>`_ and `https://a.b <https://a.b>`_").unwrap();
+        let Block::Paragraph(inlines) = &blocks[0] else { panic!("expected paragraph") };
+        assert_eq!(
+            TextRenderer::new().render_inlines(inlines),
+            "docs (https://a.b) and https://a.b"
+        );
+    }
+
+    #[test]
+    fn field_bodies_hang_under_their_names() {
+        let doc = ":param value: A long description of the value that wraps.\n\n   More detail.\n";
+        assert_eq!(
+            text_of(doc, 30),
+            "param value: A long\n    description of the value\n    that wraps.\n\n    More detail.\n"
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! Grid layout and column measurement shared by the reST writer and the plain-text and terminal
+//! renderers.
+//!
+//! Widths are counted in terminal columns, with ANSI escape sequences taking none, so text the
+//! terminal renderer has already styled lines up the same as plain text.
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::{Block, TableCell};
+
+/// A table cell placed on the grid of rows and columns, with its content written out as lines.
+pub(crate) struct Slot {
+    pub(crate) row: usize,
+    pub(crate) col: usize,
+    pub(crate) rowspan: usize,
+    pub(crate) colspan: usize,
+    pub(crate) lines: Vec<String>,
+}
+
+impl Slot {
+    /// The cell's only line, for cells that fit a simple table.
+    pub(crate) fn text(&self) -> &str {
+        self.lines.first().map_or("", String::as_str)
+    }
+}
+
+/// Place every cell in the first free column of its row, leaving room for row spans from above.
+///
+/// Cell content is written with `write`. Slots no cell covers are filled with empty cells so the table
+/// stays rectangular.
+pub(crate) fn place_cells(rows: &[&Vec<TableCell>], write: impl Fn(&[Block]) -> String) -> (Vec<Slot>, usize) {
+    let mut taken: Vec<Vec<bool>> = vec![Vec::new(); rows.len()];
+    let mut slots = Vec::new();
+    for (row, cells) in rows.iter().enumerate() {
+        let mut col = 0;
+        for cell in cells.iter() {
+            while taken[row].get(col).copied().unwrap_or(false) {
+                col += 1;
+            }
+            let rowspan = cell.rowspan.clamp(1, rows.len() - row);
+            let colspan = cell.colspan.max(1);
+            for covered in &mut taken[row..row + rowspan] {
+                if covered.len() < col + colspan {
+                    covered.resize(col + colspan, false);
+                }
+                covered[col..col + colspan].fill(true);
+            }
+            let body = write(&cell.content);
+            slots.push(Slot { row, col, rowspan, colspan, lines: body.lines().map(str::to_string).collect() });
+            col += colspan;
+        }
+    }
+
+    let columns = taken.iter().map(Vec::len).max().unwrap_or(0);
+    for (row, covered) in taken.iter().enumerate() {
+        for col in 0..columns {
+            if !covered.get(col).copied().unwrap_or(false) {
+                slots.push(Slot { row, col, rowspan: 1, colspan: 1, lines: Vec::new() });
+            }
+        }
+    }
+    slots.sort_by_key(|slot| (slot.row, slot.col));
+    (slots, columns)
+}
+
+/// Draw a grid table, sizing columns and rows to fit their cells, including cells that span.
+///
+/// Columns are at least as wide as `min_widths` gives, when it has one width per column. With
+/// `boxed`, borders use box-drawing characters instead of `+`, `-`, `=` and `|`.
+pub(crate) fn write_grid_table(
+    slots: &[Slot], columns: usize, rows: usize, headers: usize, min_widths: &[usize], boxed: bool,
+) -> String {
+    let mut widths = vec![3; columns];
+    if min_widths.len() == columns {
+        widths
+            .iter_mut()
+            .zip(min_widths)
+            .for_each(|(width, &min)| *width = min.max(1));
+    }
+    let mut heights = vec![1; rows];
+    let mut by_span: Vec<&Slot> = slots.iter().collect();
+    by_span.sort_by_key(|slot| slot.colspan);
+    for slot in &by_span {
+        let need = slot.lines.iter().map(|l| display_width(l)).max().unwrap_or(0) + 2;
+        grow(&mut widths[slot.col..slot.col + slot.colspan], need);
+    }
+    by_span.sort_by_key(|slot| slot.rowspan);
+    for slot in &by_span {
+        grow(&mut heights[slot.row..slot.row + slot.rowspan], slot.lines.len());
+    }
+
+    let xs = offsets(&widths);
+    let ys = offsets(&heights);
+    let separator = (headers > 0 && headers < rows)
+        .then(|| ys[headers])
+        .filter(|_| !slots.iter().any(|s| s.row < headers && headers < s.row + s.rowspan));
+
+    let mut canvas = vec![vec![" ".to_string(); xs[columns] + 1]; ys[rows] + 1];
+    let mut borders = vec![vec![Border::None; xs[columns] + 1]; ys[rows] + 1];
+    let mut corners = Vec::new();
+    for slot in slots {
+        let (top, bottom) = (ys[slot.row], ys[slot.row + slot.rowspan]);
+        let (left, right) = (xs[slot.col], xs[slot.col + slot.colspan]);
+        for y in [top, bottom] {
+            let rule = if separator == Some(y) { Border::Double } else { Border::Rule };
+            borders[y][left + 1..right].fill(rule);
+        }
+        for line in &mut borders[top + 1..bottom] {
+            line[left] = Border::Side;
+            line[right] = Border::Side;
+        }
+        corners.extend([(top, left), (top, right), (bottom, left), (bottom, right)]);
+        for (i, line) in slot.lines.iter().enumerate() {
+            put_text(&mut canvas[top + 1 + i], left + 2, line);
+        }
+    }
+    for (y, x) in corners {
+        borders[y][x] = Border::Corner;
+    }
+
+    for (y, row) in borders.iter().enumerate() {
+        for (x, &border) in row.iter().enumerate() {
+            let glyph = match (border, boxed) {
+                (Border::None, _) => continue,
+                (Border::Rule, false) => '-',
+                (Border::Double, false) => '=',
+                (Border::Side, false) => '|',
+                (Border::Corner, false) => '+',
+                (Border::Rule, true) => '─',
+                (Border::Double, true) => '═',
+                (Border::Side, true) => '│',
+                (Border::Corner, true) => {
+                    let vertical = |y: Option<usize>| {
+                        y.and_then(|y| borders.get(y))
+                            .is_some_and(|r| matches!(r[x], Border::Side | Border::Corner))
+                    };
+                    let horizontal = |x: Option<usize>| {
+                        x.and_then(|x| row.get(x))
+                            .is_some_and(|b| matches!(b, Border::Rule | Border::Double | Border::Corner))
+                    };
+                    junction(
+                        vertical(y.checked_sub(1)),
+                        vertical(Some(y + 1)),
+                        horizontal(x.checked_sub(1)),
+                        horizontal(Some(x + 1)),
+                        separator == Some(y),
+                    )
+                }
+            };
+            canvas[y][x] = glyph.to_string();
+        }
+    }
+    canvas.iter().map(|row| row.concat()).collect::<Vec<_>>().join("\n")
+}
+
+/// What a grid table canvas position holds.
+#[derive(Clone, Copy)]
+enum Border {
+    None,
+    Rule,
+    Double,
+    Side,
+    Corner,
+}
+
+/// The box-drawing junction joining the borders on each side of a corner.
+fn junction(up: bool, down: bool, left: bool, right: bool, double: bool) -> char {
+    match (up, down, left, right, double) {
+        (true, true, true, true, false) => '┼',
+        (true, true, true, true, true) => '╪',
+        (true, true, false, true, false) => '├',
+        (true, true, false, true, true) => '╞',
+        (true, true, true, false, false) => '┤',
+        (true, true, true, false, true) => '╡',
+        (false, true, true, true, false) => '┬',
+        (false, true, true, true, true) => '╤',
+        (true, false, true, true, false) => '┴',
+        (true, false, true, true, true) => '╧',
+        (false, true, false, true, false) => '┌',
+        (false, true, false, true, true) => '╒',
+        (false, true, true, false, false) => '┐',
+        (false, true, true, false, true) => '╕',
+        (true, false, false, true, false) => '└',
+        (true, false, false, true, true) => '╘',
+        (true, false, true, false, false) => '┘',
+        (true, false, true, false, true) => '╛',
+        (_, _, false, false, _) => '│',
+        (false, false, _, _, false) => '─',
+        (false, false, _, _, true) => '═',
+    }
+}
+
+/// Widen the last of `sizes` until, together with the separators between them, they reach `need`.
+fn grow(sizes: &mut [usize], need: usize) {
+    let total = sizes.iter().sum::<usize>() + sizes.len() - 1;
+    if let Some(last) = sizes.last_mut()
+        && total < need
+    {
+        *last += need - total;
+    }
+}
+
+/// Position of the border before each entry of `sizes`, plus the closing border.
+fn offsets(sizes: &[usize]) -> Vec<usize> {
+    let mut out = vec![0];
+    for size in sizes {
+        out.push(out[out.len() - 1] + size + 1);
+    }
+    out
+}
+
+/// Write `text` into a canvas row from column `x`, one slot per display column.
+///
+/// Escape sequences take no columns and stay attached to the character before them.
+fn put_text(row: &mut [String], mut x: usize, text: &str) {
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if let Some(len) = escape_len(rest)
+            && x > 0
+        {
+            row[x - 1].push_str(&rest[..len]);
+            rest = &rest[len..];
+            continue;
+        }
+        rest = &rest[c.len_utf8()..];
+        match c.width().unwrap_or(1) {
+            0 if x > 0 => row[x - 1].push(c),
+            width => {
+                row[x] = c.to_string();
+                if width == 2 {
+                    row[x + 1] = String::new();
+                }
+                x += width.max(1);
+            }
+        }
+    }
+}
+
+/// Width of `s` in terminal columns, counted the way the table parser counts them.
+///
+/// ANSI escape sequences, as written by the terminal renderer, take no columns.
+pub(crate) fn display_width(s: &str) -> usize {
+    let mut width = 0;
+    let mut rest = s;
+    while let Some(c) = rest.chars().next() {
+        if let Some(len) = escape_len(rest) {
+            rest = &rest[len..];
+            continue;
+        }
+        width += c.width().unwrap_or(1);
+        rest = &rest[c.len_utf8()..];
+    }
+    width
+}
+
+/// Length of the CSI (`ESC [`) or OSC (`ESC ]`, ended by `ESC \`) sequence at the start of `s`.
+pub(crate) fn escape_len(s: &str) -> Option<usize> {
+    let rest = s.strip_prefix('\x1b')?;
+    if let Some(csi) = rest.strip_prefix('[') {
+        csi.find(|c: char| ('@'..='~').contains(&c)).map(|end| end + 3)
+    } else if let Some(osc) = rest.strip_prefix(']') {
+        osc.find("\x1b\\").map(|end| end + 4)
+    } else {
+        None
+    }
+}
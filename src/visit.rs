@@ -0,0 +1,448 @@
+//! Traversal of the block tree: [`Visitor`] and [`VisitorMut`] walk it in place, [`Fold`] rebuilds it.
+//!
+//! Every trait method has a default that recurses into the node's children through the matching
+//! `walk_*`, `walk_*_mut` or `fold_*` function, so an implementation only overrides the nodes it cares
+//! about and calls the walk function itself to keep descending.
+//!
+//! ```
+//! use parserst::visit::{Visitor, walk_inline};
+//! use parserst::{Inline, parse};
+//!
+//! struct Links(Vec<String>);
+//!
+//! impl Visitor for Links {
+//!     fn visit_inline(&mut self, inline: &Inline) {
+//!         if let Inline::Link { url, .. } = inline {
+//!             self.0.push(url.to_string());
+//!         }
+//!         walk_inline(self, inline);
+//!     }
+//! }
+//!
+//! let blocks = parse(".. note::\n\n   See `docs <https://example.com>`_.\n").unwrap();
+//! let mut links = Links(Vec::new());
+//! links.visit_blocks(&blocks);
+//! assert_eq!(links.0, ["https://example.com"]);
+//! ```
+
+use crate::{Block, Field, Inline, OptionItem, PyObject, TableCell};
+
+/// Read-only traversal of a block tree.
+pub trait Visitor {
+    fn visit_blocks(&mut self, blocks: &[Block]) {
+        walk_blocks(self, blocks);
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        walk_block(self, block);
+    }
+
+    fn visit_inlines(&mut self, inlines: &[Inline]) {
+        walk_inlines(self, inlines);
+    }
+
+    fn visit_inline(&mut self, inline: &Inline) {
+        walk_inline(self, inline);
+    }
+
+    fn visit_field(&mut self, field: &Field) {
+        walk_field(self, field);
+    }
+
+    fn visit_option_item(&mut self, item: &OptionItem) {
+        walk_option_item(self, item);
+    }
+
+    fn visit_table_cell(&mut self, cell: &TableCell) {
+        walk_table_cell(self, cell);
+    }
+
+    fn visit_py_object(&mut self, obj: &PyObject) {
+        walk_py_object(self, obj);
+    }
+}
+
+pub fn walk_blocks<V: Visitor + ?Sized>(v: &mut V, blocks: &[Block]) {
+    for block in blocks {
+        v.visit_block(block);
+    }
+}
+
+/// Visit the children of `block`: its inlines, nested blocks, fields, table cells or object.
+pub fn walk_block<V: Visitor + ?Sized>(v: &mut V, block: &Block) {
+    match block {
+        Block::Heading { inlines, .. } | Block::Paragraph(inlines) => v.visit_inlines(inlines),
+        Block::List { items, .. } => items.iter().for_each(|item| v.visit_inlines(item)),
+        Block::Quote(children)
+        | Block::Comment(children)
+        | Block::Directive { content: children, .. }
+        | Block::Footnote { content: children, .. } => v.visit_blocks(children),
+        Block::Table { headers, rows, .. } => headers
+            .iter()
+            .chain(rows)
+            .flatten()
+            .for_each(|cell| v.visit_table_cell(cell)),
+        Block::FieldList { fields } => fields.iter().for_each(|field| v.visit_field(field)),
+        Block::OptionList { items } => items.iter().for_each(|item| v.visit_option_item(item)),
+        Block::PyObject(obj) => v.visit_py_object(obj),
+        Block::CodeBlock(_) | Block::LiteralBlock(_) | Block::Raw { .. } | Block::Target { .. } => {}
+    }
+}
+
+pub fn walk_inlines<V: Visitor + ?Sized>(v: &mut V, inlines: &[Inline]) {
+    for inline in inlines {
+        v.visit_inline(inline);
+    }
+}
+
+pub fn walk_inline<V: Visitor + ?Sized>(v: &mut V, inline: &Inline) {
+    match inline {
+        Inline::Em(children) | Inline::Strong(children) | Inline::Link { text: children, .. } => {
+            v.visit_inlines(children)
+        }
+        Inline::Text(_) | Inline::Code(_) | Inline::FootnoteRef(_) => {}
+    }
+}
+
+pub fn walk_field<V: Visitor + ?Sized>(v: &mut V, field: &Field) {
+    v.visit_blocks(&field.body);
+}
+
+pub fn walk_option_item<V: Visitor + ?Sized>(v: &mut V, item: &OptionItem) {
+    v.visit_blocks(&item.body);
+}
+
+pub fn walk_table_cell<V: Visitor + ?Sized>(v: &mut V, cell: &TableCell) {
+    v.visit_blocks(&cell.content);
+}
+
+pub fn walk_py_object<V: Visitor + ?Sized>(v: &mut V, obj: &PyObject) {
+    v.visit_blocks(&obj.content);
+}
+
+/// In-place mutable traversal of a block tree.
+pub trait VisitorMut {
+    fn visit_blocks_mut(&mut self, blocks: &mut [Block]) {
+        walk_blocks_mut(self, blocks);
+    }
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        walk_block_mut(self, block);
+    }
+
+    fn visit_inlines_mut(&mut self, inlines: &mut [Inline]) {
+        walk_inlines_mut(self, inlines);
+    }
+
+    fn visit_inline_mut(&mut self, inline: &mut Inline) {
+        walk_inline_mut(self, inline);
+    }
+
+    fn visit_field_mut(&mut self, field: &mut Field) {
+        walk_field_mut(self, field);
+    }
+
+    fn visit_option_item_mut(&mut self, item: &mut OptionItem) {
+        walk_option_item_mut(self, item);
+    }
+
+    fn visit_table_cell_mut(&mut self, cell: &mut TableCell) {
+        walk_table_cell_mut(self, cell);
+    }
+
+    fn visit_py_object_mut(&mut self, obj: &mut PyObject) {
+        walk_py_object_mut(self, obj);
+    }
+}
+
+pub fn walk_blocks_mut<V: VisitorMut + ?Sized>(v: &mut V, blocks: &mut [Block]) {
+    for block in blocks {
+        v.visit_block_mut(block);
+    }
+}
+
+pub fn walk_block_mut<V: VisitorMut + ?Sized>(v: &mut V, block: &mut Block) {
+    match block {
+        Block::Heading { inlines, .. } | Block::Paragraph(inlines) => v.visit_inlines_mut(inlines),
+        Block::List { items, .. } => items.iter_mut().for_each(|item| v.visit_inlines_mut(item)),
+        Block::Quote(children)
+        | Block::Comment(children)
+        | Block::Directive { content: children, .. }
+        | Block::Footnote { content: children, .. } => v.visit_blocks_mut(children),
+        Block::Table { headers, rows, .. } => headers
+            .iter_mut()
+            .chain(rows)
+            .flatten()
+            .for_each(|cell| v.visit_table_cell_mut(cell)),
+        Block::FieldList { fields } => fields.iter_mut().for_each(|field| v.visit_field_mut(field)),
+        Block::OptionList { items } => items.iter_mut().for_each(|item| v.visit_option_item_mut(item)),
+        Block::PyObject(obj) => v.visit_py_object_mut(obj),
+        Block::CodeBlock(_) | Block::LiteralBlock(_) | Block::Raw { .. } | Block::Target { .. } => {}
+    }
+}
+
+pub fn walk_inlines_mut<V: VisitorMut + ?Sized>(v: &mut V, inlines: &mut [Inline]) {
+    for inline in inlines {
+        v.visit_inline_mut(inline);
+    }
+}
+
+pub fn walk_inline_mut<V: VisitorMut + ?Sized>(v: &mut V, inline: &mut Inline) {
+    match inline {
+        Inline::Em(children) | Inline::Strong(children) | Inline::Link { text: children, .. } => {
+            v.visit_inlines_mut(children)
+        }
+        Inline::Text(_) | Inline::Code(_) | Inline::FootnoteRef(_) => {}
+    }
+}
+
+pub fn walk_field_mut<V: VisitorMut + ?Sized>(v: &mut V, field: &mut Field) {
+    v.visit_blocks_mut(&mut field.body);
+}
+
+pub fn walk_option_item_mut<V: VisitorMut + ?Sized>(v: &mut V, item: &mut OptionItem) {
+    v.visit_blocks_mut(&mut item.body);
+}
+
+pub fn walk_table_cell_mut<V: VisitorMut + ?Sized>(v: &mut V, cell: &mut TableCell) {
+    v.visit_blocks_mut(&mut cell.content);
+}
+
+pub fn walk_py_object_mut<V: VisitorMut + ?Sized>(v: &mut V, obj: &mut PyObject) {
+    v.visit_blocks_mut(&mut obj.content);
+}
+
+/// Rebuilding traversal that takes a tree by value and returns the transformed tree.
+///
+/// Override [`Fold::fold_blocks`] or [`Fold::fold_inlines`] to drop nodes or splice in several
+/// nodes where there was one.
+pub trait Fold<'a> {
+    fn fold_blocks(&mut self, blocks: Vec<Block<'a>>) -> Vec<Block<'a>> {
+        fold_blocks(self, blocks)
+    }
+
+    fn fold_block(&mut self, block: Block<'a>) -> Block<'a> {
+        fold_block(self, block)
+    }
+
+    fn fold_inlines(&mut self, inlines: Vec<Inline<'a>>) -> Vec<Inline<'a>> {
+        fold_inlines(self, inlines)
+    }
+
+    fn fold_inline(&mut self, inline: Inline<'a>) -> Inline<'a> {
+        fold_inline(self, inline)
+    }
+
+    fn fold_field(&mut self, field: Field<'a>) -> Field<'a> {
+        fold_field(self, field)
+    }
+
+    fn fold_option_item(&mut self, item: OptionItem<'a>) -> OptionItem<'a> {
+        fold_option_item(self, item)
+    }
+
+    fn fold_table_cell(&mut self, cell: TableCell<'a>) -> TableCell<'a> {
+        fold_table_cell(self, cell)
+    }
+
+    fn fold_py_object(&mut self, obj: PyObject<'a>) -> PyObject<'a> {
+        fold_py_object(self, obj)
+    }
+}
+
+pub fn fold_blocks<'a, F: Fold<'a> + ?Sized>(f: &mut F, blocks: Vec<Block<'a>>) -> Vec<Block<'a>> {
+    blocks.into_iter().map(|block| f.fold_block(block)).collect()
+}
+
+/// Rebuild `block` from its folded children.
+pub fn fold_block<'a, F: Fold<'a> + ?Sized>(f: &mut F, block: Block<'a>) -> Block<'a> {
+    match block {
+        Block::Heading { level, inlines, id } => Block::Heading { level, inlines: f.fold_inlines(inlines), id },
+        Block::Paragraph(inlines) => Block::Paragraph(f.fold_inlines(inlines)),
+        Block::List { kind, items } => {
+            Block::List { kind, items: items.into_iter().map(|item| f.fold_inlines(item)).collect() }
+        }
+        Block::Quote(children) => Block::Quote(f.fold_blocks(children)),
+        Block::Comment(children) => Block::Comment(f.fold_blocks(children)),
+        Block::Footnote { label, content } => Block::Footnote { label, content: f.fold_blocks(content) },
+        Block::Directive { name, argument, content } => {
+            Block::Directive { name, argument, content: f.fold_blocks(content) }
+        }
+        Block::Table { headers, rows, widths } => {
+            let mut fold_rows = |rows: Vec<Vec<TableCell<'a>>>| -> Vec<Vec<TableCell<'a>>> {
+                rows.into_iter()
+                    .map(|row| row.into_iter().map(|cell| f.fold_table_cell(cell)).collect())
+                    .collect()
+            };
+            let headers = fold_rows(headers);
+            Block::Table { headers, rows: fold_rows(rows), widths }
+        }
+        Block::FieldList { fields } => {
+            Block::FieldList { fields: fields.into_iter().map(|field| f.fold_field(field)).collect() }
+        }
+        Block::OptionList { items } => {
+            Block::OptionList { items: items.into_iter().map(|item| f.fold_option_item(item)).collect() }
+        }
+        Block::PyObject(obj) => Block::PyObject(Box::new(f.fold_py_object(*obj))),
+        leaf @ (Block::CodeBlock(_) | Block::LiteralBlock(_) | Block::Raw { .. } | Block::Target { .. }) => leaf,
+    }
+}
+
+pub fn fold_inlines<'a, F: Fold<'a> + ?Sized>(f: &mut F, inlines: Vec<Inline<'a>>) -> Vec<Inline<'a>> {
+    inlines.into_iter().map(|inline| f.fold_inline(inline)).collect()
+}
+
+pub fn fold_inline<'a, F: Fold<'a> + ?Sized>(f: &mut F, inline: Inline<'a>) -> Inline<'a> {
+    match inline {
+        Inline::Em(children) => Inline::Em(f.fold_inlines(children)),
+        Inline::Strong(children) => Inline::Strong(f.fold_inlines(children)),
+        Inline::Link { text, url } => Inline::Link { text: f.fold_inlines(text), url },
+        leaf @ (Inline::Text(_) | Inline::Code(_) | Inline::FootnoteRef(_)) => leaf,
+    }
+}
+
+pub fn fold_field<'a, F: Fold<'a> + ?Sized>(f: &mut F, field: Field<'a>) -> Field<'a> {
+    Field { body: f.fold_blocks(field.body), ..field }
+}
+
+pub fn fold_option_item<'a, F: Fold<'a> + ?Sized>(f: &mut F, item: OptionItem<'a>) -> OptionItem<'a> {
+    OptionItem { body: f.fold_blocks(item.body), ..item }
+}
+
+pub fn fold_table_cell<'a, F: Fold<'a> + ?Sized>(f: &mut F, cell: TableCell<'a>) -> TableCell<'a> {
+    TableCell { content: f.fold_blocks(cell.content), ..cell }
+}
+
+pub fn fold_py_object<'a, F: Fold<'a> + ?Sized>(f: &mut F, obj: PyObject<'a>) -> PyObject<'a> {
+    PyObject { content: f.fold_blocks(obj.content), ..obj }
+}
+
+/// Call `f` on every inline in `blocks`, outer nodes before the nodes nested in them.
+///
+/// This reaches headings, list items, definition terms, table cells, field and option bodies and the
+/// content of directives and Python objects alike.
+pub fn for_each_inline(blocks: &[Block], f: impl FnMut(&Inline)) {
+    struct Each<F>(F);
+    impl<F: FnMut(&Inline)> Visitor for Each<F> {
+        fn visit_inline(&mut self, inline: &Inline) {
+            (self.0)(inline);
+            walk_inline(self, inline);
+        }
+    }
+    Each(f).visit_blocks(blocks);
+}
+
+/// Call `f` on every inline in `blocks` with mutable access, outer nodes first.
+pub fn for_each_inline_mut(blocks: &mut [Block], f: impl FnMut(&mut Inline)) {
+    struct Each<F>(F);
+    impl<F: FnMut(&mut Inline)> VisitorMut for Each<F> {
+        fn visit_inline_mut(&mut self, inline: &mut Inline) {
+            (self.0)(inline);
+            walk_inline_mut(self, inline);
+        }
+    }
+    Each(f).visit_blocks_mut(blocks);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    const DOC: &str = r#"
+Title *one*
+===========
+
+Args:
+    count (int): How many.
+
++-----------+
+| **cell**  |
++-----------+
+
+:param x: A `link <https://e.com>`_.
+
+.. py:function:: f()
+
+   Body ``code``.
+
+.. a comment
+"#;
+
+    #[test]
+    fn for_each_inline_reaches_every_inline() {
+        let blocks = parse(DOC).unwrap();
+        let mut texts = Vec::new();
+        for_each_inline(&blocks, |inline| {
+            if let Inline::Text(t) | Inline::Code(t) = inline {
+                texts.push(t.to_string());
+            }
+        });
+        for expected in [
+            "Title ",
+            "one",
+            "Args",
+            "count",
+            "int",
+            "cell",
+            "link",
+            "code",
+            "a comment",
+        ] {
+            assert!(texts.iter().any(|t| t == expected), "missing {expected:?} in {texts:?}");
+        }
+    }
+
+    #[test]
+    fn visitor_mut_rewrites_in_place() {
+        let mut blocks = parse(DOC).unwrap();
+        for_each_inline_mut(&mut blocks, |inline| {
+            if let Inline::Text(t) = inline {
+                *t = t.to_uppercase().into();
+            }
+        });
+        let mut texts = Vec::new();
+        for_each_inline(&blocks, |inline| {
+            if let Inline::Text(t) = inline {
+                texts.push(t.to_string());
+            }
+        });
+        assert!(texts.iter().all(|t| *t == t.to_uppercase()));
+        assert!(texts.contains(&"CELL".to_string()));
+    }
+
+    #[test]
+    fn fold_can_drop_and_rebuild_nodes() {
+        struct StripComments;
+        impl<'a> Fold<'a> for StripComments {
+            fn fold_blocks(&mut self, blocks: Vec<Block<'a>>) -> Vec<Block<'a>> {
+                blocks
+                    .into_iter()
+                    .filter(|block| !matches!(block, Block::Comment(_)))
+                    .map(|block| self.fold_block(block))
+                    .collect()
+            }
+
+            fn fold_inline(&mut self, inline: Inline<'a>) -> Inline<'a> {
+                match inline {
+                    Inline::Code(code) => Inline::Text(code),
+                    other => fold_inline(self, other),
+                }
+            }
+        }
+
+        let blocks = StripComments.fold_blocks(parse(DOC).unwrap());
+        assert!(!blocks.iter().any(|b| matches!(b, Block::Comment(_))));
+        let Some(Block::PyObject(obj)) = blocks.last() else {
+            panic!("expected the py:function last");
+        };
+        assert_eq!(
+            obj.content,
+            [Block::Paragraph(vec![
+                Inline::Text("Body ".into()),
+                Inline::Text("code".into()),
+                Inline::Text(".".into())
+            ])]
+        );
+    }
+}